@@ -4,27 +4,154 @@
 // that can be found in the LICENSE file.
 //
 
+mod repl;
+
 use colored::Colorize;
 use mate_rs::{
-    calculator::Calculator, environment::Environment, lexer::Lexer, monitor::Monitor, token::Token,
+    calculator::Calculator,
+    environment::Environment,
+    errors::Error,
+    formatter::{Base, Formatter, Notation},
+    lexer::Lexer,
+    monitor::{self, Monitor},
+    plot::{Plot, PlotStyle},
+    rpc::{value_json, Server},
+    script::Script,
+    session::Session,
+    table::Table,
+    token::Sub,
+    trace::Trace,
+    value::Value,
 };
+use repl::ReplHelper;
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+use serde_json::json;
 use std::{
     env,
-    io::{stdin, stdout, Write},
+    io::{stdin, stdout, BufRead, IsTerminal, Write},
+    path::Path,
+    process,
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// The output format of batch mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Output {
+    Plain,
+    Json,
+    Csv,
+}
+
+// The format of parsed tokens, that are shown with `--tokens`.
+// Plain `-t` keeps the list of tokens, the others are chosen with `--tokens=FORMAT`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tokens {
+    List,
+    Tree,
+    Dot,
+    Json,
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     let mut log_tokens = false;
+    let mut tokens = Tokens::List;
+    let mut show_steps = false;
     let mut expression: Option<String> = None;
+    let mut preload: Vec<String> = Vec::new();
+    let mut script: Option<String> = None;
+    let mut output: Option<Output> = None;
+    let mut serve = false;
+    let mut formatter = Formatter::new();
+    let mut plot: Option<String> = None;
+    let mut table: Option<String> = None;
+    let mut plot_style = PlotStyle::Braille;
 
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
             "-t" | "--tokens" => log_tokens = true,
+            arg if arg.starts_with("--tokens=") => {
+                tokens = match arg.trim_start_matches("--tokens=") {
+                    "tree" => Tokens::Tree,
+                    "dot" => Tokens::Dot,
+                    "json" => Tokens::Json,
+                    _ => {
+                        eprintln!("{} Expected tree, dot or json for: {}", "[!]".bold().red(), arg.red());
+                        process::exit(1);
+                    }
+                };
+                log_tokens = true;
+            }
+            "-s" | "--steps" => show_steps = true,
+            "-f" | "--file" => match args.get(i + 1) {
+                Some(file) => {
+                    script = Some(file.clone());
+                    i += 1;
+                }
+                None => {
+                    eprintln!("{} Missing file for: {}", "[!]".bold().red(), args[i].red());
+                    process::exit(1);
+                }
+            },
+            "--serve" => serve = true,
+            "-p" | "--precision" | "--significant" | "--notation" | "--base" => {
+                let setting = args[i].trim_start_matches('-');
+                let setting = if setting == "p" { "precision" } else { setting };
+                let value = args.get(i + 1).map(String::as_str).unwrap_or_default();
+                formatter = match format_setting(formatter, setting, value) {
+                    Ok(formatter) => formatter,
+                    Err(e) => {
+                        eprintln!("{} {}", "[!]".bold().red(), e.red());
+                        process::exit(1);
+                    }
+                };
+                i += 1;
+            }
+            "--group" => formatter = formatter.with_grouping(true),
+            "--ascii" => plot_style = PlotStyle::Ascii,
+            "--plot" | "--table" => match args.get(i + 1) {
+                Some(spec) => {
+                    if args[i] == "--plot" {
+                        plot = Some(spec.clone());
+                    } else {
+                        table = Some(spec.clone());
+                    }
+                    i += 1;
+                }
+                None => {
+                    eprintln!("{} Missing expression for: {}", "[!]".bold().red(), args[i].red());
+                    process::exit(1);
+                }
+            },
+            "-o" | "--output" => {
+                output = match args.get(i + 1).map(String::as_str) {
+                    Some("plain") => Some(Output::Plain),
+                    Some("json") => Some(Output::Json),
+                    Some("csv") => Some(Output::Csv),
+                    _ => {
+                        eprintln!(
+                            "{} Expected json, csv or plain for: {}",
+                            "[!]".bold().red(),
+                            args[i].red()
+                        );
+                        process::exit(1);
+                    }
+                };
+                i += 1;
+            }
+            "-l" | "--load" => match args.get(i + 1) {
+                Some(file) => {
+                    preload.push(file.clone());
+                    i += 1;
+                }
+                None => {
+                    eprintln!("{} Missing file for: {}", "[!]".bold().red(), args[i].red());
+                    process::exit(1);
+                }
+            },
             "-h" | "--help" => {
                 print_help();
                 return;
@@ -33,6 +160,9 @@ fn main() {
                 println!("mate v{VERSION}");
                 return;
             }
+            arg if arg.ends_with(".mate") && Path::new(arg).is_file() => {
+                script = Some(arg.to_string());
+            }
             arg if !arg.starts_with('-') => {
                 expression = Some(args[i..].join(" "));
                 break;
@@ -45,15 +175,64 @@ fn main() {
         i += 1;
     }
 
-    // For one-off expressions, use empty environment
+    // Preload saved variables, for both one-off expressions and REPL.
+    let mut env = Environment::new();
+    for file in preload.iter() {
+        if let Err(e) = env.load(Path::new(file)) {
+            print_err(&e.to_string());
+            process::exit(1);
+        }
+    }
+
+    if serve {
+        if let Err(e) = Server::with_environment(env).serve(stdin().lock(), stdout()) {
+            eprintln!("{} {}", "[!]".bold().red(), e.to_string().red());
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(file) = script {
+        process::exit(run_script(&file, &mut env, &formatter));
+    }
+
+    if let Some(spec) = plot {
+        // Without a known terminal size, the chart fits the usual 80 columns.
+        let width = env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).unwrap_or(80);
+        if !print_plot(&spec, &env, plot_style, width) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(spec) = table {
+        let output = output.unwrap_or(Output::Plain);
+        process::exit(print_table(&spec, &env, output, &formatter));
+    }
+
     if let Some(expr) = expression {
-        let mut env = Environment::new();
-        execute_calculator(&expr, log_tokens, &mut env);
+        // Exports of tokens are printed alone, to be piped into other tools.
+        if log_tokens && matches!(tokens, Tokens::Dot | Tokens::Json) {
+            process::exit(export_tokens(&expr, tokens));
+        }
+
+        match output {
+            Some(output) => process::exit(run_batch(vec![expr], output, &mut env, &formatter)),
+            None => {
+                execute_calculator(&expr, log_tokens.then_some(tokens), show_steps, &mut env, &formatter);
+            }
+        }
         return;
     }
 
+    // Without a terminal, expressions are read from stdin in batch mode.
+    if !stdin().is_terminal() || output.is_some() {
+        let lines = stdin().lock().lines().map_while(Result::ok);
+        let output = output.unwrap_or(Output::Plain);
+        process::exit(run_batch(lines, output, &mut env, &formatter));
+    }
+
     // REPL mode - maintain environment across expressions
-    let mut env = Environment::new();
 
     println!(
         "{}\n",
@@ -61,73 +240,535 @@ fn main() {
     );
     println!("Type 'help' for available commands, 'quit' to exit.\n");
 
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("{} {}", "[!]".bold().red(), e.to_string().red());
+            process::exit(1);
+        }
+    };
+    editor.set_helper(Some(ReplHelper::default()));
+
+    // Results are kept in the session, as `ans` and `_1`, `_2`, ...
+    let mut session = Session::with_environment(env);
+
+    // History is kept between sessions, in the data directory of user.
+    let history = repl::history_path();
+    if let Some(path) = history.as_ref() {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = editor.load_history(path);
+    }
+
     loop {
-        print!("{} ", ">>>".bold());
-        let _ = stdout().flush();
-
-        let mut input = String::new();
-        match stdin().read_line(&mut input) {
-            Err(e) => println!("{} {}\n", "[!]".bold().red(), e.to_string().red()),
-            Ok(_) => {
-                let trimmed = input.trim();
-                if trimmed.is_empty() {
-                    continue;
-                }
+        if let Some(helper) = editor.helper_mut() {
+            helper.set_session(&session);
+            helper.set_formatter(formatter);
+        }
 
-                match trimmed.to_lowercase().as_str() {
-                    "quit" | "exit" | "q" => {
-                        println!("Goodbye!");
-                        break;
-                    }
-                    "help" | "h" | "?" => {
-                        print_repl_help();
-                    }
-                    "clear" | "cls" => {
-                        print!("\x1B[2J\x1B[1;1H");
-                        let _ = stdout().flush();
-                    }
-                    "tokens" => {
-                        log_tokens = !log_tokens;
-                        println!(
-                            "Token logging: {}\n",
-                            if log_tokens { "enabled" } else { "disabled" }
-                        );
+        let input = match editor.readline(&format!("{} ", ">>>".bold())) {
+            Ok(input) => input,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => {
+                println!("Goodbye!");
+                break;
+            }
+            Err(e) => {
+                print_err(&e.to_string());
+                break;
+            }
+        };
+
+        // Lines of multi-line input are joined, as a single expression.
+        let input = input.replace('\n', " ");
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let _ = editor.add_history_entry(trimmed);
+        if let Some(path) = history.as_ref() {
+            let _ = editor.save_history(path);
+        }
+
+        match trimmed.to_lowercase().as_str() {
+            "quit" | "exit" | "q" => {
+                println!("Goodbye!");
+                break;
+            }
+            "help" | "h" | "?" => {
+                print_repl_help();
+            }
+            "clear" | "cls" => {
+                print!("\x1B[2J\x1B[1;1H");
+                let _ = stdout().flush();
+            }
+            "tokens" => {
+                log_tokens = !log_tokens;
+                println!(
+                    "Token logging: {}\n",
+                    if log_tokens { "enabled" } else { "disabled" }
+                );
+            }
+            "steps" => {
+                show_steps = !show_steps;
+                println!(
+                    "Step-by-step evaluation: {}\n",
+                    if show_steps { "enabled" } else { "disabled" }
+                );
+            }
+            "reactive" => {
+                let env = session.environment_mut();
+                env.set_reactive(!env.is_reactive());
+                println!(
+                    "Reactive mode: {}\n",
+                    if env.is_reactive() { "enabled" } else { "disabled" }
+                );
+            }
+            "vars" | "variables" => {
+                print_variables(session.environment(), &formatter);
+            }
+            "history" => {
+                print_history(&session, &formatter);
+            }
+            "format" => {
+                print_format(&formatter);
+            }
+            "reset" => {
+                session.clear();
+                println!("All variables and history cleared.\n");
+            }
+            _ if format_command(trimmed).is_some() => {
+                let (setting, value) = format_command(trimmed).unwrap_or_default();
+                match format_setting(formatter, setting, value) {
+                    Ok(f) => {
+                        formatter = f;
+                        print_format(&formatter);
                     }
-                    "vars" | "variables" => {
-                        print_variables(&env);
+                    Err(e) => print_err(&e),
+                }
+            }
+            _ if file_command(trimmed, "plot").is_some() => {
+                let spec = file_command(trimmed, "plot").unwrap_or_default();
+                let width = editor.dimensions().map(|(columns, _)| columns as usize).unwrap_or(80);
+                print_plot(spec, session.environment(), plot_style, width);
+            }
+            _ if file_command(trimmed, "table").is_some() => {
+                let spec = file_command(trimmed, "table").unwrap_or_default();
+                print_table(spec, session.environment(), Output::Plain, &formatter);
+            }
+            _ if file_command(trimmed, "save").is_some() => {
+                let file = file_command(trimmed, "save").unwrap_or_default();
+                match session.environment().save(Path::new(file)) {
+                    Ok(()) => println!("Variables saved to {file}.\n"),
+                    Err(e) => print_err(&e.to_string()),
+                }
+            }
+            _ if file_command(trimmed, "load").is_some() => {
+                let file = file_command(trimmed, "load").unwrap_or_default();
+                match session.environment_mut().load(Path::new(file)) {
+                    Ok(()) => println!("Variables loaded from {file}.\n"),
+                    Err(e) => print_err(&e.to_string()),
+                }
+            }
+            _ => {
+                let input = session.expand(trimmed);
+                let env = session.environment_mut();
+                let log_tokens = log_tokens.then_some(tokens);
+                let result = execute_calculator(&input, log_tokens, show_steps, env, &formatter);
+                if let Some(value) = result {
+                    session.record(&input, value);
+                }
+            }
+        }
+    }
+}
+
+// Parses REPL commands with an argument, like `save vars.mate` or `plot x ^ 2 from 0 to 1`.
+// Assignments to variables named like the command, `save = 5`, aren't commands.
+fn file_command<'a>(input: &'a str, command: &str) -> Option<&'a str> {
+    let (name, file) = input.split_once(char::is_whitespace)?;
+    let file = file.trim();
+
+    if !name.eq_ignore_ascii_case(command) || file.is_empty() || file.starts_with('=') {
+        return None;
+    }
+
+    Some(file)
+}
+
+// Parses the REPL command, that changes a setting of result formatting: `format base hex`.
+fn format_command(input: &str) -> Option<(&str, &str)> {
+    let (name, rest) = input.split_once(char::is_whitespace)?;
+    if !name.eq_ignore_ascii_case("format") || rest.trim_start().starts_with('=') {
+        return None;
+    }
+
+    let rest = rest.trim();
+    Some(rest.split_once(char::is_whitespace).unwrap_or((rest, "")))
+}
+
+// Applies a setting of result formatting, shared by command-line options and the REPL.
+fn format_setting(formatter: Formatter, setting: &str, value: &str) -> Result<Formatter, String> {
+    let value = value.trim().to_lowercase();
+    let digits = || {
+        value
+            .parse::<usize>()
+            .map_err(|_| format!("error: expected an amount of digits for {setting}, got `{value}`"))
+    };
+
+    match setting.to_lowercase().as_str() {
+        "precision" => Ok(formatter.with_precision(digits()?)),
+        "significant" => Ok(formatter.with_significant(digits()?)),
+        "notation" => match value.as_str() {
+            "auto" => Ok(formatter.with_notation(Notation::Auto)),
+            "fixed" => Ok(formatter.with_notation(Notation::Fixed)),
+            "sci" | "scientific" => Ok(formatter.with_notation(Notation::Scientific)),
+            "eng" | "engineering" => Ok(formatter.with_notation(Notation::Engineering)),
+            _ => Err(format!("error: expected auto, fixed, sci or eng notation, got `{value}`")),
+        },
+        "grouping" => match value.as_str() {
+            "on" => Ok(formatter.with_grouping(true)),
+            "off" => Ok(formatter.with_grouping(false)),
+            _ => Err(format!("error: expected on or off for grouping, got `{value}`")),
+        },
+        "base" => match value.as_str() {
+            "dec" | "decimal" => Ok(formatter.with_base(Base::Decimal)),
+            "hex" | "hexadecimal" => Ok(formatter.with_base(Base::Hexadecimal)),
+            "bin" | "binary" => Ok(formatter.with_base(Base::Binary)),
+            "oct" | "octal" => Ok(formatter.with_base(Base::Octal)),
+            _ => Err(format!("error: expected dec, hex, bin or oct base, got `{value}`")),
+        },
+        "reset" => Ok(Formatter::new()),
+        _ => Err(format!(
+            "error: unknown format setting `{setting}`, expected precision, significant, notation, grouping, base or reset"
+        )),
+    }
+}
+
+// Executes the script file, and returns the exit code.
+// Results of non-assignment statements are printed, or the last result if there are none.
+fn run_script(file: &str, env: &mut Environment, formatter: &Formatter) -> i32 {
+    let text = match std::fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{} {}", "[!]".bold().red(), format!("error: {file}: {e}").red());
+            return 1;
+        }
+    };
+
+    let mut printed = false;
+    let result = Script::parse(&text).run(env, |statement, value| {
+        if !statement.is_assignment() {
+            println!("{}", formatter.format_value(value).green().bold());
+            printed = true;
+        }
+    });
+
+    match result {
+        Ok(last) => {
+            if let Some(value) = last.filter(|_| !printed) {
+                println!("{}", formatter.format_value(&value).green().bold());
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{} {}", "[!]".bold().red(), format!("{file}:{e}").red());
+            1
+        }
+    }
+}
+
+// Calculates each line of input, and prints one result per line in the [output] format,
+// with no colors or prompts. Returns the exit code: non-zero if any line has failed.
+fn run_batch<I>(lines: I, output: Output, env: &mut Environment, formatter: &Formatter) -> i32
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut failed = false;
+    if output == Output::Csv {
+        println!("input,value,number,error_kind,error_point,error_message");
+    }
+
+    for line in lines {
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+
+        let result = Lexer::lex(input).and_then(|sub| Calculator::calculate_value(sub, input, env));
+        failed |= result.is_err();
+
+        match output {
+            Output::Plain => match &result {
+                Ok(value) => println!("{}", formatter.format_value(value)),
+                Err(e) => println!("{}", headline(e)),
+            },
+            Output::Json => {
+                let record = match &result {
+                    Ok(value) => {
+                        let value = batch_value_json(value, formatter);
+                        json!({ "input": input, "value": value, "error": null })
                     }
-                    "reset" => {
-                        env.clear();
-                        println!("All variables cleared.\n");
+                    Err(e) => json!({ "input": input, "value": null, "error": error_json(input, e) }),
+                };
+                println!("{record}");
+            }
+            Output::Csv => {
+                let (value, number, kind, point, message) = match &result {
+                    Ok(value) => {
+                        let number = value.as_number().filter(|n| n.is_finite());
+                        let number = number.map(|n| n.to_string()).unwrap_or_default();
+                        let value = formatter.format_value(value);
+                        (value, number, String::new(), String::new(), String::new())
                     }
-                    _ => execute_calculator(trimmed, log_tokens, &mut env),
-                }
+                    Err(e) => (
+                        String::new(),
+                        String::new(),
+                        format!("{:?}", e.kind()),
+                        error_point(input, e).map(|p| p.to_string()).unwrap_or_default(),
+                        headline(e),
+                    ),
+                };
+                let fields = [input.to_string(), value, number, kind, point, message];
+                let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                println!("{}", row.join(","));
             }
-        };
+        }
     }
+
+    i32::from(failed)
 }
 
-fn execute_calculator(input: &str, log_tokens: bool, env: &mut Environment) {
+// Encodes the value like the JSON-RPC server does, with the text of [formatter],
+// so finite numbers can be read as numbers.
+fn batch_value_json(value: &Value, formatter: &Formatter) -> serde_json::Value {
+    let mut json = value_json(value);
+    json["value"] = json!(formatter.format_value(value));
+    json
+}
+
+// The first line of error message, without the explanation below it.
+fn headline(e: &Error) -> String {
+    e.to_string().lines().next().unwrap_or_default().trim().to_string()
+}
+
+// The index of character in [input], that the error starts at.
+fn error_point(input: &str, e: &Error) -> Option<usize> {
+    e.span().map(|span| span.chars(input).0)
+}
+
+fn error_json(input: &str, e: &Error) -> serde_json::Value {
+    json!({
+        "kind": format!("{:?}", e.kind()),
+        "point": error_point(input, e),
+        "span": e.span().map(|span| json!({ "start": span.start, "end": span.end })),
+        "message": headline(e),
+        "details": e.to_string().trim_end(),
+    })
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+
+    field.to_string()
+}
+
+// Calculates and prints the result of input, returns the result on success.
+fn execute_calculator(
+    input: &str,
+    log_tokens: Option<Tokens>,
+    show_steps: bool,
+    env: &mut Environment,
+    formatter: &Formatter,
+) -> Option<Value> {
     let sub = match Lexer::lex(input) {
         Ok(tt) => tt,
-        Err(e) => return print_err(&e.to_string()),
+        Err(e) => {
+            print_err(&e.to_string());
+            return None;
+        }
     };
 
-    if log_tokens {
-        print_tokens(&sub.tokens);
+    if let Some(format) = log_tokens {
+        print_tokens(&sub, format);
+    }
+
+    if show_steps {
+        return match Calculator::calculate_traced(sub, input, env) {
+            Ok((v, trace)) => {
+                print_steps(&trace);
+                println!("{}\n", formatter.format_value(&v).green().bold());
+                Some(v)
+            }
+            Err(e) => {
+                print_err(&e.to_string());
+                None
+            }
+        };
+    }
+
+    // In reactive mode, an assignment recomputes every dependent variable.
+    let assigned = match sub.tokens.as_slice() {
+        [name, assign, ..] if name.is_identifier() && assign.is_assign() => Some(name.literal.clone()),
+        _ => None,
+    };
+
+    match Calculator::calculate_value(sub, input, env) {
+        Ok(v) => {
+            println!("{}", formatter.format_value(&v).green().bold());
+            if let Some(name) = assigned.filter(|_| env.is_reactive()) {
+                print_recomputed(env, &name, formatter);
+            }
+            println!();
+            Some(v)
+        }
+        Err(e) => {
+            print_err(&e.to_string());
+            None
+        }
+    }
+}
+
+// Draws the chart of plot command, that fits the [width] of terminal.
+// Returns false if it couldn't be drawn.
+fn print_plot(spec: &str, env: &Environment, style: PlotStyle, width: usize) -> bool {
+    let chart = Plot::parse(spec, env)
+        .and_then(|plot| plot.with_size(width, 20).with_style(style).render(env));
+
+    match chart {
+        Ok(chart) => {
+            println!("{chart}\n");
+            true
+        }
+        Err(e) => {
+            print_err(&e.to_string());
+            false
+        }
     }
+}
 
-    match Calculator::calculate_with_env(sub, input, env) {
-        Ok(v) => println!("{}\n", v.to_string().green().bold()),
-        Err(e) => print_err(&e.to_string()),
+// Prints the table of table command, in the [output] format. Failed cells hold the
+// first line of their error. Returns the exit code: non-zero if the table couldn't be made.
+fn print_table(spec: &str, env: &Environment, output: Output, formatter: &Formatter) -> i32 {
+    let table = Table::parse(spec, env).and_then(|table| {
+        let rows = table.rows(env)?;
+        Ok((table, rows))
+    });
+    let (table, rows) = match table {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("{} {}", "[!]".bold().red(), e.to_string().red());
+            return 1;
+        }
     };
+
+    let cell = |result: &Result<f64, Error>| match result {
+        Ok(value) => formatter.format(*value),
+        Err(e) => headline(e),
+    };
+
+    match output {
+        Output::Plain => {
+            let header: Vec<String> = std::iter::once(table.variable().to_string())
+                .chain(table.expressions().iter().cloned())
+                .collect();
+            let cells: Vec<Vec<String>> = rows
+                .iter()
+                .map(|(x, results)| {
+                    std::iter::once(formatter.format(*x)).chain(results.iter().map(cell)).collect()
+                })
+                .collect();
+
+            let widths: Vec<usize> = (0..header.len())
+                .map(|i| {
+                    let width = |line: &Vec<String>| line[i].chars().count();
+                    cells.iter().map(width).chain(std::iter::once(width(&header))).max().unwrap_or(0)
+                })
+                .collect();
+
+            let line: Vec<String> = header.iter().zip(&widths).map(|(h, w)| format!("{h:<w$}")).collect();
+            println!("{}", line.join(" │ ").trim_end().bold());
+            let rule: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+            println!("{}", rule.join("─┼─"));
+
+            for ((_, results), row) in rows.iter().zip(&cells) {
+                // Numbers are aligned to the right, and errors to the left.
+                let mut line: Vec<String> = vec![format!("{:>w$}", row[0], w = widths[0])];
+                for ((result, text), w) in results.iter().zip(&row[1..]).zip(&widths[1..]) {
+                    match result {
+                        Ok(_) => line.push(format!("{text:>w$}")),
+                        Err(_) => line.push(format!("{:<w$}", text.red())),
+                    }
+                }
+                println!("{}", line.join(" │ ").trim_end());
+            }
+            println!();
+        }
+        Output::Json => {
+            // Values are JSON numbers, and their text by the formatter is kept beside them.
+            // JSON has no non-finite numbers, so those have only the text.
+            for (x, results) in rows.iter() {
+                let results: Vec<serde_json::Value> = results
+                    .iter()
+                    .zip(table.expressions())
+                    .map(|(result, expression)| match result {
+                        Ok(value) => json!({
+                            "value": Some(*value).filter(|v| v.is_finite()),
+                            "text": formatter.format(*value),
+                            "error": null,
+                        }),
+                        Err(e) => json!({ "value": null, "text": null, "error": error_json(expression, e) }),
+                    })
+                    .collect();
+                println!("{}", json!({ "x": x, "text": formatter.format(*x), "results": results }));
+            }
+        }
+        Output::Csv => {
+            let header: Vec<String> = std::iter::once(table.variable())
+                .chain(table.expressions().iter().map(String::as_str))
+                .map(csv_field)
+                .collect();
+            println!("{}", header.join(","));
+
+            for (x, results) in rows.iter() {
+                let row: Vec<String> = std::iter::once(formatter.format(*x))
+                    .chain(results.iter().map(cell))
+                    .map(|field| csv_field(&field))
+                    .collect();
+                println!("{}", row.join(","));
+            }
+        }
+    }
+
+    0
+}
+
+fn print_recomputed(env: &Environment, name: &str, formatter: &Formatter) {
+    for dependent in env.dependents(name) {
+        if let Some(value) = env.get_value(&dependent) {
+            let value = formatter.format_value(value);
+            println!("  {} {} = {}", "↻".cyan(), dependent.cyan(), value);
+        }
+    }
+}
+
+fn print_steps(trace: &Trace) {
+    let reductions = trace.reductions();
+    println!("  {}", reductions[0].dimmed());
+    for expression in reductions.iter().skip(1) {
+        println!("{} {}", "→".cyan(), expression.dimmed());
+    }
 }
 
 fn print_err(msg: &str) {
     println!("{} {}\n", "[!]".bold().red(), msg.red())
 }
 
-fn print_variables(env: &Environment) {
+fn print_variables(env: &Environment, formatter: &Formatter) {
     let names = env.names();
     if names.is_empty() {
         println!("No variables defined.\n");
@@ -135,34 +776,122 @@ fn print_variables(env: &Environment) {
     }
     println!("\n{}", "Variables:".bold());
     for name in names {
-        if let Some(value) = env.get(name) {
-            println!("  {} = {}", name.cyan(), value);
+        if let Some(value) = env.get_value(name) {
+            let value = formatter.format_value(value);
+            match env.formula(name) {
+                Some(formula) => println!(
+                    "  {} = {} {}",
+                    name.cyan(),
+                    value,
+                    format!("({})", formula.expression()).dimmed()
+                ),
+                None => println!("  {} = {}", name.cyan(), value),
+            }
         }
     }
     println!();
 }
 
-fn print_tokens(tt: &[Token]) {
-    println!("---------------");
-    for t in tt.iter() {
-        println!("{}", t.to_string(0));
+fn print_history(session: &Session, formatter: &Formatter) {
+    if session.history().is_empty() {
+        println!("No history yet.\n");
+        return;
+    }
+    println!("\n{}", "History:".bold());
+    for entry in session.history() {
+        println!(
+            "  {} {} {}",
+            format!("_{}", entry.number).cyan(),
+            entry.input,
+            format!("= {}", formatter.format_value(&entry.value)).green()
+        );
+    }
+    println!();
+}
+
+fn print_format(formatter: &Formatter) {
+    let digits = match (formatter.precision(), formatter.significant()) {
+        (Some(p), _) => format!("{p} decimals"),
+        (None, Some(s)) => format!("{s} significant digits"),
+        (None, None) => String::from("auto"),
+    };
+
+    println!("\n{}", "Format:".bold());
+    println!("  {}    {}", "digits".cyan(), digits);
+    println!("  {}  {:?}", "notation".cyan(), formatter.notation());
+    println!("  {}  {}", "grouping".cyan(), if formatter.grouping() { "on" } else { "off" });
+    println!("  {}      {:?}", "base".cyan(), formatter.base());
+    println!();
+}
+
+fn print_tokens(sub: &Sub, format: Tokens) {
+    match format {
+        Tokens::List => {
+            println!("---------------");
+            for t in sub.tokens.iter() {
+                println!("{}", t.to_string(0));
+            }
+            println!("---------------\n");
+        }
+        Tokens::Tree => {
+            println!("---------------");
+            println!("{}", monitor::to_tree(sub));
+            println!("---------------\n");
+        }
+        Tokens::Dot => println!("{}", monitor::to_dot(sub)),
+        Tokens::Json => println!("{:#}", monitor::to_json(sub)),
+    }
+}
+
+// Prints only the tokens of input, and returns the exit code: non-zero if it can't be lexed.
+fn export_tokens(input: &str, format: Tokens) -> i32 {
+    match Lexer::lex(input) {
+        Ok(sub) => {
+            print_tokens(&sub, format);
+            0
+        }
+        Err(e) => {
+            eprintln!("{} {}", "[!]".bold().red(), e.to_string().red());
+            1
+        }
     }
-    println!("---------------\n");
 }
 
 fn print_help() {
     println!("mate - A simple arithmetic expression interpreter\n");
     println!("USAGE:");
-    println!("    mate [OPTIONS] [EXPRESSION]\n");
+    println!("    mate [OPTIONS] [EXPRESSION]");
+    println!("    mate [OPTIONS] SCRIPT.mate\n");
     println!("OPTIONS:");
     println!("    -h, --help       Print this help message");
     println!("    -v, --version    Print version information");
-    println!("    -t, --tokens     Show parsed tokens\n");
+    println!("    -t, --tokens     Show parsed tokens");
+    println!("    --tokens=FMT     Show parsed tokens as tree, dot (Graphviz) or json");
+    println!("    -s, --steps      Show step-by-step evaluation");
+    println!("    -l, --load FILE  Load variables saved by `save` (can be repeated)");
+    println!("    -f, --file FILE  Execute a script file");
+    println!("    -o, --output FMT Format of batch mode results: plain, json or csv");
+    println!("    -p, --precision N Show results with N decimals");
+    println!("    --significant N  Show results with N significant digits");
+    println!("    --notation NOT   Notation of results: auto, fixed, sci or eng");
+    println!("    --group          Group thousands of results: 1,234,567");
+    println!("    --base BASE      Base of integer results: dec, hex, bin or oct");
+    println!("    --plot SPEC      Draw a chart: EXPRESSION from A to B, over x");
+    println!("    --ascii          Draw charts with ASCII instead of braille");
+    println!("    --table SPEC     Print values: EXPRESSION[, ...] for x = A..B [step S]");
+    println!("    --serve          Serve line-delimited JSON-RPC on stdin and stdout\n");
     println!("EXAMPLES:");
     println!("    mate 2 + 2");
     println!("    mate \"(5 + 3) * 2\"");
     println!("    mate -t \"10 / 2\"");
+    println!("    mate --tokens=dot \"2 * (3 + 4)\" | dot -Tsvg > tokens.svg");
+    println!("    mate --steps \"(2 + 5) * 3\"");
+    println!("    mate --load constants.mate \"g * 2\"");
+    println!("    mate script.mate # Statements separated by new lines or ';', '#' comments");
+    println!("    cat exprs.txt | mate --output json   # Batch mode, one result per line");
     println!("    mate \"sqrt(16) + 5\"");
+    println!("    mate --plot \"sin(x) * x from -10 to 10\"");
+    println!("    mate --output csv --table \"x ^ 2 + 1 for x = 0..10 step 0.5\"");
     println!("    mate              # Start interactive REPL\n");
     println!("SUPPORTED OPERATIONS:");
     println!("    +    Addition");
@@ -175,10 +904,23 @@ fn print_help() {
     println!("    =    Assignment (e.g., x = 5)");
     println!("    ()   Parentheses for grouping");
     println!("    []   Absolute value (e.g., [-5] = 5)\n");
+    println!("INTERVALS:");
+    println!("    [9.8, 9.82]              Interval of all numbers from 9.8 to 9.82");
+    println!("    [9.8, 9.82] * 2.5 ± 0.1  With intervals, ± means [2.4, 2.6]");
+    println!("                             Bounds are rounded outwards\n");
+    println!("UNCERTAINTIES:");
+    println!("    x = 12.3 ± 0.2           Value with standard uncertainty");
+    println!("    x * 2                    Uncertainty is propagated: 24.6 ± 0.4");
+    println!("    x - x                    Reuses are correlated: 0 ± 0");
+    println!("    2 * x ± 0.1              ± binds tightest: 2 * (x ± 0.1)\n");
     println!("VARIABLES:");
     println!("    x = 5        Assign value to variable");
     println!("    x + 2        Use variable in expression");
     println!("    y = x * 2    Assign expression result\n");
+    println!("REACTIVE MODE (type 'reactive' in REPL):");
+    println!("    y = x * 2    The expression of y is kept");
+    println!("    x = 10       Changing x recomputes y = 20");
+    println!("    a = a + 1    Circular dependencies are reported as errors\n");
     println!("MATH FUNCTIONS:");
     println!("    sqrt(x)   Square root");
     println!("    sin(x)    Sine (radians)");
@@ -189,7 +931,10 @@ fn print_help() {
     println!("    exp(x)    Exponential (e^x)");
     println!("    floor(x)  Round down");
     println!("    ceil(x)   Round up");
-    println!("    round(x)  Round to nearest");
+    println!("    round(x)  Round to nearest\n");
+    println!("CALCULUS:");
+    println!("    integrate(expr, x, a, b)       Definite integral of expr over x from a to b");
+    println!("    integrate(expr, x, a, b, tol)  Same, with a custom error tolerance");
 }
 
 fn print_repl_help() {
@@ -198,8 +943,30 @@ fn print_repl_help() {
     println!("  quit, exit, q Exit the REPL");
     println!("  clear, cls    Clear the screen");
     println!("  tokens        Toggle token display");
+    println!("  steps         Toggle step-by-step evaluation");
+    println!("  reactive      Toggle reactive mode, recomputing dependent variables");
     println!("  vars          Show all variables");
-    println!("  reset         Clear all variables\n");
+    println!("  history       Show numbered inputs with their results");
+    println!("  plot EXPR from A to B   Draw a chart of EXPR over x, gaps where it's undefined");
+    println!("  table EXPR, ... for x = A..B step S   Print values of expressions over x");
+    println!("  format        Show the format of results, change it with:");
+    println!("    format precision N        N decimals");
+    println!("    format significant N      N significant digits");
+    println!("    format notation NOT       auto, fixed, sci or eng");
+    println!("    format grouping on|off    Group thousands: 1,234,567");
+    println!("    format base BASE          dec, hex, bin or oct, for integers");
+    println!("    format reset              The default format");
+    println!("  reset         Clear all variables and history");
+    println!("  save FILE     Save variables to FILE (JSON, if it ends with .json)");
+    println!("  load FILE     Load variables from FILE\n");
+    println!("{}", "Line editing:".bold());
+    println!("  Tab           Complete functions, variables and commands");
+    println!("  Up, Down      Browse history of previous sessions too");
+    println!("  Ctrl-R        Search history");
+    println!("  Ctrl-C        Discard the current input");
+    println!("  Ctrl-D        Exit the REPL");
+    println!("  Input continues on the next line, while parentheses are open");
+    println!("  Results are previewed under the prompt, as the input is typed\n");
     println!("{}", "Supported operations:".bold());
     println!("  +    Addition");
     println!("  -    Subtraction");
@@ -210,13 +977,18 @@ fn print_repl_help() {
     println!("  !    Factorial (e.g., 5! = 120)");
     println!("  =    Assignment (e.g., x = 5)");
     println!("  ()   Parentheses for grouping");
-    println!("  []   Absolute value (e.g., [-5] = 5)\n");
+    println!("  []   Absolute value (e.g., [-5] = 5)");
+    println!("  [a, b]  Interval (e.g., [1, 2] * 3 = [3, 6])");
+    println!("  ±    Uncertainty (e.g., 12.3 ± 0.2), or interval half-width next to [a, b]\n");
     println!("{}", "Variables:".bold());
     println!("  x = 5        Assign value to variable");
     println!("  x + 2        Use variable in expression");
-    println!("  y = x * 2    Assign expression result\n");
+    println!("  y = x * 2    Assign expression result");
+    println!("  ans, _1, _2  The last result, and the numbered results of history");
+    println!("  * 2          Starting with an operator continues the last result: ans * 2\n");
     println!("{}", "Math functions:".bold());
-    println!("  sqrt, sin, cos, tan, log, ln, exp, floor, ceil, round\n");
+    println!("  sqrt, sin, cos, tan, log, ln, exp, floor, ceil, round");
+    println!("  integrate(expr, x, a, b)  Definite integral\n");
     println!("{}", "Examples:".bold());
     println!("  2 + 2");
     println!("  x = 10");
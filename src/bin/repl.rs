@@ -0,0 +1,595 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use colored::Colorize;
+use mate_rs::{
+    calculator::Calculator,
+    errors::Error,
+    formatter::Formatter,
+    lexer::Lexer,
+    limits::EvalLimits,
+    session::Session,
+    token::{Token, TokenType, BUILTINS},
+    value::Value,
+};
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::{CmdKind, Highlighter},
+    hint::Hinter,
+    validate::{ValidationContext, ValidationResult, Validator},
+    Context, Helper,
+};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    path::PathBuf,
+    time::Duration,
+};
+
+// The commands of REPL, completed at the start of line.
+pub const COMMANDS: [&str; 16] = [
+    "quit",
+    "exit",
+    "help",
+    "clear",
+    "tokens",
+    "steps",
+    "reactive",
+    "vars",
+    "variables",
+    "reset",
+    "save",
+    "load",
+    "history",
+    "format",
+    "plot",
+    "table",
+];
+
+// The short forms of commands, that aren't completed.
+const ALIASES: [&str; 4] = ["q", "h", "?", "cls"];
+
+// The result of input, previewed before it's entered, or none for commands and empty input.
+type Preview = Option<Result<Value, Error>>;
+
+// How a character of input is highlighted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Style {
+    Plain,
+    Number,
+    Operator,
+    Function,
+    Variable,
+    Unknown,
+    Bracket,
+    Matching,
+    Error,
+}
+
+// The line editor helper of REPL:
+//  - completes built-in functions, defined variables and REPL commands on tab,
+//  - continues the input on new lines, while brackets are unbalanced,
+//  - highlights the input by its tokens as it's typed, with unknown identifiers,
+//    illegal characters and unmatched brackets in red, and the error point underlined,
+//  - previews the result of input under the prompt, before it's entered,
+//    with the same continuation of last result, as [Session].
+//
+//  ╭──────────────────────────────╮
+//  │ >>> sqrt(radius * 2)         │   ◀─ the bracket pair at cursor is highlighted
+//  │ = 3.7416573867739413         │   ◀─ preview, calculated in a child scope of variables
+//  ╰──────────────────────────────╯
+//
+// The preview of line is calculated once, and shared by its hint and highlighting.
+#[derive(Default)]
+pub struct ReplHelper {
+    session: Session,
+    formatter: Formatter,
+    cursor: Cell<Option<usize>>,
+    preview: RefCell<Option<(String, Preview)>>,
+}
+
+impl ReplHelper {
+    // Updates the variables to complete and preview with, after each calculation.
+    pub fn set_session(&mut self, session: &Session) {
+        self.session = session.clone();
+        self.preview.take();
+    }
+
+    // Updates the format of previewed results.
+    pub fn set_formatter(&mut self, formatter: Formatter) {
+        self.formatter = formatter;
+    }
+
+    // Lists completions of the word, that ends at [pos]. Returns the start of word too.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+
+        let word = line[start..pos].to_lowercase();
+        if word.is_empty() {
+            return (start, Vec::new());
+        }
+
+        let mut candidates: Vec<Pair> = Vec::new();
+        if line[..start].trim().is_empty() {
+            for command in COMMANDS.iter().filter(|c| c.starts_with(&word)) {
+                candidates.push(Pair {
+                    display: command.to_string(),
+                    replacement: command.to_string(),
+                });
+            }
+        }
+
+        for builtin in BUILTINS.iter().filter(|b| b.name.starts_with(&word)) {
+            candidates.push(Pair {
+                display: builtin.signature.to_string(),
+                replacement: format!("{}(", builtin.name),
+            });
+        }
+
+        let mut variables = self.session.environment().names();
+        variables.sort();
+        for variable in variables {
+            if variable.to_lowercase().starts_with(&word) {
+                candidates.push(Pair {
+                    display: variable.clone(),
+                    replacement: variable.clone(),
+                });
+            }
+        }
+
+        (start, candidates)
+    }
+
+    // Calculates the input in a child scope of session, with tight limits to keep typing
+    // responsive. Commands and empty input have no preview. The result is cached until
+    // the input or the session changes.
+    //
+    // Errors point to the expanded input, see [error_point] for the input as it's typed.
+    pub fn preview(&self, input: &str) -> Preview {
+        if let Some((line, result)) = self.preview.borrow().as_ref() {
+            if line == input {
+                return result.clone();
+            }
+        }
+
+        let result = self.calculate_preview(input);
+        self.preview
+            .replace(Some((input.to_string(), result.clone())));
+        result
+    }
+
+    fn calculate_preview(&self, input: &str) -> Preview {
+        if input.trim().is_empty() || is_command(input) {
+            return None;
+        }
+
+        let limits = EvalLimits::default()
+            .with_max_operations(100_000)
+            .with_timeout(Duration::from_millis(50));
+
+        let input = self.session.expand(input);
+        let mut scope = self.session.environment().child();
+        let result = Lexer::lex_with_limits(&input, &limits)
+            .and_then(|sub| Calculator::calculate_with_limits(sub, &input, &mut scope, &limits));
+
+        Some(result)
+    }
+
+    // Maps the point of error in the expanded input, back to the input as it's typed.
+    fn error_point(&self, input: &str, e: &Error) -> Option<usize> {
+        let leading = input.chars().take_while(|c| c.is_whitespace()).count();
+        let expanded = self.session.expand(input);
+        let added = expanded.chars().count() - input.trim().chars().count();
+
+        let (point, _) = e.span()?.chars(&expanded);
+        point.checked_sub(added).map(|p| p + leading)
+    }
+
+    // Decides the style of each character of input, by the tokens of [Lexer].
+    // The brackets at [cursor] and their pairs are styled as matching.
+    fn styles(&self, input: &str, cursor: Option<usize>) -> Vec<Style> {
+        let tokens = Lexer::tokenize(input);
+        let mut styles = vec![Style::Plain; input.chars().count()];
+        let mut fill = |t: &Token, style: Style| {
+            let (start, end) = t.span.chars(input);
+            for s in styles.iter_mut().take(end).skip(start) {
+                *s = style;
+            }
+        };
+
+        // The variables, that are defined by the input itself.
+        let mut defined: Vec<String> = integration_variables(&tokens);
+        if let [name, assign, ..] = tokens.as_slice() {
+            if name.is_identifier() && assign.is_assign() {
+                defined.push(name.literal.clone());
+            }
+        }
+
+        // Pairs the brackets, unmatched ones stay in the stack or get no pair.
+        let mut open: Vec<usize> = Vec::new();
+        let mut pairs: Vec<(usize, Option<usize>)> = Vec::new();
+        for (i, t) in tokens.iter().enumerate() {
+            if t.is_lparen() || t.is_labs() {
+                open.push(i);
+            } else if t.is_rparen() || t.is_rabs() {
+                match open.last() {
+                    Some(&o) if tokens[o].matchto(t) => {
+                        open.pop();
+                        pairs.push((o, Some(i)));
+                        pairs.push((i, Some(o)));
+                    }
+                    _ => pairs.push((i, None)),
+                }
+            }
+        }
+        pairs.extend(open.into_iter().map(|o| (o, None)));
+
+        for t in tokens.iter() {
+            let style = match t.typ {
+                TokenType::NUMBER => Style::Number,
+                TokenType::IDENTIFIER => {
+                    let env = self.session.environment();
+                    let known = env.exists(&t.literal) || defined.contains(&t.literal);
+                    if known {
+                        Style::Variable
+                    } else {
+                        Style::Unknown
+                    }
+                }
+                TokenType::ILLEGAL => Style::Unknown,
+                TokenType::LPAREN | TokenType::RPAREN | TokenType::LABS | TokenType::RABS => {
+                    Style::Bracket
+                }
+                _ if t.is_function() => Style::Function,
+                _ => Style::Operator,
+            };
+            fill(t, style);
+        }
+
+        for (i, pair) in pairs {
+            match pair {
+                None => fill(&tokens[i], Style::Unknown),
+                Some(p) if cursor.is_some_and(|c| at_cursor(input, &tokens[i], c)) => {
+                    fill(&tokens[i], Style::Matching);
+                    fill(&tokens[p], Style::Matching);
+                }
+                Some(_) => {}
+            }
+        }
+
+        styles
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_incomplete(ctx.input()) {
+            return Ok(ValidationResult::Incomplete);
+        }
+
+        Ok(ValidationResult::Valid(None))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, _pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        match self.preview(line)? {
+            Ok(value) => Some(format!("\n= {}", self.formatter.format_value(&value))),
+            Err(_) => None,
+        }
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if line.trim().is_empty() || is_command(line) {
+            return Cow::Borrowed(line);
+        }
+
+        let mut styles = self.styles(line, self.cursor.get());
+
+        // The error of calculation is underlined at its point, if it has one.
+        if let Some(Err(e)) = self.preview(line) {
+            if let Some(s) = self.error_point(line, &e).and_then(|p| styles.get_mut(p)) {
+                *s = Style::Error;
+            }
+        }
+
+        Cow::Owned(paint(line, &styles))
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
+
+    fn highlight_char(&self, line: &str, pos: usize, kind: CmdKind) -> bool {
+        // The final refresh, after the input is entered, has no matching brackets.
+        let cursor = if kind == CmdKind::ForcedRefresh {
+            None
+        } else {
+            Some(line[..pos].chars().count())
+        };
+
+        self.cursor.set(cursor);
+        true
+    }
+}
+
+impl Helper for ReplHelper {}
+
+// Checks if the input is a REPL command, instead of an expression.
+// Assignments to variables named like the command, `save = 5`, aren't commands.
+pub fn is_command(input: &str) -> bool {
+    let input = input.trim().to_lowercase();
+    if COMMANDS.contains(&input.as_str()) || ALIASES.contains(&input.as_str()) {
+        return true;
+    }
+
+    match input.split_once(char::is_whitespace) {
+        Some((name, rest)) => {
+            ["save", "load", "format", "plot", "table"].contains(&name)
+                && !rest.trim().is_empty()
+                && !rest.trim().starts_with('=')
+        }
+        None => false,
+    }
+}
+
+// The bound variables of integrals in input, like `x` in `integrate(x ^ 2, x, 0, 1)`.
+fn integration_variables(tokens: &[Token]) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+
+    for (i, t) in tokens.iter().enumerate() {
+        if !t.takes_arguments() {
+            continue;
+        }
+
+        let mut depth: usize = 0;
+        for (j, t) in tokens.iter().enumerate().skip(i + 1) {
+            if t.is_lparen() || t.is_labs() {
+                depth += 1;
+            } else if t.is_rparen() || t.is_rabs() {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            } else if depth == 1 && t.is_comma() {
+                if let Some(v) = tokens.get(j + 1).filter(|v| v.is_identifier()) {
+                    variables.push(v.literal.clone());
+                }
+                break;
+            }
+        }
+    }
+
+    variables
+}
+
+// Checks if the cursor is on the token, or right after it.
+fn at_cursor(input: &str, t: &Token, cursor: usize) -> bool {
+    let (start, end) = t.span.chars(input);
+    cursor >= start && cursor <= end
+}
+
+// Colors the input, by the style of each character.
+fn paint(input: &str, styles: &[Style]) -> String {
+    let mut painted = String::new();
+    let chars: Vec<char> = input.chars().collect();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let style = styles.get(i).copied().unwrap_or(Style::Plain);
+        let len = styles[i..]
+            .iter()
+            .take_while(|s| **s == style)
+            .count()
+            .max(1);
+        let part: String = chars[i..(i + len).min(chars.len())].iter().collect();
+
+        let part = match style {
+            Style::Plain | Style::Bracket => part.normal(),
+            Style::Number => part.yellow(),
+            Style::Operator => part.magenta(),
+            Style::Function => part.blue(),
+            Style::Variable => part.cyan(),
+            Style::Unknown => part.red(),
+            Style::Matching => part.bold().green(),
+            Style::Error => part.red().underline(),
+        };
+        painted.push_str(&part.to_string());
+        i += len;
+    }
+
+    painted
+}
+
+// Checks if the input has more opening brackets, than closing ones.
+// Bars of absolute values are paired by the lexer, so `|x` is unfinished too.
+pub fn is_incomplete(input: &str) -> bool {
+    let mut depth: i32 = 0;
+    for t in Lexer::tokenize(input) {
+        if t.is_lparen() || t.is_labs() {
+            depth += 1;
+        } else if t.is_rparen() || t.is_rabs() {
+            depth -= 1;
+        }
+    }
+
+    depth > 0
+}
+
+// The file of REPL history, in the data directory of user.
+pub fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("mate").join("history"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mate_rs::{environment::Environment, span::Span};
+
+    #[test]
+    fn candidates() {
+        let mut env = Environment::new();
+        env.set("radius", 7.0);
+        env.set("area", 1.0);
+
+        let mut helper = ReplHelper::default();
+        helper.set_session(&Session::with_environment(env));
+
+        let test_data: Vec<(&str, usize, Vec<&str>)> = vec![
+            ("r", 0, vec!["reactive", "reset", "round(", "radius"]),
+            ("2 * r", 4, vec!["round(", "radius"]),
+            ("sq", 0, vec!["sqrt("]),
+            ("x + A", 4, vec!["area"]),
+            ("2 + ", 4, vec![]),
+        ];
+
+        for (line, start, expected) in test_data {
+            let (s, candidates) = helper.candidates(line, line.len());
+            let replacements: Vec<&str> =
+                candidates.iter().map(|c| c.replacement.as_str()).collect();
+            assert_eq!((s, replacements), (start, expected), "Failed for: {line}");
+        }
+    }
+
+    #[test]
+    fn styles() {
+        use Style::*;
+
+        let mut env = Environment::new();
+        env.set("r", 7.0);
+
+        let mut helper = ReplHelper::default();
+        helper.set_session(&Session::with_environment(env));
+
+        let test_data: Vec<(&str, Option<usize>, Vec<Style>)> = vec![
+            (
+                "r * 2",
+                None,
+                vec![Variable, Plain, Operator, Plain, Number],
+            ),
+            (
+                "y + r",
+                None,
+                vec![Unknown, Plain, Operator, Plain, Variable],
+            ),
+            (
+                "y = r",
+                None,
+                vec![Variable, Plain, Operator, Plain, Variable],
+            ),
+            (
+                "sin(r)",
+                None,
+                vec![Function, Function, Function, Bracket, Variable, Bracket],
+            ),
+            (
+                "sin(r)",
+                Some(6),
+                vec![Function, Function, Function, Matching, Variable, Matching],
+            ),
+            ("(2]", Some(0), vec![Unknown, Number, Unknown]),
+            ("2 $", None, vec![Number, Plain, Unknown]),
+        ];
+
+        for (input, cursor, expected) in test_data {
+            assert_eq!(
+                helper.styles(input, cursor),
+                expected,
+                "Failed for: {input}"
+            );
+        }
+
+        // The bound variable of integral is defined by the input.
+        let styles = helper.styles("integrate(x, x, 0, 1)", None);
+        assert_eq!((styles[10], styles[13]), (Variable, Variable));
+    }
+
+    #[test]
+    fn preview() {
+        let mut env = Environment::new();
+        env.set("r", 7.0);
+
+        let mut helper = ReplHelper::default();
+        helper.set_session(&Session::with_environment(env));
+
+        assert_eq!(helper.preview("r * 2"), Some(Ok(Value::Number(14.0))));
+        assert_eq!(helper.preview("r = 2"), Some(Ok(Value::Number(2.0))));
+        assert!(
+            matches!(helper.preview("r / 0"), Some(Err(e)) if e.span() == Some(Span::new(4, 5)))
+        );
+        assert_eq!(helper.preview("vars"), None);
+        assert_eq!(helper.preview("save = 5"), Some(Ok(Value::Number(5.0))));
+
+        // The preview never changes variables.
+        assert_eq!(helper.session.environment().get("r"), Some(7.0));
+
+        // Continuations of the last result point to the input as it's typed.
+        let mut session = Session::new();
+        session.calculate("5").unwrap();
+        helper.set_session(&session);
+        assert_eq!(helper.preview("* 2"), Some(Ok(Value::Number(10.0))));
+        let error = helper.preview(" / 0").unwrap().unwrap_err();
+        assert_eq!(
+            (error.span(), helper.error_point(" / 0", &error)),
+            (Some(Span::new(6, 7)), Some(3))
+        );
+
+        // The preview is kept for the line, until the session changes.
+        assert_eq!(helper.preview(" / 0"), Some(Err(error)));
+        assert_eq!(
+            helper
+                .preview
+                .borrow()
+                .as_ref()
+                .map(|(line, _)| line.as_str()),
+            Some(" / 0")
+        );
+        session.calculate("7").unwrap();
+        helper.set_session(&session);
+        assert_eq!(helper.preview(" / 0"), helper.calculate_preview(" / 0"));
+        assert_eq!(helper.preview("* 2"), Some(Ok(Value::Number(14.0))));
+    }
+
+    #[test]
+    fn incomplete() {
+        let test_data: Vec<(&str, bool)> = vec![
+            ("2 + 2", false),
+            ("sqrt(16", true),
+            ("(1 + [2", true),
+            ("(1 + [2])", false),
+            ("1)", false),
+            ("|x", true),
+            ("|x| + |y", true),
+            ("|x - [1, 2]|", false),
+            ("2 * |x|", false),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(is_incomplete(input), expected, "Failed for: {input}");
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator,
+    environment::Environment,
+    errors::Error,
+    lexer::Lexer,
+    limits::EvalLimits,
+    script::{Script, Statement},
+    span::Span,
+    suggest,
+    token::{Builtin, BUILTINS},
+    utils::ChUtils,
+    value::Value,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+// A problem of an expression, at a range of bytes of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    pub span: Span,
+    pub message: String,
+}
+
+// A problem of a script statement, at a range of a single line.
+// Lines and columns start from zero, and [end] is exclusive.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+// The result of a script statement, to be shown after it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    pub line: usize,
+    pub column: usize,
+    pub label: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompletionKind {
+    Function,
+    Constant,
+    Variable,
+}
+
+// A completion of the word being typed. Constants are labelled by their names,
+// like `pi`, and insert the symbol that lexer reads: `π`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: String,
+    pub insert: String,
+}
+
+// The static analysis of a script, used by editors.
+//
+// Every statement is calculated with the default [EvalLimits], since the script is
+// re-analyzed on each keystroke. Failed statements don't stop the analysis,
+// each of them results a [Diagnostic], and the next statements are still calculated.
+//
+//  ╭──────────────────╮     ╭────────────────────────────────────────╮
+//  │ r = 7            │ ──▶ │ r = 7              = 7                 │
+//  │ r / 0            │ ──▶ │ r / 0              ~~~ division by zero│
+//  │ r * 2            │ ──▶ │ r * 2              = 14                │
+//  ╰──────────────────╯     ╰────────────────────────────────────────╯
+#[derive(Debug)]
+pub struct Analysis {
+    lines: Vec<String>,
+    env: Environment<'static>,
+    diagnostics: Vec<Diagnostic>,
+    hints: Vec<Hint>,
+}
+
+impl Analysis {
+    pub fn new(text: &str) -> Self {
+        let mut analysis = Self {
+            lines: text.lines().map(String::from).collect(),
+            env: Environment::new(),
+            diagnostics: Vec::new(),
+            hints: Vec::new(),
+        };
+
+        let limits = EvalLimits::default();
+        for statement in Script::parse(text).statements() {
+            let e = match analysis.calculate(statement, &limits) {
+                Ok(value) => {
+                    analysis.hints.push(Hint {
+                        line: statement.line - 1,
+                        column: statement.column - 1 + statement.source.chars().count(),
+                        label: format!("= {value}"),
+                    });
+                    continue;
+                }
+                Err(e) => e,
+            };
+
+            // Calculation stops at the first error, so the statement is checked again,
+            // to report all of its syntax errors at once.
+            let mut syntax_errors: Vec<Problem> = Vec::new();
+            if limits.check_input_length(&statement.source).is_ok() {
+                syntax_errors = check(&statement.source, &analysis.env);
+                syntax_errors.retain(|p| p.severity == Severity::Error);
+            }
+
+            if syntax_errors.is_empty() {
+                let diagnostic = Analysis::diagnostic(statement, e.span(), headline(&e));
+                analysis.diagnostics.push(diagnostic);
+            }
+            for p in syntax_errors {
+                let diagnostic = Analysis::diagnostic(statement, Some(p.span), p.message);
+                analysis.diagnostics.push(diagnostic);
+            }
+        }
+
+        analysis
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn hints(&self) -> &[Hint] {
+        &self.hints
+    }
+
+    // The variables, after calculation of the whole script.
+    pub fn environment(&self) -> &Environment<'static> {
+        &self.env
+    }
+
+    // Describes the function or variable under the given position.
+    pub fn hover(&self, line: usize, column: usize) -> Option<String> {
+        let word = self.word_at(line, column)?;
+
+        if let Some(builtin) = Builtin::find(&word) {
+            return Some(format!("{} — {}", builtin.signature, builtin.description));
+        }
+
+        let value = self.env.get_value(&word)?;
+        match self.env.formula(&word) {
+            Some(formula) => Some(format!("{word} = {value} ({})", formula.expression())),
+            None => Some(format!("{word} = {value}")),
+        }
+    }
+
+    // Lists the functions, constants and variables, that start with [prefix].
+    pub fn completions(&self, prefix: &str) -> Vec<Completion> {
+        let prefix = prefix.to_lowercase();
+        let mut completions: Vec<Completion> = BUILTINS
+            .iter()
+            .filter(|b| b.name.starts_with(&prefix))
+            .map(|b| Completion {
+                label: b.name.to_string(),
+                kind: CompletionKind::Function,
+                detail: format!("{} — {}", b.signature, b.description),
+                insert: b.name.to_string(),
+            })
+            .collect();
+
+        for (name, symbol) in suggest::CONSTANTS
+            .iter()
+            .filter(|(name, _)| name.starts_with(&prefix))
+        {
+            if let Some(value) = symbol.to_string().constant() {
+                completions.push(Completion {
+                    label: name.to_string(),
+                    kind: CompletionKind::Constant,
+                    detail: format!("{symbol} = {value}"),
+                    insert: symbol.to_string(),
+                });
+            }
+        }
+
+        let mut names = self.env.names();
+        names.sort();
+        for name in names {
+            if !name.to_lowercase().starts_with(&prefix) {
+                continue;
+            }
+
+            if let Some(value) = self.env.get_value(name) {
+                completions.push(Completion {
+                    label: name.clone(),
+                    kind: CompletionKind::Variable,
+                    detail: value.to_string(),
+                    insert: name.clone(),
+                });
+            }
+        }
+
+        completions
+    }
+
+    // The part of identifier before the given position, to complete.
+    pub fn prefix_at(&self, line: usize, column: usize) -> String {
+        let chars: Vec<char> = match self.lines.get(line) {
+            Some(line) => line.chars().collect(),
+            None => return String::new(),
+        };
+
+        let end = column.min(chars.len());
+        let start = chars[..end]
+            .iter()
+            .rposition(|c| !is_word(*c))
+            .map_or(0, |p| p + 1);
+
+        chars[start..end].iter().collect()
+    }
+
+    fn calculate(&mut self, statement: &Statement, limits: &EvalLimits) -> Result<Value, Error> {
+        let sub = Lexer::lex_with_limits(&statement.source, limits)?;
+        Calculator::calculate_with_limits(sub, &statement.source, &mut self.env, limits)
+    }
+
+    // Maps the problem to the range of statement, at its span if it has one.
+    fn diagnostic(statement: &Statement, span: Option<Span>, message: String) -> Diagnostic {
+        let line = statement.line - 1;
+        let start = statement.column - 1;
+
+        match span {
+            Some(span) => {
+                let last = statement.source.chars().count().saturating_sub(1);
+                let (from, to) = span.chars(&statement.source);
+                let from = from.min(last);
+                let to = to.clamp(from + 1, last + 1);
+                Diagnostic {
+                    line,
+                    start: start + from,
+                    end: start + to,
+                    message,
+                }
+            }
+            None => Diagnostic {
+                line,
+                start,
+                end: start + statement.source.chars().count(),
+                message,
+            },
+        }
+    }
+
+    // The identifier, that covers the given position.
+    fn word_at(&self, line: usize, column: usize) -> Option<String> {
+        let chars: Vec<char> = self.lines.get(line)?.chars().collect();
+
+        let at = column.min(chars.len());
+        let start = chars[..at]
+            .iter()
+            .rposition(|c| !is_word(*c))
+            .map_or(0, |p| p + 1);
+        let end = chars[at..]
+            .iter()
+            .position(|c| !is_word(*c))
+            .map_or(chars.len(), |p| at + p);
+
+        let word: String = chars[start..end].iter().collect();
+        match word.chars().next() {
+            Some(c) if c.is_alphabetic() || c == '_' => Some(word),
+            _ => None,
+        }
+    }
+}
+
+// Finds every problem of [input] at once, without calculating it: the syntax errors,
+// that the recovering lexer has gone past, and the variables, that [env] doesn't have.
+//
+//  ╭──────────────────╮     ╭─────────────────────────────────────────────────╮
+//  │ (2 $ 3 + radius  │ ──▶ │ 0..1    error    unclosed bracket               │
+//  ╰──────────────────╯     │ 3..4    error    found an illegal character: `$` │
+//                           │ 9..15   warning  undefined variable 'radius'    │
+//                           ╰─────────────────────────────────────────────────╯
+pub fn check(input: &str, env: &Environment) -> Vec<Problem> {
+    let (sub, errors) = Lexer::lex_recovering(input);
+
+    let mut problems: Vec<Problem> = errors
+        .iter()
+        .map(|e| Problem {
+            severity: Severity::Error,
+            span: e.span().unwrap_or(Span::new(0, input.len())),
+            message: headline(e),
+        })
+        .collect();
+
+    for t in Calculator::undefined_variables(&sub, env) {
+        let message = match suggest::did_you_mean(&t.literal, env) {
+            Some(suggestion) => format!(
+                "warning: undefined variable '{}', did you mean `{suggestion}`?",
+                t.literal
+            ),
+            None => format!("warning: undefined variable '{}'", t.literal),
+        };
+
+        problems.push(Problem {
+            severity: Severity::Warning,
+            span: t.span,
+            message,
+        });
+    }
+
+    problems.sort_by_key(|p| p.span.start);
+    problems
+}
+
+// The first line of error message, without its explanation.
+fn headline(e: &Error) -> String {
+    e.to_string()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "r = 7\nr / 0; unknown + 1\nsqrt(r * 2)  # comment";
+
+    #[test]
+    fn diagnostics() {
+        let analysis = Analysis::new(SCRIPT);
+        assert_eq!(
+            analysis.diagnostics(),
+            &[
+                Diagnostic {
+                    line: 1,
+                    start: 4,
+                    end: 5,
+                    message: String::from("error: division by zero"),
+                },
+                Diagnostic {
+                    line: 1,
+                    start: 7,
+                    end: 14,
+                    message: String::from("error: undefined variable 'unknown'"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_diagnostics() {
+        // Every syntax error of a statement is reported at once.
+        let analysis = Analysis::new("r = 7\n(r $ 2] + 1");
+        let diagnostics: Vec<(usize, usize, usize, &str)> = analysis
+            .diagnostics()
+            .iter()
+            .map(|d| (d.line, d.start, d.end, d.message.as_str()))
+            .collect();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                (1, 0, 1, "error: unclosed bracket"),
+                (1, 3, 4, "error: found an illegal character: `$`"),
+                (1, 6, 7, "error: mismatched parentheses or brackets"),
+            ]
+        );
+    }
+
+    #[test]
+    fn check() {
+        let mut env = Environment::new();
+        env.set("r", 7.0);
+
+        type Problems = Vec<(Severity, Span, &'static str)>;
+
+        let test_data: Vec<(&str, Problems)> = vec![
+            ("r * 2", vec![]),
+            (
+                "(2 $ 3 + radius",
+                vec![
+                    (Severity::Error, Span::new(0, 1), "error: unclosed bracket"),
+                    (
+                        Severity::Error,
+                        Span::new(3, 4),
+                        "error: found an illegal character: `$`",
+                    ),
+                    (
+                        Severity::Warning,
+                        Span::new(9, 15),
+                        "warning: undefined variable 'radius'",
+                    ),
+                ],
+            ),
+            (
+                "area = pi * r ^ 2",
+                vec![(
+                    Severity::Warning,
+                    Span::new(7, 9),
+                    "warning: undefined variable 'pi', did you mean `π`?",
+                )],
+            ),
+            (
+                "",
+                vec![(
+                    Severity::Error,
+                    Span::default(),
+                    "error: cannot parse an empty input",
+                )],
+            ),
+        ];
+
+        for (input, expected) in test_data {
+            let problems = super::check(input, &env);
+            let problems: Vec<(Severity, Span, &str)> = problems
+                .iter()
+                .map(|p| (p.severity, p.span, p.message.as_str()))
+                .collect();
+            assert_eq!(problems, expected, "Failed for: {input}");
+        }
+    }
+
+    #[test]
+    fn hints() {
+        let analysis = Analysis::new(SCRIPT);
+        let hints: Vec<(usize, usize, &str)> = analysis
+            .hints()
+            .iter()
+            .map(|h| (h.line, h.column, h.label.as_str()))
+            .collect();
+
+        assert_eq!(hints, vec![(0, 5, "= 7"), (2, 11, "= 3.7416573867739413")]);
+    }
+
+    #[test]
+    fn hover() {
+        let analysis = Analysis::new(SCRIPT);
+
+        let test_data: Vec<((usize, usize), Option<&str>)> = vec![
+            ((0, 0), Some("r = 7")),
+            ((2, 2), Some("sqrt(x) — Square root")),
+            ((2, 6), Some("r = 7")),
+            ((2, 7), None),
+            ((1, 9), None),
+            ((10, 0), None),
+        ];
+
+        for (position, expected) in test_data {
+            let hover = analysis.hover(position.0, position.1);
+            assert_eq!(hover.as_deref(), expected, "Failed for: {position:?}");
+        }
+    }
+
+    #[test]
+    fn completions() {
+        let analysis = Analysis::new("radius = 2\nrounded = 3\nr");
+
+        assert_eq!(analysis.prefix_at(2, 1), "r");
+        let labels: Vec<String> = analysis
+            .completions(&analysis.prefix_at(2, 1))
+            .into_iter()
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(labels, vec!["round", "radius", "rounded"]);
+
+        let completion = &analysis.completions("integ")[0];
+        assert_eq!(completion.kind, CompletionKind::Function);
+        assert!(completion.detail.starts_with("integrate(expr, x, a, b"));
+
+        // Constants are completed by their names, and insert their symbols.
+        let completion = &analysis.completions("p")[0];
+        assert_eq!(completion.kind, CompletionKind::Constant);
+        assert_eq!(
+            (completion.label.as_str(), completion.insert.as_str()),
+            ("pi", "π")
+        );
+        assert_eq!(completion.detail, format!("π = {}", std::f64::consts::PI));
+
+        assert_eq!(analysis.completions("").len(), BUILTINS.len() + 3 + 2);
+    }
+}
@@ -5,11 +5,46 @@
 //
 
 use crate::{
-    environment::Environment,
+    environment::{Environment, Formula},
     errors::Error,
+    integrator::Integrator,
+    interval::Interval,
+    limits::{Budget, EvalLimits},
+    span::Span,
+    suggest,
     token::{Sub, SubMethod, Token, TokenType},
+    trace::Trace,
+    uncertain::Uncertain,
     utils::ChUtils,
+    value::{Domain, Value},
 };
+use std::collections::BTreeSet;
+
+// The state of a single calculation, shared by every nested sub-expression of it.
+struct Context<'a, 'p> {
+    input: &'a str,
+    env: &'a mut Environment<'p>,
+    trace: Option<Trace>,
+    budget: Budget,
+}
+
+impl<'a, 'p> Context<'a, 'p> {
+    fn new(input: &'a str, env: &'a mut Environment<'p>) -> Self {
+        Self {
+            input,
+            env,
+            trace: None,
+            budget: Budget::new(EvalLimits::unlimited()),
+        }
+    }
+
+    // Records the reduction of [tokens] to [value], if the calculation is traced.
+    fn record<V: Domain>(&mut self, tokens: &[Token], value: &V) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record(tokens, value.clone().into_value());
+        }
+    }
+}
 
 pub struct Calculator {}
 impl Calculator {
@@ -37,7 +72,132 @@ impl Calculator {
     // and the operation is PRODUCT(Multiplication). Result of function would be ──▶ 6 * 7 = 42
     //
     pub fn calculate_with_env(sub: Sub, input: &str, env: &mut Environment) -> Result<f64, Error> {
-        let mut result: f64 = 0.0;
+        Calculator::evaluate::<f64>(sub, &mut Context::new(input, env))
+    }
+
+    // Calculates the expression in the interval mode.
+    // Every number is treated as a degenerate interval, and interval literals
+    // like `[9.8, 9.82]` or `2.5 ± 0.1` are allowed.
+    //
+    //  ╭──────────────────────────╮     ╭─────────────────╮
+    //  │ [9.8, 9.82] * 2.5 ± 0.1  │ ──▶ │ [23.52, 25.532] │
+    //  ╰──────────────────────────╯     ╰─────────────────╯
+    //
+    pub fn calculate_interval(
+        sub: Sub,
+        input: &str,
+        env: &mut Environment,
+    ) -> Result<Interval, Error> {
+        Calculator::evaluate::<Interval>(sub, &mut Context::new(input, env))
+    }
+
+    // Calculates the expression like [calculate_value], within the given resource [limits].
+    // Use it together with [Lexer::lex_with_limits] to calculate untrusted input.
+    //
+    // Every breach of limits results an error with a dedicated [ErrorKind],
+    // instead of a stack overflow or a hang.
+    pub fn calculate_with_limits(
+        sub: Sub,
+        input: &str,
+        env: &mut Environment,
+        limits: &EvalLimits,
+    ) -> Result<Value, Error> {
+        let mut ctx = Context::new(input, env);
+        ctx.budget = Budget::new(*limits);
+
+        Calculator::evaluate_value(sub, &mut ctx)
+    }
+
+    // Calculates the expression with uncertainty propagation.
+    // `±` creates a value with standard uncertainty, which is propagated by
+    // the first-order error propagation, through operations and functions.
+    //
+    //  ╭──────────────────────────╮     ╭─────────────╮
+    //  │ x = 12.3 ± 0.2           │ ──▶ │ 12.3 ± 0.2  │
+    //  │ x - x                    │ ──▶ │ 0 ± 0       │
+    //  ╰──────────────────────────╯     ╰─────────────╯
+    //
+    pub fn calculate_uncertain(
+        sub: Sub,
+        input: &str,
+        env: &mut Environment,
+    ) -> Result<Uncertain, Error> {
+        Calculator::evaluate::<Uncertain>(sub, &mut Context::new(input, env))
+    }
+
+    // Calculates the expression in the mode that fits it best:
+    //
+    // - If the expression contains an interval literal or a variable that holds an interval,
+    //   it's calculated in the interval mode, where `±` is a half-width of interval.
+    // - If the expression contains a `±` or a variable that holds an uncertain value,
+    //   it's calculated with uncertainty propagation.
+    // - Otherwise, it's calculated as a plain number.
+    pub fn calculate_value(sub: Sub, input: &str, env: &mut Environment) -> Result<Value, Error> {
+        Calculator::evaluate_value(sub, &mut Context::new(input, env))
+    }
+
+    // Calculates the expression like [calculate_value], and records every reduction step
+    // that was made to reach the answer.
+    //
+    //  ╭─────────────╮     ╭───────╮     ╭────╮
+    //  │ (2 + 5) * 3 │ ──▶ │ 7 * 3 │ ──▶ │ 21 │
+    //  ╰─────────────╯     ╰───────╯     ╰────╯
+    //
+    pub fn calculate_traced(
+        sub: Sub,
+        input: &str,
+        env: &mut Environment,
+    ) -> Result<(Value, Trace), Error> {
+        let mut ctx = Context::new(input, env);
+        ctx.trace = Some(Trace::new(input));
+
+        // For assignments, only the expression after `=` is reduced.
+        let tokens = match sub.tokens.as_slice() {
+            [name, assign, rest @ ..] if name.is_identifier() && assign.is_assign() => rest.to_vec(),
+            tokens => tokens.to_vec(),
+        };
+
+        let value = Calculator::evaluate_value(sub, &mut ctx)?;
+        let mut trace = ctx.trace.unwrap_or_else(|| Trace::new(input));
+        trace.record(&tokens, value.clone());
+
+        Ok((value, trace))
+    }
+
+    // Calculates the expression in the mode that fits it best, see [calculate_value].
+    fn evaluate_value(sub: Sub, ctx: &mut Context) -> Result<Value, Error> {
+        let is_interval = |t: &Token, env: &Environment| {
+            (t.is_sub_exp() && Calculator::is_interval_literal(&t.sub))
+                || (t.is_identifier() && env.get_value(&t.literal).is_some_and(Value::is_interval))
+        };
+        let is_uncertain = |t: &Token, env: &Environment| {
+            t.is_plusminus()
+                || (t.is_identifier() && env.get_value(&t.literal).is_some_and(Value::is_uncertain))
+        };
+
+        if Calculator::is_interval_literal(&sub) || Calculator::uses(&sub, ctx.env, &is_interval) {
+            return Calculator::evaluate::<Interval>(sub, ctx).map(Value::Interval);
+        }
+
+        if Calculator::uses(&sub, ctx.env, &is_uncertain) {
+            return Calculator::evaluate::<Uncertain>(sub, ctx).map(Value::Uncertain);
+        }
+
+        Calculator::evaluate::<f64>(sub, ctx).map(Value::Number)
+    }
+
+    // The X/O/Y loop itself, generic over the value [Domain].
+    fn evaluate<V: Domain>(sub: Sub, ctx: &mut Context) -> Result<V, Error> {
+        ctx.budget.enter()?;
+        let result = Calculator::reduce::<V>(sub, ctx);
+        ctx.budget.leave();
+
+        result
+    }
+
+    // Reduces the sub-expression to a single value, see [evaluate].
+    fn reduce<V: Domain>(sub: Sub, ctx: &mut Context) -> Result<V, Error> {
+        let mut result: V = V::from_number(0.0);
         let tokens = &sub.tokens;
 
         if tokens.is_empty() {
@@ -50,102 +210,130 @@ impl Calculator {
             // Create a new Sub with the expression tokens (everything after =)
             let expr_tokens: Vec<Token> = tokens[2..].to_vec();
             let expr_sub = Sub::new(expr_tokens, SubMethod::PAREN);
-            let value = Calculator::calculate_with_env(expr_sub, input, env)?;
-            env.set(&var_name, value);
-            return Ok(value);
+
+            if !ctx.env.is_reactive() {
+                let value = Calculator::evaluate::<V>(expr_sub, ctx)?;
+                ctx.env.set_value(&var_name, value.clone().into_value());
+                return Ok(value);
+            }
+
+            // In reactive mode, the expression is kept, to recompute the variable
+            // when any of its dependencies change.
+
+            let is_new = ctx.env.get_value(&var_name).is_none();
+            let snapshot = ctx.env.snapshot(std::slice::from_ref(&var_name));
+
+            // Cycles are checked before the evaluation, so they're found even if
+            // variables of the cycle don't have values yet.
+            let dependencies = Calculator::dependencies(&expr_sub);
+            if dependencies.is_empty() {
+                ctx.env.forget(&var_name);
+            } else {
+                let formula = Formula {
+                    input: ctx.input.to_string(),
+                    sub: expr_sub.clone(),
+                    expression: ctx.input.get(tokens[1].span.end..).unwrap_or_default().trim().to_string(),
+                    dependencies,
+                };
+                ctx.env.define(&var_name, formula)?;
+            }
+
+            let result = match Calculator::evaluate::<V>(expr_sub.clone(), ctx) {
+                Ok(value) => {
+                    ctx.env.set_value(&var_name, value.clone().into_value());
+                    Calculator::recompute_with_budget(ctx.env, &var_name, &mut ctx.budget)
+                        .map(|_| value)
+                }
+                Err(e) => Err(e),
+            };
+
+            // A new variable, that reads variables which aren't defined yet, keeps its
+            // formula, to be computed once they're defined. Any other failure rolls
+            // the assignment back as a whole.
+            if result.is_err() {
+                let undefined = Calculator::undefined_variables(&expr_sub, ctx.env);
+                if !is_new || undefined.is_empty() {
+                    ctx.env.restore(snapshot);
+                }
+            }
+
+            return result;
         }
 
-        // Handle single identifier (variable lookup)
-        if tokens.len() == 1 && tokens[0].is_identifier() {
-            let var_name = &tokens[0].literal;
-            return env.get(var_name).ok_or_else(|| {
-                Error::new(format!("error: undefined variable '{var_name}'"))
-            });
+        // Handle interval literal: [lo, hi]
+        if Calculator::is_interval_literal(&sub) {
+            return Calculator::interval_literal::<V>(&sub, ctx);
         }
 
         if tokens.len() == 1 && tokens[0].is_sub_exp() {
-            return Calculator::calculate_with_env(tokens[0].sub.clone(), input, env);
+            let result = Calculator::evaluate::<V>(tokens[0].sub.clone(), ctx)?;
+            return match sub.method {
+                SubMethod::PAREN => Ok(result),
+                SubMethod::ABS => Ok(result.abs()),
+            };
         }
 
         // Handle factorial: [NUMBER/SUBEXP/IDENTIFIER, FACTORIAL]
         if tokens.len() == 2 && tokens[1].is_factorial() {
-            let operand = if tokens[0].is_number() {
-                tokens[0].literal.parse::<f64>().map_err(|_| {
-                    Error::cannot_parse_to_number(input.to_string(), tokens[0].clone())
-                })?
-            } else if tokens[0].is_sub_exp() {
-                Calculator::calculate_with_env(tokens[0].sub.clone(), input, env)?
-            } else if tokens[0].is_identifier() {
-                let var_name = &tokens[0].literal;
-                env.get(var_name).ok_or_else(|| {
-                    Error::new(format!("error: undefined variable '{var_name}'"))
-                })?
-            } else {
-                return Err(Error::missing_some_tokens(input.to_string(), tokens[0].index.1));
+            let operand = match Calculator::operand::<V>(&tokens[0], ctx)? {
+                Some(v) => v,
+                None => {
+                    return Err(Error::missing_some_tokens(ctx.input.to_string(), tokens[0].span.end))
+                }
             };
-            return Calculator::compute_factorial(operand);
+            ctx.budget.operation()?;
+            return operand.factorial();
         }
 
         let mut i: usize = 0;
         while i <= tokens.len() {
             if i > tokens.len() - 1 {
-                let point = tokens.last().map(|t| t.index.1).unwrap_or(0);
-                return Err(Error::missing_some_tokens(input.to_string(), point));
+                let at = tokens.last().map_or(0, |t| t.span.end);
+                return Err(Error::missing_some_tokens(ctx.input.to_string(), at));
             }
 
             let token = &tokens[i];
             if token.is_illegal() {
-                return Err(Error::illegal_token(input.to_string(), token.clone()));
+                return Err(Error::illegal_token(ctx.input.to_string(), token.clone()));
             }
 
-            let mut y: f64 = 0.0;
-            let x: f64 = result;
-            let operation: TokenType = Calculator::take_operation(i, tokens, input)?;
-
-            let y_point = token.index.1;
-            if token.is_number() {
-                y = match token.literal.as_str().parse::<f64>() {
-                    Ok(v) => v,
-                    Err(_) => {
-                        return Err(Error::cannot_parse_to_number(
-                            input.to_string(),
-                            token.clone(),
-                        ))
-                    }
-                };
-            } else if token.is_identifier() {
-                // Variable lookup
-                let var_name = &token.literal;
-                y = env.get(var_name).ok_or_else(|| {
-                    Error::new(format!("error: undefined variable '{var_name}'"))
-                })?;
-            } else if token.is_sub_exp() {
-                y = Calculator::calculate_with_env(token.sub.clone(), input, env)?;
-            } else if token.is_function() {
+            if token.is_comma() {
+                return Err(Error::unexpected_separator(ctx.input.to_string(), token.clone()));
+            }
+
+            let mut y: V = V::from_number(0.0);
+            let x: V = result;
+            let operation: TokenType = Calculator::take_operation(i, tokens, ctx.input)?;
+
+            if token.is_function() {
                 // Function token - the next token should be its argument
                 if i + 1 >= tokens.len() {
-                    return Err(Error::missing_some_tokens(input.to_string(), y_point));
+                    return Err(Error::missing_some_tokens(ctx.input.to_string(), token.span.end));
                 }
                 let arg_token = &tokens[i + 1];
-                let arg = if arg_token.is_sub_exp() {
-                    Calculator::calculate_with_env(arg_token.sub.clone(), input, env)?
-                } else if arg_token.is_number() {
-                    arg_token.literal.parse::<f64>().map_err(|_| {
-                        Error::cannot_parse_to_number(input.to_string(), arg_token.clone())
-                    })?
-                } else if arg_token.is_identifier() {
-                    let var_name = &arg_token.literal;
-                    env.get(var_name).ok_or_else(|| {
-                        Error::new(format!("error: undefined variable '{var_name}'"))
-                    })?
+                if token.takes_arguments() {
+                    y = Calculator::execute_call::<V>(token, arg_token, ctx)?;
                 } else {
-                    return Err(Error::missing_some_tokens(input.to_string(), arg_token.index.1));
-                };
-                y = Calculator::execute_function(token.typ.clone(), arg, input, y_point)?;
+                    let arg = match Calculator::operand::<V>(arg_token, ctx)? {
+                        Some(v) => v,
+                        None => {
+                            return Err(Error::missing_some_tokens(
+                                ctx.input.to_string(),
+                                arg_token.span.end,
+                            ))
+                        }
+                    };
+                    ctx.budget.operation()?;
+                    y = V::function(token.typ.clone(), arg, ctx.input, token.span)?;
+                }
+                ctx.record(&tokens[i..=i + 1], &y);
                 i += 1; // Skip the argument token
+            } else if let Some(v) = Calculator::operand::<V>(token, ctx)? {
+                y = v;
             }
 
-            result = Calculator::execute_operation(x, y, operation, input, y_point)?;
+            ctx.budget.operation()?;
+            result = V::operation(x, y, operation, ctx.input, token.span)?;
             i += 2;
         }
 
@@ -157,14 +345,206 @@ impl Calculator {
         Ok(result)
     }
 
+    // Resolves the value of an operand token: a number, a variable or a sub-expression.
+    // Returns none, if the token isn't an operand.
+    fn operand<V: Domain>(
+        token: &Token,
+        ctx: &mut Context,
+    ) -> Result<Option<V>, Error> {
+        if token.is_number() {
+            let n = token.normalized().parse::<f64>().ok().or_else(|| token.constant());
+            let n = n.ok_or_else(|| {
+                Error::cannot_parse_to_number(ctx.input.to_string(), token.clone())
+            })?;
+            return Ok(Some(V::from_number(n)));
+        }
+
+        if token.is_identifier() {
+            // Variable lookup
+            let var_name = &token.literal;
+            let value = ctx.env.get_value(var_name).ok_or_else(|| {
+                let suggestion = suggest::did_you_mean(var_name, ctx.env);
+                Error::undefined_variable(ctx.input.to_string(), token, suggestion)
+            })?;
+            return V::from_value(var_name, value).map(Some);
+        }
+
+        if token.is_sub_exp() {
+            let value = Calculator::evaluate::<V>(token.sub.clone(), ctx)?;
+
+            // Wrapped single values, like `(5)`, aren't worth a step.
+            let is_trivial = token.sub.method == SubMethod::PAREN
+                && matches!(token.sub.tokens.as_slice(), [t] if !t.is_sub_exp());
+            if !is_trivial {
+                ctx.record(std::slice::from_ref(token), &value);
+            }
+
+            return Ok(Some(value));
+        }
+
+        Ok(None)
+    }
+
+    // Checks if the sub-expression is an interval literal, i.e. square brackets
+    // with a comma directly inside of them: `[9.8, 9.82]`.
+    fn is_interval_literal(sub: &Sub) -> bool {
+        sub.method == SubMethod::ABS && sub.tokens.iter().any(|t| t.is_comma())
+    }
+
+    // Calculates both bounds of an interval literal, and creates the interval from them.
+    fn interval_literal<V: Domain>(
+        sub: &Sub,
+        ctx: &mut Context,
+    ) -> Result<V, Error> {
+        let bounds = Calculator::split_arguments(sub);
+        if bounds.len() != 2 || bounds.iter().any(|b| b.tokens.is_empty()) {
+            let comma = sub.tokens.iter().filter(|t| t.is_comma()).nth(1);
+            return match comma {
+                Some(t) => Err(Error::unexpected_separator(ctx.input.to_string(), t.clone())),
+                None => Err(Error::invalid_arguments(
+                    "interval",
+                    "expected `[lo, hi]` with both bounds",
+                )),
+            };
+        }
+
+        let lo = Calculator::evaluate::<V>(bounds[0].clone(), ctx)?;
+        let hi = Calculator::evaluate::<V>(bounds[1].clone(), ctx)?;
+        V::interval(lo, hi)
+    }
+
+    // Checks if any token of the expression, including nested ones, matches [found].
+    fn uses(sub: &Sub, env: &Environment, found: &dyn Fn(&Token, &Environment) -> bool) -> bool {
+        // The assigned variable's current value doesn't matter.
+        let tokens = match sub.tokens.as_slice() {
+            [name, assign, rest @ ..] if name.is_identifier() && assign.is_assign() => rest,
+            tokens => tokens,
+        };
+
+        tokens.iter().any(|t| {
+            found(t, env) || (t.is_sub_exp() && Calculator::uses(&t.sub, env, found))
+        })
+    }
+
+    // Collects the variables, that the expression reads.
+    fn dependencies(sub: &Sub) -> BTreeSet<String> {
+        Calculator::reads(sub).into_iter().map(|t| t.literal).collect()
+    }
+
+    // Finds the variables, that the expression reads, but [env] doesn't have.
+    // The assigned variable isn't read, so it's never undefined.
+    //
+    //  ╭─────────────────────────╮
+    //  │ area = pi * radius ^ 2  │ ──▶ [pi, radius], if env has neither of them
+    //  ╰─────────────────────────╯
+    pub fn undefined_variables(sub: &Sub, env: &Environment) -> Vec<Token> {
+        let sub = match sub.tokens.as_slice() {
+            [name, assign, rest @ ..] if name.is_identifier() && assign.is_assign() => {
+                Sub::new(rest.to_vec(), sub.method.clone())
+            }
+            _ => sub.clone(),
+        };
+
+        Calculator::reads(&sub)
+            .into_iter()
+            .filter(|t| env.get_value(&t.literal).is_none())
+            .collect()
+    }
+
+    // Collects the identifier tokens, that the expression reads, in the order of input.
+    // The integration variable isn't read by its own integrand.
+    fn reads(sub: &Sub) -> Vec<Token> {
+        let mut found: Vec<Token> = Vec::new();
+
+        for (i, t) in sub.tokens.iter().enumerate() {
+            if t.is_identifier() {
+                found.push(t.clone());
+                continue;
+            }
+
+            if !t.is_sub_exp() {
+                continue;
+            }
+
+            let is_integrand = i > 0 && sub.tokens[i - 1].typ == TokenType::INTEGRATE;
+            if !is_integrand {
+                found.extend(Calculator::reads(&t.sub));
+                continue;
+            }
+
+            let args = Calculator::split_arguments(&t.sub);
+            let bound = match args.get(1).map(|a| a.tokens.as_slice()) {
+                Some([v]) if v.is_identifier() => Some(v.literal.clone()),
+                _ => None,
+            };
+
+            for (n, arg) in args.iter().enumerate() {
+                let reads = Calculator::reads(arg).into_iter();
+                let is_bound = |t: &Token| n <= 1 && bound.as_ref() == Some(&t.literal);
+                found.extend(reads.filter(|t| !is_bound(t)));
+            }
+        }
+
+        found
+    }
+
+    // Recomputes every variable, that depends on [name], in reactive mode.
+    // Returns the names of recomputed variables, in the order of recomputation.
+    //
+    // Variables that still read undefined ones are skipped, they wait for them.
+    // If any variable can't be recomputed, the ones recomputed before it are restored,
+    // so no variable is left computed from a different value than the others.
+    pub(crate) fn recompute(env: &mut Environment, name: &str) -> Result<Vec<String>, Error> {
+        let mut budget = Budget::new(EvalLimits::unlimited());
+        Calculator::recompute_with_budget(env, name, &mut budget)
+    }
+
+    fn recompute_with_budget(
+        env: &mut Environment,
+        name: &str,
+        budget: &mut Budget,
+    ) -> Result<Vec<String>, Error> {
+        let dependents = env.dependents(name);
+        let snapshot = env.snapshot(&dependents);
+
+        let mut recomputed: Vec<String> = Vec::new();
+        for dependent in dependents {
+            let formula = match env.formula(&dependent) {
+                Some(formula) => formula.clone(),
+                None => continue,
+            };
+
+            if !Calculator::undefined_variables(&formula.sub, env).is_empty() {
+                continue;
+            }
+
+            let mut ctx = Context::new(&formula.input, env);
+            ctx.budget = *budget;
+            let value = Calculator::evaluate_value(formula.sub.clone(), &mut ctx);
+            *budget = ctx.budget;
+
+            match value {
+                Ok(value) => env.set_value(&dependent, value),
+                Err(e) => {
+                    env.restore(snapshot);
+                    return Err(Error::recompute_failed(&dependent, &e));
+                }
+            }
+
+            recomputed.push(dependent);
+        }
+
+        Ok(recomputed)
+    }
+
     fn take_operation(i: usize, tokens: &[Token], input: &str) -> Result<TokenType, Error> {
         if i == 0 {
             return Ok(TokenType::PLUS);
         }
 
         if i > tokens.len() {
-            let point = tokens.last().map(|t| t.index.1).unwrap_or(0);
-            return Err(Error::missing_some_tokens(input.to_string(), point));
+            let at = tokens.last().map_or(0, |t| t.span.end);
+            return Err(Error::missing_some_tokens(input.to_string(), at));
         }
 
         let prev_token = &tokens[i - 1];
@@ -176,6 +556,7 @@ impl Calculator {
             || prev_token.is_div_or_prod()
             || prev_token.is_percentage()
             || prev_token.is_power()
+            || prev_token.is_plusminus()
         {
             return Ok(prev_token.typ.clone());
         }
@@ -198,11 +579,11 @@ impl Calculator {
         y: f64,
         operation: TokenType,
         input: &str,
-        y_point: i32,
+        y_span: Span,
     ) -> Result<f64, Error> {
         // Check for division by zero
         if operation == TokenType::DIVIDE && y == 0.0 {
-            return Err(Error::division_by_zero(input.to_string(), y_point));
+            return Err(Error::division_by_zero(input.to_string(), y_span));
         }
 
         let result = match operation {
@@ -221,16 +602,16 @@ impl Calculator {
     // Computes the factorial of a non-negative integer.
     // n! = n * (n-1) * (n-2) * ... * 2 * 1
     // 0! = 1 by definition
-    fn compute_factorial(n: f64) -> Result<f64, Error> {
+    pub(crate) fn compute_factorial(n: f64) -> Result<f64, Error> {
         // Check if n is a non-negative integer
         if n < 0.0 {
-            return Err(Error::new(format!(
+            return Err(Error::out_of_domain(format!(
                 "error: factorial is not defined for negative numbers: {n}"
             )));
         }
 
         if n != n.floor() {
-            return Err(Error::new(format!(
+            return Err(Error::out_of_domain(format!(
                 "error: factorial is only defined for integers: {n}"
             )));
         }
@@ -239,7 +620,7 @@ impl Calculator {
 
         // Factorial grows very fast, limit to reasonable values
         if n > 170 {
-            return Err(Error::new(format!(
+            return Err(Error::out_of_domain(format!(
                 "error: factorial of {n} is too large to compute"
             )));
         }
@@ -252,28 +633,134 @@ impl Calculator {
         Ok(result)
     }
 
+    // Executes a multi-argument function, like `integrate(x ^ 2, x, 0, 1)`.
+    // The [arg] token must be the sub-expression, which holds comma separated arguments.
+    fn execute_call<V: Domain>(
+        func: &Token,
+        arg: &Token,
+        ctx: &mut Context,
+    ) -> Result<V, Error> {
+        if !arg.is_sub_exp() {
+            return Err(Error::missing_some_tokens(ctx.input.to_string(), arg.span.end));
+        }
+
+        let args = Calculator::split_arguments(&arg.sub);
+        match func.typ {
+            TokenType::INTEGRATE => Calculator::integrate::<V>(args, ctx),
+            _ => Err(Error::missing_some_tokens(ctx.input.to_string(), func.span.end)),
+        }
+    }
+
+    // Splits the tokens of an argument list by the commas between them.
+    //
+    //  ╭─────────────────────╮     ╭───────────────────────────╮
+    //  │ x ^ 2 , x , 0 , 1   │ ──▶ │ {x ^ 2}  {x}  {0}  {1}    │
+    //  ╰─────────────────────╯     ╰───────────────────────────╯
+    fn split_arguments(sub: &Sub) -> Vec<Sub> {
+        sub.tokens
+            .split(|t| t.is_comma())
+            .map(|tokens| Sub::new(tokens.to_vec(), SubMethod::PAREN))
+            .collect()
+    }
+
+    // The built-in numeric definite integration:
+    // `integrate(expr, x, a, b)` or `integrate(expr, x, a, b, tolerance)`.
+    //
+    // [expr] is evaluated with [x] bound in a scoped environment, see [Integrator].
+    fn integrate<V: Domain>(
+        args: Vec<Sub>,
+        ctx: &mut Context,
+    ) -> Result<V, Error> {
+        if args.len() != 4 && args.len() != 5 {
+            return Err(Error::invalid_arguments(
+                "integrate",
+                &format!("expected `integrate(expr, x, a, b)`, got {} arguments", args.len()),
+            ));
+        }
+
+        let var = match args[1].tokens.as_slice() {
+            [t] if t.is_identifier() => t.literal.clone(),
+            _ => {
+                return Err(Error::invalid_arguments(
+                    "integrate",
+                    "the second argument must be a variable name",
+                ))
+            }
+        };
+
+        if args[0].tokens.is_empty() {
+            return Err(Error::invalid_arguments("integrate", "missing the integrand"));
+        }
+
+        let a = Calculator::exact_argument::<V>(&args[2], ctx)?;
+        let b = Calculator::exact_argument::<V>(&args[3], ctx)?;
+
+        let mut integrator = Integrator::new();
+        if let Some(tolerance) = args.get(4) {
+            let tolerance = Calculator::exact_argument::<V>(tolerance, ctx)?;
+            integrator = integrator.with_tolerance(tolerance);
+        }
+
+        // The integrand is evaluated in a child scope of environment,
+        // but shares the budget of the whole calculation.
+        let mut scope = ctx.env.child();
+        scope.set_reactive(false);
+        let mut inner = Context {
+            input: ctx.input,
+            env: &mut scope,
+            trace: None,
+            budget: ctx.budget,
+        };
+
+        let integral = integrator.integrate(
+            |x| {
+                inner.env.set(&var, x);
+                Calculator::evaluate::<f64>(args[0].clone(), &mut inner)
+            },
+            a,
+            b,
+        );
+
+        // Only the value is returned, the estimated error fits in the tolerance anyway.
+        // Use [Integrator] directly to get the error estimate as well.
+        ctx.budget = inner.budget;
+        Ok(V::from_number(integral?.value))
+    }
+
+    // Calculates an argument of a built-in, that must be an exact number.
+    fn exact_argument<V: Domain>(
+        arg: &Sub,
+        ctx: &mut Context,
+    ) -> Result<f64, Error> {
+        let value = Calculator::evaluate::<V>(arg.clone(), ctx)?;
+        value.to_number().ok_or_else(|| {
+            Error::invalid_arguments("integrate", "bounds and tolerance must be exact numbers")
+        })
+    }
+
     // Executes a math function on the given argument.
-    fn execute_function(
+    pub(crate) fn execute_function(
         func: TokenType,
         arg: f64,
         input: &str,
-        point: i32,
+        span: Span,
     ) -> Result<f64, Error> {
         let result = match func {
             TokenType::SQRT => {
                 if arg < 0.0 {
-                    return Err(Error::new(format!(
+                    return Err(Error::out_of_domain(format!(
                         "error: cannot take square root of negative number: {arg}"
                     )));
                 }
                 arg.sqrt()
             }
+            TokenType::CBRT => arg.cbrt(),
             TokenType::SIN => arg.sin(),
             TokenType::COS => arg.cos(),
             TokenType::TAN => arg.tan(),
             TokenType::LOG => {
                 if arg <= 0.0 {
-                    return Err(Error::new(format!(
+                    return Err(Error::out_of_domain(format!(
                         "error: logarithm undefined for non-positive number: {arg}"
                     )));
                 }
@@ -281,7 +768,7 @@ impl Calculator {
             }
             TokenType::LN => {
                 if arg <= 0.0 {
-                    return Err(Error::new(format!(
+                    return Err(Error::out_of_domain(format!(
                         "error: natural log undefined for non-positive number: {arg}"
                     )));
                 }
@@ -292,7 +779,7 @@ impl Calculator {
             TokenType::CEIL => arg.ceil(),
             TokenType::ROUND => arg.round(),
             _ => {
-                return Err(Error::missing_some_tokens(input.to_string(), point));
+                return Err(Error::missing_some_tokens(input.to_string(), span.end));
             }
         };
 
@@ -300,10 +787,61 @@ impl Calculator {
     }
 }
 
+// Plain numbers are the default domain of [Calculator].
+impl Domain for f64 {
+    fn from_number(n: f64) -> Self {
+        n
+    }
+
+    fn from_value(name: &str, value: &Value) -> Result<Self, Error> {
+        value
+            .as_number()
+            .ok_or_else(|| Error::incompatible_variable(name, value.kind(), "plain number"))
+    }
+
+    fn into_value(self) -> Value {
+        Value::Number(self)
+    }
+
+    fn to_number(&self) -> Option<f64> {
+        Some(*self)
+    }
+
+    fn operation(
+        x: Self,
+        y: Self,
+        operation: TokenType,
+        input: &str,
+        span: Span,
+    ) -> Result<Self, Error> {
+        if operation == TokenType::PLUSMINUS {
+            return Err(Error::unsupported_in_mode("`±`", "plain number"));
+        }
+
+        Calculator::execute_operation(x, y, operation, input, span)
+    }
+
+    fn function(func: TokenType, arg: Self, input: &str, span: Span) -> Result<Self, Error> {
+        Calculator::execute_function(func, arg, input, span)
+    }
+
+    fn factorial(self) -> Result<Self, Error> {
+        Calculator::compute_factorial(self)
+    }
+
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+
+    fn interval(_lo: Self, _hi: Self) -> Result<Self, Error> {
+        Err(Error::unsupported_in_mode("interval literal", "plain number"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lexer::Lexer;
+    use crate::{errors::ErrorKind, lexer::Lexer};
     use std::collections::HashMap;
 
     #[test]
@@ -347,14 +885,14 @@ mod tests {
         ]);
 
         for (expected, args) in test_data {
-            let result = Calculator::execute_operation(args.0, args.1, args.2, "test", 0);
+            let result = Calculator::execute_operation(args.0, args.1, args.2, "test", Span::default());
             assert_eq!(result.unwrap().to_string(), expected);
         }
     }
 
     #[test]
     fn division_by_zero() {
-        let result = Calculator::execute_operation(10.0, 0.0, TokenType::DIVIDE, "10 / 0", 5);
+        let result = Calculator::execute_operation(10.0, 0.0, TokenType::DIVIDE, "10 / 0", Span::new(5, 6));
         assert!(result.is_err());
 
         let test_cases: Vec<&str> = vec![
@@ -380,6 +918,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn error_spans() {
+        let test_data: Vec<(&str, Option<&str>)> = vec![
+            ("10 / 0", Some("0")),
+            ("2 • 3 / 0", Some("0")),
+            ("¤ = 2 • 3 / 0", Some("¤")),
+            ("é = 1 / (2 - 2)", Some("(2 - 2)")),
+            ("2 • 3 •", Some("")),
+            ("sqrt(0 - 4)", None),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let error = Calculator::calculate(sub, input).unwrap_err();
+            assert_eq!(error.span().map(|s| s.text(input)), expected, "Failed for: {input}");
+        }
+    }
+
     #[test]
     fn valid_division() {
         let test_cases: HashMap<&str, f64> = HashMap::from([
@@ -497,6 +1053,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn error_kinds() {
+        let test_data: Vec<(&str, ErrorKind)> = vec![
+            ("", ErrorKind::EmptyInput),
+            ("()", ErrorKind::EmptyInput),
+            ("5 +", ErrorKind::MissingTokens),
+            ("2 $ 3", ErrorKind::IllegalToken),
+            ("(5 + 3", ErrorKind::MismatchedBrackets),
+            ("( ]", ErrorKind::MismatchedBrackets),
+            ("5 / 0", ErrorKind::DivisionByZero),
+            ("x + 1", ErrorKind::UndefinedVariable),
+            ("sqrt(-4)", ErrorKind::OutOfDomain),
+            ("ln(0)", ErrorKind::OutOfDomain),
+            ("(-3)!", ErrorKind::OutOfDomain),
+            ("integrate(x, 0, 1)", ErrorKind::InvalidArguments),
+            ("integrate(exp(x), x, 0, 1000)", ErrorKind::NotFinite),
+            ("2 ± 1", ErrorKind::UnsupportedInMode),
+        ];
+
+        for (input, expected) in test_data {
+            let result = match Lexer::lex(input) {
+                Ok(sub) => Calculator::calculate(sub, input),
+                Err(e) => Err(e),
+            };
+            assert_eq!(result.map_err(|e| e.kind()), Err(expected), "Failed for: {input}");
+        }
+    }
+
     #[test]
     fn complex_nested_expressions() {
         let cases: HashMap<&str, f64> = HashMap::from([
@@ -1320,6 +1904,30 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn undefined_variables() {
+        let mut env = Environment::new();
+        env.set("x", 1.0);
+
+        let test_data: Vec<(&str, Vec<&str>)> = vec![
+            ("x + 1", vec![]),
+            ("y + x * z", vec!["y", "z"]),
+            ("y = x + y", vec!["y"]),
+            ("area = pi * r ^ 2", vec!["pi", "r"]),
+            ("integrate(t * k, t, 0, b)", vec!["k", "b"]),
+            ("sqrt([y - 1])", vec!["y"]),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let undefined: Vec<String> = Calculator::undefined_variables(&sub, &env)
+                .into_iter()
+                .map(|t| t.literal)
+                .collect();
+            assert_eq!(undefined, expected, "Failed for: {input}");
+        }
+    }
+
     #[test]
     fn variable_complex_expressions() {
         let mut env = Environment::new();
@@ -1379,6 +1987,48 @@ mod tests {
         assert_eq!(env.get("z"), Some(15.0));
     }
 
+    #[test]
+    fn unicode_input() {
+        let mut env = Environment::new();
+        env.set("x", 3.0);
+        env.set("r", 2.0);
+
+        // Copied from word processors and rendered LaTeX, with their own signs and spaces.
+        let test_data: Vec<(&str, f64)> = vec![
+            ("√16 × 2 − 3", 5.0),
+            ("10 ÷ 4", 2.5),
+            ("2\u{a0}×\u{a0}3", 6.0),
+            ("2\u{2009}×\u{2009}3", 6.0),
+            ("10\u{202f}÷\u{202f}4", 2.5),
+            ("\u{200b}2 +\u{200b}3\u{200b}", 5.0),
+            ("\u{feff}x\u{2060}× 2", 6.0),
+            ("2 ⋅ 3 · 2", 12.0),
+            ("∛27 − √(9)", 0.0),
+            ("x² + 2⋅x + 1", 16.0),
+            ("2³ + 1", 9.0),
+            ("(1 + 1)²", 4.0),
+            ("x⁻¹ × 3", 1.0),
+            ("2πr", 4.0 * std::f64::consts::PI),
+            ("τ ÷ 2", std::f64::consts::PI),
+            ("1 ÷ ∞", 0.0),
+            ("|−3| · 2", 6.0),
+            ("||x| − 5|", 2.0),
+            ("|x − 5| |1 − x|", 4.0),
+            ("2|x − 6|", 6.0),
+            ("5 − −3", 8.0),
+            ("√√16", 2.0),
+            ("∛√64", 2.0),
+            ("2√√16 + 1", 5.0),
+            ("√√(8 + 8) × 2", 4.0),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let result = Calculator::calculate_with_env(sub, input, &mut env);
+            assert_eq!(result, Ok(expected), "Failed for: {input}");
+        }
+    }
+
     #[test]
     fn variable_self_reference() {
         let mut env = Environment::new();
@@ -1405,6 +2055,91 @@ mod tests {
         assert_eq!(result, 6.0);
     }
 
+    #[test]
+    fn reactive_variables() {
+        let mut env = Environment::reactive();
+
+        let test_data: Vec<(&str, f64)> = vec![
+            ("a = 2", 2.0),
+            ("b = a * 10", 20.0),
+            ("c = integrate(x * a, x, 0, b)", 400.0),
+            ("a = 1", 1.0),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let result = Calculator::calculate_with_env(sub, input, &mut env);
+            assert!((result.unwrap() - expected).abs() < 1e-6, "Failed for: {input}");
+        }
+
+        // Changing [a] has recomputed [b], and then [c] with the new [b].
+        assert_eq!(env.get("b"), Some(10.0));
+        assert!((env.get("c").unwrap() - 50.0).abs() < 1e-6);
+        assert_eq!(env.dependents("a"), vec!["b", "c"]);
+
+        // The integration variable isn't a dependency.
+        assert!(env.formula("x").is_none());
+        assert!(env.dependents("x").is_empty());
+    }
+
+    #[test]
+    fn reactive_errors() {
+        let mut env = Environment::reactive();
+        let calculate = |input: &str, env: &mut Environment| {
+            Calculator::calculate_with_env(Lexer::lex(input).unwrap(), input, env)
+        };
+
+        calculate("b = 1", &mut env).unwrap();
+        calculate("a = b + 1", &mut env).unwrap();
+        assert_eq!(
+            calculate("b = a", &mut env),
+            Err(Error::circular_dependency(&[
+                String::from("b"),
+                String::from("a"),
+                String::from("b"),
+            ]))
+        );
+        assert_eq!(env.get("b"), Some(1.0));
+        assert!(env.formula("b").is_none());
+
+        assert_eq!(
+            calculate("a = a + 1", &mut env),
+            Err(Error::circular_dependency(&[String::from("a"), String::from("a")]))
+        );
+        assert_eq!(env.get("a"), Some(2.0));
+
+        calculate("c = 10 / b", &mut env).unwrap();
+        let error = calculate("b = 0", &mut env).unwrap_err();
+        assert!(error.to_string().starts_with("error: variable 'c' couldn't be recomputed"));
+
+        // The failed assignment is rolled back, with [a] that was recomputed before [c].
+        assert_eq!(env.get("b"), Some(1.0));
+        assert_eq!(env.get("a"), Some(2.0));
+        assert_eq!(env.get("c"), Some(10.0));
+
+        // Cycles are found before the evaluation, so variables of them don't need values.
+        let mut env = Environment::reactive();
+        assert!(calculate("a = b + 1", &mut env).is_err());
+        assert!(env.formula("a").is_some());
+        assert_eq!(
+            calculate("b = a", &mut env),
+            Err(Error::circular_dependency(&[
+                String::from("b"),
+                String::from("a"),
+                String::from("b"),
+            ]))
+        );
+
+        // The waiting variable is computed, once its dependencies are defined.
+        calculate("b = 2", &mut env).unwrap();
+        assert_eq!(env.get("a"), Some(3.0));
+
+        // Failures of existing variables don't keep the new formula.
+        assert!(calculate("a = b + z", &mut env).is_err());
+        assert_eq!(env.formula("a").map(|f| f.expression()), Some("b + 1"));
+        assert_eq!(env.get("a"), Some(3.0));
+    }
+
     #[test]
     fn variable_case_sensitivity() {
         let mut env = Environment::new();
@@ -1535,6 +2270,8 @@ mod tests {
             ("  (5+5)  ", 10.0),
             ("sqrt(  16  )", 4.0),
             ("sqrt(16)", 4.0),
+            ("2 * 2\n+ 1", 5.0),
+            ("2 * 2\t- 1", 3.0),
         ];
 
         for (input, expected) in cases {
@@ -1711,6 +2448,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn single_values() {
         let cases: Vec<(&str, f64)> = vec![
             ("5", 5.0),
@@ -1926,4 +2664,394 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn integrate_builtin() {
+        let cases: Vec<(&str, f64)> = vec![
+            ("integrate(x ^ 2, x, 0, 1)", 1.0 / 3.0),
+            ("integrate(x^2,x,0,1)", 1.0 / 3.0),
+            ("integrate(2x, x, 0, 3)", 9.0),
+            ("integrate(sin(x), x, 0, 3.141592653589793)", 2.0),
+            ("integrate(1 / x, x, 1, 2)", 2.0_f64.ln()),
+            ("integrate([x], x, -1, 1)", 1.0),
+            ("integrate(x, x, 1, 0)", -0.5),
+            ("integrate(x, x, -1.5, 0.5)", -1.0),
+            ("integrate(x ^ 2, x, 0, 1, 0.001)", 1.0 / 3.0),
+            ("2 * integrate(x, x, 0, 2) + 1", 5.0),
+            ("integrate(x, x, 0, 2) ^ 2", 4.0),
+            ("integrate(integrate(x * y, y, 0, 1), x, 0, 2)", 1.0),
+        ];
+
+        for (input, expected) in cases {
+            let sub = Lexer::lex(input).unwrap();
+            let result = Calculator::calculate(sub, input).unwrap();
+            assert!(
+                (result - expected).abs() < 1e-9,
+                "Failed for input: {}, expected: {}, got: {}",
+                input,
+                expected,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn integrate_with_variables() {
+        let mut env = Environment::new();
+        env.set("x", 10.0);
+        env.set("upper", 3.0);
+
+        let input = "integrate(x ^ 2, x, 0, upper)";
+        let sub = Lexer::lex(input).unwrap();
+        let result = Calculator::calculate_with_env(sub, input, &mut env).unwrap();
+        assert!((result - 9.0).abs() < 1e-9);
+        assert_eq!(env.get("x"), Some(10.0));
+    }
+
+    #[test]
+    fn integrate_errors() {
+        let error_cases: Vec<&str> = vec![
+            "integrate(x, 0, 1)",
+            "integrate(x, 2, 0, 1)",
+            "integrate(, x, 0, 1)",
+            "integrate(exp(x), x, 0, 1000)",
+            "integrate(1 / x, x, -1, 1)",
+            "integrate(x, x, 0, 1, 0)",
+            "integrate(y, x, 0, 1)",
+            "integrate 5",
+        ];
+
+        for input in error_cases {
+            let result = match Lexer::lex(input) {
+                Ok(sub) => Calculator::calculate(sub, input),
+                Err(e) => Err(e),
+            };
+            assert!(
+                result.is_err(),
+                "Expected error for input: '{}', got: {:?}",
+                input,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn interval_expressions() {
+        let test_data: Vec<(&str, f64, f64)> = vec![
+            ("[1, 2] + [3, 4]", 4.0, 6.0),
+            ("[1, 2] - [3, 4]", -3.0, -1.0),
+            ("[-1, 2] * [3, 4]", -4.0, 8.0),
+            ("[2, 4] / [1, 2]", 1.0, 4.0),
+            ("[-2, 3] ^ 2", 0.0, 9.0),
+            ("[-3, -1]", -3.0, -1.0),
+            ("[[-3, -1]]", 1.0, 3.0),
+            ("[[-3, 2]]", 0.0, 3.0),
+            ("2.5 ± 0.5", 2.0, 3.0),
+            ("[9.8, 9.82] * 2.5 ± 0.1", 23.52, 25.532),
+            ("sqrt([4, 9])", 2.0, 3.0),
+            ("[1 + 1, 2 * 3]", 2.0, 6.0),
+            ("5", 5.0, 5.0),
+        ];
+
+        for (input, lo, hi) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let mut env = Environment::new();
+            let result = Calculator::calculate_interval(sub, input, &mut env).unwrap();
+
+            assert!(
+                result.contains(lo) && result.contains(hi),
+                "Failed for: {input}, expected: [{lo}, {hi}], got: {result}"
+            );
+            assert!(
+                (result.lo() - lo).abs() < 1e-9 && (result.hi() - hi).abs() < 1e-9,
+                "Too wide for: {input}, expected: [{lo}, {hi}], got: {result}"
+            );
+        }
+    }
+
+    #[test]
+    fn interval_variables() {
+        let mut env = Environment::new();
+        let inputs = ["g = [9.8, 9.82]", "t = [1.9, 2.1]", "h = g * t ^ 2 / 2"];
+        for input in inputs {
+            let sub = Lexer::lex(input).unwrap();
+            let result = Calculator::calculate_value(sub, input, &mut env).unwrap();
+            assert!(result.is_interval(), "Expected interval for: {input}");
+        }
+
+        let h = env.get_value("h").and_then(Value::as_interval).unwrap();
+        assert!(h.contains(9.8 * 1.9 * 1.9 / 2.0) && h.contains(9.82 * 2.1 * 2.1 / 2.0));
+        assert_eq!(env.get("h"), None);
+
+        // Plain number mode can't use interval variables.
+        let input = "g * 2";
+        let sub = Lexer::lex(input).unwrap();
+        assert_eq!(
+            Calculator::calculate_with_env(sub.clone(), input, &mut env),
+            Err(Error::incompatible_variable("g", "an interval", "plain number"))
+        );
+        assert!(Calculator::calculate_value(sub, input, &mut env)
+            .unwrap()
+            .is_interval());
+
+        // Re-assigning an interval variable to a number is a plain calculation.
+        let input = "g = 10";
+        let sub = Lexer::lex(input).unwrap();
+        assert_eq!(
+            Calculator::calculate_value(sub, input, &mut env),
+            Ok(Value::Number(10.0))
+        );
+    }
+
+    #[test]
+    fn interval_errors() {
+        let input = "10 / [-1, 1]";
+        let sub = Lexer::lex(input).unwrap();
+        let result = Calculator::calculate_interval(sub, input, &mut Environment::new());
+        assert_eq!(
+            result,
+            Err(Error::interval_division_by_zero(
+                input.to_string(),
+                Span::new(5, 12),
+                Interval::new(-1.0, 1.0).unwrap()
+            ))
+        );
+
+        let error_cases: Vec<&str> = vec![
+            "[2, 1]",
+            "[1, 2, 3]",
+            "[1, ]",
+            "sqrt([-1, 4])",
+            "ln([0, 1])",
+            "[1, 2]!",
+            "integrate(x, x, [0, 1], 1)",
+        ];
+
+        for input in error_cases {
+            let result = match Lexer::lex(input) {
+                Ok(sub) => Calculator::calculate_interval(sub, input, &mut Environment::new()),
+                Err(e) => Err(e),
+            };
+            assert!(
+                result.is_err(),
+                "Expected error for input: '{}', got: {:?}",
+                input,
+                result
+            );
+        }
+
+        // Plain number mode rejects interval syntax.
+        for input in ["[1, 2] + 1", "2 ± 1"] {
+            let sub = Lexer::lex(input).unwrap();
+            assert!(Calculator::calculate(sub, input).is_err());
+        }
+    }
+
+    #[test]
+    fn uncertain_expressions() {
+        let test_data: Vec<(&str, f64, f64)> = vec![
+            ("12.3 ± 0.2", 12.3, 0.2),
+            ("2 * 12.3 ± 0.2", 24.6, 0.4),
+            ("3 ± 0.3 + 4 ± 0.4", 7.0, 0.5),
+            ("(3 ± 0.3) ^ 2", 9.0, 1.8),
+            ("sqrt(16 ± 0.8)", 4.0, 0.1),
+            ("1 - [-2 ± 0.1]", -1.0, 0.1),
+            ("5", 5.0, 0.0),
+            // `±` binds tighter than any other operation.
+            ("1 ± 0.1 * 3", 3.0, 0.3),
+            ("3 * 1 ± 0.1", 3.0, 0.3),
+            ("6 / 2 ± 0.1", 3.0, 0.15),
+            ("1 + 2 ± 0.1", 3.0, 0.1),
+            ("2 ± 0.1 ^ 2", 4.0, 0.4),
+        ];
+
+        for (input, value, sigma) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let mut env = Environment::new();
+            let result = Calculator::calculate_uncertain(sub, input, &mut env).unwrap();
+
+            assert!(
+                (result.value() - value).abs() < 1e-12 && (result.sigma() - sigma).abs() < 1e-12,
+                "Failed for: {input}, expected: {value} ± {sigma}, got: {result}"
+            );
+        }
+    }
+
+    #[test]
+    fn uncertain_variables() {
+        let mut env = Environment::new();
+        let test_data: Vec<(&str, &str)> = vec![
+            ("x = 12.3 ± 0.2", "12.3 ± 0.2"),
+            ("x - x", "0 ± 0"),
+            ("x / x", "1 ± 0"),
+            ("x + x", "24.6 ± 0.4"),
+            ("y = 2 * x", "24.6 ± 0.4"),
+            ("y - 2 * x", "0 ± 0"),
+            ("x = 5", "5"),
+            ("2 * x ± 0.1", "10 ± 0.2"),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let result = Calculator::calculate_value(sub, input, &mut env).unwrap();
+            assert_eq!(result.to_string(), expected, "Failed for: {input}");
+        }
+
+        // Independent measurements aren't correlated.
+        let mut env = Environment::new();
+        for input in ["a = 3 ± 0.3", "b = 3 ± 0.4", "c = a - b"] {
+            let sub = Lexer::lex(input).unwrap();
+            Calculator::calculate_value(sub, input, &mut env).unwrap();
+        }
+        let c = env.get_value("c").and_then(Value::as_uncertain).unwrap();
+        assert!((c.sigma() - 0.5).abs() < 1e-12);
+
+        // Interval literals turn `±` into an interval half-width.
+        let input = "[1, 2] + 3 ± 1";
+        let sub = Lexer::lex(input).unwrap();
+        let result = Calculator::calculate_value(sub, input, &mut env).unwrap();
+        assert_eq!(result, Value::Interval(Interval::new(3.0, 6.0).unwrap()));
+
+        // Uncertain values can't be mixed with intervals or plain numbers.
+        let input = "a * [1, 2]";
+        let sub = Lexer::lex(input).unwrap();
+        assert_eq!(
+            Calculator::calculate_value(sub, input, &mut env),
+            Err(Error::incompatible_variable("a", "an uncertain value", "interval"))
+        );
+        assert!(Calculator::calculate(Lexer::lex("a").unwrap(), "a").is_err());
+    }
+
+    #[test]
+    fn uncertain_errors() {
+        let error_cases: Vec<&str> = vec![
+            "1 ± -0.1",
+            "1 ± (2 ± 0.1)",
+            "10 / (0 ± 0.1)",
+            "sqrt(0 ± 0.1)",
+            "ln(-1 ± 0.1)",
+            "(5 ± 0.1)!",
+            "[1, 2]",
+        ];
+
+        for input in error_cases {
+            let result = match Lexer::lex(input) {
+                Ok(sub) => Calculator::calculate_uncertain(sub, input, &mut Environment::new()),
+                Err(e) => Err(e),
+            };
+            assert!(
+                result.is_err(),
+                "Expected error for input: '{}', got: {:?}",
+                input,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn traced() {
+        let test_data: HashMap<&str, &str> = HashMap::from([
+            ("(2 + 5) * 3", "(2 + 5) * 3 → 7 * 3 → 21"),
+            ("2 + 3 * 4 - 1", "2 + 3 * 4 - 1 → 2 + 12 - 1 → 13"),
+            ("sqrt(9 + 16) + 1", "sqrt(9 + 16) + 1 → sqrt(25) + 1 → 5 + 1 → 6"),
+            ("[2 - 12] * 2", "[2 - 12] * 2 → 10 * 2 → 20"),
+            ("5! + 1", "5! + 1 → 120 + 1 → 121"),
+            ("x = (1 + 2) * 3", "x = (1 + 2) * 3 → x = 3 * 3 → x = 9"),
+            ("[1, 2] * 3", "[1, 2] * 3 → [3, 6]"),
+            ("42", "42"),
+        ]);
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            let mut env = Environment::new();
+            let (_, trace) = Calculator::calculate_traced(sub, input, &mut env).unwrap();
+            assert_eq!(trace.to_string(), expected);
+        }
+
+        let input = "y = (2 + 5) * 3";
+        let sub = Lexer::lex(input).unwrap();
+        let mut env = Environment::new();
+        let (value, trace) = Calculator::calculate_traced(sub, input, &mut env).unwrap();
+
+        assert_eq!(value, Value::Number(21.0));
+        assert_eq!(env.get("y"), Some(21.0));
+        assert_eq!(trace.steps().len(), 2);
+        assert_eq!(trace.steps()[0].source, "(2 + 5)");
+        assert_eq!(trace.steps()[0].span, Span::new(4, 11));
+        assert_eq!(trace.steps()[0].value, Value::Number(7.0));
+
+        let input = "(2 + 5) / 0";
+        let sub = Lexer::lex(input).unwrap();
+        assert!(Calculator::calculate_traced(sub, input, &mut env).is_err());
+    }
+
+    #[test]
+    fn limits() {
+        let deep_parens = format!("{}1{}", "(".repeat(1000), ")".repeat(1000));
+        let deep_powers = vec!["2"; 500].join(" ^ ");
+        let nested_integral =
+            "integrate(integrate(integrate(sqrt(x * y * z), x, 0, 1), y, 0, 1), z, 0, 1)";
+
+        let test_data: Vec<(String, EvalLimits, ErrorKind)> = vec![
+            (
+                "1 + 1".repeat(100),
+                EvalLimits::default().with_max_input_length(100),
+                ErrorKind::InputTooLong,
+            ),
+            (
+                "1 + 1".repeat(100),
+                EvalLimits::default().with_max_tokens(100),
+                ErrorKind::TooManyTokens,
+            ),
+            (deep_parens, EvalLimits::default(), ErrorKind::TooDeep),
+            (deep_powers, EvalLimits::default(), ErrorKind::TooDeep),
+            ("2^".repeat(2400) + "2", EvalLimits::default(), ErrorKind::TooDeep),
+            ("√".repeat(2400) + "16", EvalLimits::default(), ErrorKind::TooDeep),
+            (
+                nested_integral.to_string(),
+                EvalLimits::default().with_max_operations(10_000),
+                ErrorKind::TooManyOperations,
+            ),
+            (
+                nested_integral.to_string(),
+                EvalLimits::unlimited().with_timeout(std::time::Duration::from_millis(10)),
+                ErrorKind::Timeout,
+            ),
+        ];
+
+        for (input, limits, expected) in test_data {
+            let result = match Lexer::lex_with_limits(&input, &limits) {
+                Ok(sub) => {
+                    Calculator::calculate_with_limits(sub, &input, &mut Environment::new(), &limits)
+                }
+                Err(e) => Err(e),
+            };
+
+            assert_eq!(
+                result.map_err(|e| e.kind()),
+                Err(expected),
+                "Failed for: {}",
+                &input[..input.len().min(50)]
+            );
+        }
+
+        // Long chains of powers are refused while lexing, instead of being combined first.
+        let long_powers = "2^".repeat(50_000) + "2";
+        let limits = EvalLimits::default()
+            .with_max_input_length(200_000)
+            .with_max_tokens(200_000);
+        let started = std::time::Instant::now();
+        let result = Lexer::lex_with_limits(&long_powers, &limits).map_err(|e| e.kind());
+        assert_eq!(result, Err(ErrorKind::TooDeep));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        // Inputs within limits are calculated as usual.
+        let limits = EvalLimits::default();
+        for (input, expected) in [("2 + 2 * 2", 6.0), ("integrate(x, x, 0, 2)", 2.0)] {
+            let sub = Lexer::lex_with_limits(input, &limits).unwrap();
+            let result =
+                Calculator::calculate_with_limits(sub, input, &mut Environment::new(), &limits);
+            assert_eq!(result, Ok(Value::Number(expected)));
+        }
+    }
 }
@@ -4,52 +4,343 @@
 // that can be found in the LICENSE file.
 //
 
-use std::collections::HashMap;
+use crate::{calculator::Calculator, errors::Error, token::Sub, value::Value};
+use std::collections::{BTreeSet, HashMap};
 
 /// Environment holds variable bindings for the calculator.
 /// Variables are stored as name -> value mappings.
+/// A value is a plain number, an interval, or an uncertain value.
+///
+/// In reactive mode, the environment also stores the defining expression
+/// of each assigned variable, like cells of a spreadsheet. So when a variable
+/// changes, every variable that depends on it gets recomputed.
+///
+/// An environment can have child scopes, see [Environment::child].
+/// A child reads through to its parent without copying it, and every assignment
+/// binds in the child itself, so the parent never changes:
+///
+///  ╭──────────────────────╮
+///  │ root:  x = 1, y = 2  │ ◀── reads of `y`
+///  ╰──────────────────────╯
+///             ▲
+///  ╭──────────────────────╮
+///  │ child: x = 10        │ ◀── reads and writes of `x`
+///  ╰──────────────────────╯
 #[derive(Clone, Debug, Default)]
-pub struct Environment {
-    variables: HashMap<String, f64>,
+pub struct Environment<'p> {
+    variables: HashMap<String, Value>,
+    reactive: bool,
+    formulas: HashMap<String, Formula>,
+    parent: Option<&'p Environment<'p>>,
 }
 
-impl Environment {
+/// The defining expression of a variable in reactive mode: `y = x * 2`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Formula {
+    /// The whole input, that the variable was assigned by.
+    pub(crate) input: String,
+    /// The tokens of expression, after `=`.
+    pub(crate) sub: Sub,
+    /// The expression text, after `=`.
+    pub(crate) expression: String,
+    /// Variables that the expression reads.
+    pub(crate) dependencies: BTreeSet<String>,
+}
+
+/// The value and formula of a variable, taken to be restored later.
+#[derive(Clone, Debug)]
+pub(crate) struct Snapshot {
+    name: String,
+    value: Option<Value>,
+    formula: Option<Formula>,
+}
+
+impl Formula {
+    /// Returns the expression text, after `=`.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns the variables that the expression reads.
+    pub fn dependencies(&self) -> Vec<&String> {
+        self.dependencies.iter().collect()
+    }
+}
+
+impl<'p> Environment<'p> {
     /// Creates a new empty environment.
     pub fn new() -> Self {
         Self {
             variables: HashMap::new(),
+            reactive: false,
+            formulas: HashMap::new(),
+            parent: None,
+        }
+    }
+
+    /// Creates a child scope of the environment.
+    ///
+    /// The child sees every variable of its parents, and assignments to the child
+    /// always bind in the child itself, shadowing the parent's variable of the same name.
+    /// The parent stays untouched; it's borrowed, not copied, while the child lives.
+    /// A child starts in the reactive mode of its parent, with no formulas of its own.
+    pub fn child(&self) -> Environment<'_> {
+        Environment {
+            variables: HashMap::new(),
+            reactive: self.reactive,
+            formulas: HashMap::new(),
+            parent: Some(self),
+        }
+    }
+
+    /// Returns the parent scope, if this is a child scope.
+    pub fn parent(&self) -> Option<&Environment<'p>> {
+        self.parent
+    }
+
+    /// Returns the nesting level of the scope: zero for a root environment,
+    /// one for its child, and so on.
+    pub fn depth(&self) -> usize {
+        self.parent.map_or(0, |p| p.depth() + 1)
+    }
+
+    /// Lists the bindings of every scope level, from the root to this scope.
+    /// The bindings of each level are sorted by name, and shadowed ones are included.
+    pub fn scopes(&self) -> Vec<Vec<(&String, &Value)>> {
+        let mut scopes = match self.parent {
+            Some(parent) => parent.scopes(),
+            None => Vec::new(),
+        };
+
+        let mut locals: Vec<(&String, &Value)> = self.variables.iter().collect();
+        locals.sort_by(|a, b| a.0.cmp(b.0));
+        scopes.push(locals);
+
+        scopes
+    }
+
+    /// Returns the names of variables, that are bound in this scope itself.
+    pub fn local_names(&self) -> Vec<&String> {
+        self.variables.keys().collect()
+    }
+
+    /// Checks if a variable is bound in this scope itself, not in a parent.
+    pub fn is_local(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+
+    /// Creates a new empty environment in reactive mode.
+    pub fn reactive() -> Self {
+        let mut env = Environment::new();
+        env.reactive = true;
+        env
+    }
+
+    /// Turns reactive mode on or off.
+    /// Turning it off forgets every stored formula, and keeps the current values.
+    pub fn set_reactive(&mut self, reactive: bool) {
+        self.reactive = reactive;
+        if !reactive {
+            self.formulas.clear();
         }
     }
 
-    /// Sets a variable to a value.
+    /// Checks if the environment is in reactive mode.
+    pub fn is_reactive(&self) -> bool {
+        self.reactive
+    }
+
+    /// Sets a variable to a number.
+    /// Like every assignment, it binds in this scope, see [Environment::child].
     pub fn set(&mut self, name: &str, value: f64) {
+        self.set_value(name, Value::Number(value));
+    }
+
+    /// Sets a variable to a number, and recomputes every variable that depends on it.
+    /// Returns the names of recomputed variables, in the order of recomputation.
+    ///
+    /// Any stored formula of the variable is forgotten, since it becomes an input.
+    /// If any dependent can't be recomputed, the environment stays as it was.
+    pub fn update(&mut self, name: &str, value: f64) -> Result<Vec<String>, Error> {
+        let snapshot = self.snapshot(&[name.to_string()]);
+        self.formulas.remove(name);
+        self.set(name, value);
+
+        let result = Calculator::recompute(self, name);
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+
+        result
+    }
+
+    /// Sets a variable to any kind of value.
+    pub fn set_value(&mut self, name: &str, value: Value) {
         self.variables.insert(name.to_string(), value);
     }
 
-    /// Gets a variable's value, if it exists.
+    /// Gets a variable's number, if it exists and holds a plain number.
     pub fn get(&self, name: &str) -> Option<f64> {
-        self.variables.get(name).copied()
+        self.get_value(name).and_then(Value::as_number)
     }
 
-    /// Checks if a variable exists.
+    /// Gets a variable's value, if it exists.
+    pub fn get_value(&self, name: &str) -> Option<&Value> {
+        match self.variables.get(name) {
+            Some(value) => Some(value),
+            None => self.parent.and_then(|p| p.get_value(name)),
+        }
+    }
+
+    /// Checks if a variable exists, in this scope or in any parent.
     pub fn exists(&self, name: &str) -> bool {
-        self.variables.contains_key(name)
+        self.get_value(name).is_some()
     }
 
-    /// Returns all variable names.
+    /// Returns all visible variable names, including the ones of parents.
     pub fn names(&self) -> Vec<&String> {
-        self.variables.keys().collect()
+        let mut names: Vec<&String> = self.variables.keys().collect();
+        if let Some(parent) = self.parent {
+            for name in parent.names() {
+                if !self.variables.contains_key(name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names
     }
 
-    /// Clears all variables.
+    /// Clears all variables of this scope. Parents stay untouched.
     pub fn clear(&mut self) {
         self.variables.clear();
+        self.formulas.clear();
+    }
+
+    /// Gets the defining formula of a variable, if it's stored in reactive mode.
+    pub fn formula(&self, name: &str) -> Option<&Formula> {
+        self.formulas.get(name)
+    }
+
+    /// Returns every variable that depends on the given one, directly or through other
+    /// variables, in the order they have to be recomputed.
+    pub fn dependents(&self, name: &str) -> Vec<String> {
+        // Collect every formula that reaches [name].
+        let mut affected: BTreeSet<String> = BTreeSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (var, formula) in self.formulas.iter() {
+                if affected.contains(var) {
+                    continue;
+                }
+
+                let reaches = formula
+                    .dependencies
+                    .iter()
+                    .any(|d| d == name || affected.contains(d));
+                if reaches {
+                    affected.insert(var.clone());
+                    changed = true;
+                }
+            }
+        }
+
+        // Order them so that every variable comes after its dependencies.
+        let mut ordered: Vec<String> = Vec::new();
+        for var in affected.iter() {
+            self.visit(var, &affected, &mut ordered);
+        }
+
+        ordered
+    }
+
+    /// Post-order walk of the dependency graph, used by [dependents].
+    fn visit(&self, var: &String, affected: &BTreeSet<String>, ordered: &mut Vec<String>) {
+        if ordered.contains(var) {
+            return;
+        }
+
+        if let Some(formula) = self.formulas.get(var) {
+            for dep in formula.dependencies.iter() {
+                if affected.contains(dep) {
+                    self.visit(dep, affected, ordered);
+                }
+            }
+        }
+
+        ordered.push(var.clone());
+    }
+
+    /// Stores the formula of a variable, after checking that it doesn't make a cycle.
+    pub(crate) fn define(&mut self, name: &str, formula: Formula) -> Result<(), Error> {
+        for dep in formula.dependencies.iter() {
+            if let Some(mut path) = self.path(dep, name) {
+                path.insert(0, name.to_string());
+                return Err(Error::circular_dependency(&path));
+            }
+        }
+
+        self.formulas.insert(name.to_string(), formula);
+        Ok(())
+    }
+
+    /// Finds the chain of formulas, that leads from [from] to [to].
+    fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        if from == to {
+            return Some(vec![to.to_string()]);
+        }
+
+        let formula = self.formulas.get(from)?;
+        for dep in formula.dependencies.iter() {
+            if let Some(mut path) = self.path(dep, to) {
+                path.insert(0, from.to_string());
+                return Some(path);
+            }
+        }
+
+        None
+    }
+
+    /// Forgets the stored formula of a variable.
+    pub(crate) fn forget(&mut self, name: &str) {
+        self.formulas.remove(name);
+    }
+
+    /// Takes the values and formulas of the given variables in this scope,
+    /// to put them back by [Environment::restore].
+    pub(crate) fn snapshot(&self, names: &[String]) -> Vec<Snapshot> {
+        names
+            .iter()
+            .map(|name| Snapshot {
+                name: name.clone(),
+                value: self.variables.get(name).cloned(),
+                formula: self.formulas.get(name).cloned(),
+            })
+            .collect()
+    }
+
+    /// Puts back the values and formulas taken by [Environment::snapshot].
+    /// Variables that had no value or formula, lose the ones they've got since.
+    pub(crate) fn restore(&mut self, snapshot: Vec<Snapshot>) {
+        for s in snapshot {
+            match s.value {
+                Some(value) => self.variables.insert(s.name.clone(), value),
+                None => self.variables.remove(&s.name),
+            };
+
+            match s.formula {
+                Some(formula) => self.formulas.insert(s.name, formula),
+                None => self.formulas.remove(&s.name),
+            };
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{interval::Interval, lexer::Lexer};
 
     #[test]
     fn new_environment() {
@@ -73,6 +364,19 @@ mod tests {
         assert_eq!(env.get("x"), Some(10.0));
     }
 
+    #[test]
+    fn set_and_get_value() {
+        let mut env = Environment::new();
+        let interval = Interval::new(1.0, 2.0).unwrap();
+        env.set_value("x", Value::Interval(interval));
+        env.set("y", 5.0);
+
+        assert_eq!(env.get_value("x"), Some(&Value::Interval(interval)));
+        assert_eq!(env.get_value("y"), Some(&Value::Number(5.0)));
+        assert_eq!(env.get("x"), None);
+        assert!(env.exists("x"));
+    }
+
     #[test]
     fn exists() {
         let mut env = Environment::new();
@@ -90,4 +394,115 @@ mod tests {
         assert!(!env.exists("x"));
         assert!(!env.exists("y"));
     }
+
+    #[test]
+    fn child_scopes() {
+        let mut global = Environment::new();
+        global.set("x", 1.0);
+        global.set("y", 2.0);
+
+        let mut child = global.child();
+        assert_eq!(child.depth(), 1);
+        assert_eq!(child.get("y"), Some(2.0));
+        assert!(child.exists("x"));
+        assert!(!child.is_local("x"));
+
+        // Assignments shadow the parent's variables, and create new ones locally.
+        child.set("x", 10.0);
+        child.set("z", 3.0);
+        assert_eq!(child.get("x"), Some(10.0));
+        assert!(child.is_local("x"));
+
+        let mut grandchild = child.child();
+        grandchild.set("y", 20.0);
+        assert_eq!(grandchild.depth(), 2);
+        assert_eq!(grandchild.get("x"), Some(10.0));
+
+        let mut names = grandchild.names();
+        names.sort();
+        assert_eq!(names, vec!["x", "y", "z"]);
+        assert_eq!(grandchild.local_names(), vec!["y"]);
+        assert_eq!(
+            grandchild.scopes(),
+            vec![
+                vec![
+                    (&String::from("x"), &Value::Number(1.0)),
+                    (&String::from("y"), &Value::Number(2.0)),
+                ],
+                vec![
+                    (&String::from("x"), &Value::Number(10.0)),
+                    (&String::from("z"), &Value::Number(3.0)),
+                ],
+                vec![(&String::from("y"), &Value::Number(20.0))],
+            ]
+        );
+
+        grandchild.clear();
+        assert_eq!(grandchild.get("y"), Some(2.0));
+        assert_eq!(grandchild.parent().and_then(|p| p.get("z")), Some(3.0));
+
+        assert_eq!(global.get("x"), Some(1.0));
+        assert_eq!(global.get("z"), None);
+        assert_eq!(global.depth(), 0);
+        assert!(global.parent().is_none());
+    }
+
+    #[test]
+    fn child_assignments() {
+        let mut global = Environment::new();
+        let calculate = |input: &str, env: &mut Environment| {
+            Calculator::calculate_with_env(Lexer::lex(input).unwrap(), input, env).unwrap()
+        };
+
+        calculate("x = 5", &mut global);
+
+        let mut child = global.child();
+        assert_eq!(calculate("x = x * 2", &mut child), 10.0);
+        assert_eq!(calculate("x + 1", &mut child), 11.0);
+        drop(child);
+
+        assert_eq!(calculate("x + 1", &mut global), 6.0);
+    }
+
+    #[test]
+    fn reactive() {
+        let mut env = Environment::reactive();
+        for input in ["x = 2", "y = x * 2", "z = y + x", "w = 10"] {
+            let sub = Lexer::lex(input).unwrap();
+            Calculator::calculate_with_env(sub, input, &mut env).unwrap();
+        }
+
+        assert!(env.is_reactive());
+        assert_eq!(env.formula("x"), None);
+        assert_eq!(env.formula("y").map(Formula::expression), Some("x * 2"));
+        assert_eq!(env.formula("z").unwrap().dependencies(), vec!["x", "y"]);
+
+        assert_eq!(env.dependents("x"), vec!["y", "z"]);
+        assert_eq!(env.dependents("y"), vec!["z"]);
+        assert!(env.dependents("z").is_empty());
+        assert!(env.dependents("w").is_empty());
+
+        assert_eq!(env.update("x", 3.0), Ok(vec![String::from("y"), String::from("z")]));
+        assert_eq!(env.get("y"), Some(6.0));
+        assert_eq!(env.get("z"), Some(9.0));
+
+        // An updated formula variable becomes a plain input.
+        assert_eq!(env.update("y", 1.0), Ok(vec![String::from("z")]));
+        assert_eq!(env.formula("y"), None);
+        assert_eq!(env.get("z"), Some(4.0));
+        assert_eq!(env.dependents("x"), vec!["z"]);
+
+        // A failed recomputation leaves the environment as it was.
+        let sub = Lexer::lex("v = 1 / (z - 3)").unwrap();
+        Calculator::calculate_with_env(sub, "v = 1 / (z - 3)", &mut env).unwrap();
+        assert!(env.update("x", 2.0).is_err());
+        assert_eq!(env.get("x"), Some(3.0));
+        assert_eq!(env.get("z"), Some(4.0));
+        assert_eq!(env.get("v"), Some(1.0));
+
+        env.set_reactive(false);
+        assert!(env.formula("z").is_none());
+        assert!(env.dependents("x").is_empty());
+        assert_eq!(env.get("z"), Some(4.0));
+    }
 }
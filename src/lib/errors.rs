@@ -4,18 +4,81 @@
 // that can be found in the LICENSE file.
 //
 
-use crate::token::Token;
-use std::fmt;
+use crate::{
+    interval::Interval,
+    span::Span,
+    token::{Token, TokenType},
+};
+use std::{fmt, path::Path, time::Duration};
+
+// The kind of error, to tell errors apart without parsing their messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    // Any other kind of invalid input or impossible calculation.
+    Other,
+
+    // Problems of the input syntax.
+    EmptyInput,
+    MissingTokens,
+    InvalidSyntax,
+    IllegalToken,
+    MismatchedBrackets,
+
+    // Problems of the calculation.
+    UndefinedVariable,
+    DivisionByZero,
+    OutOfDomain,
+    InvalidArguments,
+    NotFinite,
+    NotConverged,
+    UnsupportedInMode,
+    CircularDependency,
+
+    // Problems of saved files.
+    InvalidFile,
+    Io,
+
+    // Breaches of [EvalLimits].
+    InputTooLong,
+    TooManyTokens,
+    TooDeep,
+    TooManyOperations,
+    Timeout,
+}
 
 // Main structure model for errors of lexer.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Error {
+    kind: ErrorKind,
     msg: String,
+    // The bytes of input, that the error points at.
+    span: Option<Span>,
 }
 
 impl Error {
     pub fn new(msg: String) -> Self {
-        Self { msg }
+        Self {
+            kind: ErrorKind::Other,
+            msg,
+            span: None,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    // The bytes of input, that the error points at, if the error is
+    // about a certain part of input.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    // Moves the error's span, by the given amount of bytes.
+    // Used when the input was a part of some bigger text, like a line of script.
+    pub fn shifted(mut self, by: usize) -> Self {
+        self.span = self.span.map(|s| s.shifted(by));
+        self
     }
 
     // The error template used to generate cool error messages by input, invalid token, title of
@@ -30,32 +93,48 @@ impl Error {
     //         | > Your detailed error
     //         | > explanation here.
     // ```
-    fn indexed_error(input: String, point: i32, err: String, expl: Vec<&str>) -> Self {
+    //
+    // The explanation points at the first character of [span], which is found by its
+    // column, so it stays in place for characters that take more than one byte.
+    fn indexed_error(
+        kind: ErrorKind,
+        input: String,
+        span: Span,
+        err: String,
+        expl: Vec<&str>,
+    ) -> Self {
         let mut message = err;
 
         let tab = "     ";
-        let space_count = if point > 1 { (point - 1) as usize } else { 0 };
-        let space: String = " ".repeat(space_count);
+        let space: String = " ".repeat(span.start_position(&input).column - 1);
 
         message.push_str(&format!("{tab}\"{}\" \n", input.trim_end()));
         for exp in expl.iter() {
             message.push_str(&format!(" {tab}{space}{exp}\n"));
         }
 
-        Self { msg: message }
+        Self {
+            kind,
+            msg: message,
+            span: Some(span),
+        }
     }
 
     // A custom early made error for empty input cases.
     pub fn empty_input() -> Self {
         Self {
+            kind: ErrorKind::EmptyInput,
             msg: String::from("error: cannot parse an empty input"),
+            span: None,
         }
     }
 
     // A custom early made error for empty tokens cases.
     pub fn empty_tokens() -> Self {
         Self {
+            kind: ErrorKind::EmptyInput,
             msg: String::from("error: cannot calculate result from an empty token list"),
+            span: None,
         }
     }
 
@@ -72,26 +151,25 @@ impl Error {
     //                         | > to the actual number representation.
     // ```
     //
-    pub fn missing_some_tokens(input: String, point: i32) -> Self {
+    //
+    // The [at] is the byte of input, where the missing tokens were expected.
+    pub fn missing_some_tokens(input: String, at: usize) -> Self {
         let message = "error: missing some tokens to calculate result\n\n".to_string();
 
-        let mut inpt: String = input.trim_end().to_string();
-        let pointer = " {X} ";
-
-        for (i, pch) in pointer.chars().enumerate() {
-            let p: i32 = point + (i as i32) + 1;
-
-            let backid: usize = if p < 1 { 0 } else { (p - 1) as usize };
-
-            let back_ch = inpt.chars().nth(backid).unwrap_or('0');
-            let next_ch = inpt.chars().nth((p + 1) as usize).unwrap_or('0');
-
-            if (back_ch == ' ' || next_ch == ' ') && pch == ' ' {
-                continue;
-            }
+        let (index, _) = Span::at(at).chars(&input);
+        let span = Span::from_chars(&input, index, index);
+        let trimmed = input.trim_end();
+        let (before, after) = trimmed.split_at(span.start.min(trimmed.len()));
 
-            inpt.insert(p as usize, pch);
+        // The pointer is separated from its neighbours by a single space.
+        let mut pointer = String::from("{X}");
+        if !before.is_empty() && !before.ends_with(' ') {
+            pointer.insert(0, ' ');
         }
+        if !after.is_empty() && !after.starts_with(' ') {
+            pointer.push(' ');
+        }
+        let inpt = format!("{before}{pointer}{after}");
 
         // A split list of error explanation.
         let explanation: Vec<&str> = Vec::from([
@@ -100,7 +178,11 @@ impl Error {
             "| > hint: `42`, `+`, `-`, `/`, `*`, `%`, `^`.",
         ]);
 
-        Error::indexed_error(inpt, point + 4, message, explanation)
+        let pointed = Span::at(before.len() + pointer.find('{').unwrap_or(0));
+        let mut error =
+            Error::indexed_error(ErrorKind::MissingTokens, inpt, pointed, message, explanation);
+        error.span = Some(Span::at(span.start));
+        error
     }
 
     // A custom [indexed_error] implementation for rust string -> to -> number parsing error.
@@ -127,7 +209,7 @@ impl Error {
             "| > as number) to the actual number representation.",
         ]);
 
-        Error::indexed_error(input, token.index.1 + 1, message, explanation)
+        Error::indexed_error(ErrorKind::InvalidSyntax, input, token.span, message, explanation)
     }
 
     // A custom early made error for invalid order case of token characters.
@@ -138,7 +220,11 @@ impl Error {
         msg.push_str(&format!("{space}A valid token/character order is:"));
         msg.push_str(&format!("{space}[Numerable], [Operation], [Numerable]"));
 
-        Self { msg }
+        Self {
+            kind: ErrorKind::InvalidSyntax,
+            msg,
+            span: None,
+        }
     }
 
     pub fn illegal_token(input: String, token: Token) -> Self {
@@ -156,11 +242,34 @@ impl Error {
             "| >   -> https://github.com/theiskaa/mate/issues/new",
         ]);
 
-        Error::indexed_error(input, token.index.1 + 1, message, explanation)
+        Error::indexed_error(ErrorKind::IllegalToken, input, token.span, message, explanation)
+    }
+
+    // A custom error for variables, that aren't defined.
+    // Points at the name, and tells what it was probably meant to be, if there is
+    // a [suggestion] for it:
+    //
+    // ```
+    // [!] error: undefined variable 'raduis'
+    //
+    //      "raduis * 2"
+    //       |
+    //       | > did you mean `radius`?
+    // ```
+    pub fn undefined_variable(input: String, token: &Token, suggestion: Option<String>) -> Self {
+        let message = format!("error: undefined variable '{}'\n\n", token.literal);
+
+        let hint = suggestion.map(|s| format!("| > did you mean `{s}`?"));
+        let explanation: Vec<&str> = match hint.as_deref() {
+            Some(hint) => Vec::from(["|", hint]),
+            None => Vec::from(["|", "| > This variable isn't defined yet."]),
+        };
+
+        Error::indexed_error(ErrorKind::UndefinedVariable, input, token.span, message, explanation)
     }
 
     // A custom error for division by zero cases.
-    pub fn division_by_zero(input: String, point: i32) -> Self {
+    pub fn division_by_zero(input: String, span: Span) -> Self {
         let message = String::from("error: division by zero\n\n");
 
         let explanation: Vec<&str> = Vec::from([
@@ -169,10 +278,190 @@ impl Error {
             "| > hint: ensure the divisor is not zero.",
         ]);
 
-        Error::indexed_error(input, point, message, explanation)
+        // The error points at the whole divisor.
+        Error::indexed_error(ErrorKind::DivisionByZero, input, span, message, explanation)
+    }
+
+    // A custom error for argument separators that are used outside of an argument list.
+    pub fn unexpected_separator(input: String, token: Token) -> Self {
+        let message = String::from("error: unexpected argument separator `,`\n\n");
+
+        let explanation: Vec<&str> = Vec::from([
+            "|",
+            "| > Commas separate the arguments of functions",
+            "| > like `integrate(x ^ 2, x, 0, 1)`.",
+        ]);
+
+        Error::indexed_error(ErrorKind::InvalidSyntax, input, token.span, message, explanation)
+    }
+
+    // A custom error for function calls with a wrong argument list.
+    pub fn invalid_arguments(function: &str, expl: &str) -> Self {
+        Self {
+            kind: ErrorKind::InvalidArguments,
+            msg: format!("error: invalid arguments for `{function}`: {expl}"),
+            span: None,
+        }
     }
 
-    pub fn mismatched_parentheses(input: String, point: i32) -> Self {
+    // A custom error for math functions and operations, that aren't defined for
+    // their arguments, like the square root of a negative number.
+    pub fn out_of_domain(msg: String) -> Self {
+        Self {
+            kind: ErrorKind::OutOfDomain,
+            msg,
+            span: None,
+        }
+    }
+
+    // A custom error for integrands that evaluate to infinity or NaN.
+    pub fn non_finite_integrand(at: f64) -> Self {
+        Self {
+            kind: ErrorKind::NotFinite,
+            msg: format!("error: integrand is not finite at {at}"),
+            span: None,
+        }
+    }
+
+    // A custom error for integrals that couldn't reach the requested tolerance.
+    pub fn integral_not_converged(evaluations: usize) -> Self {
+        Self {
+            kind: ErrorKind::NotConverged,
+            msg: format!("error: integral did not converge within {evaluations} evaluations"),
+            span: None,
+        }
+    }
+
+    // A custom error for interval literals with a wrong order of bounds, like `[2, 1]`.
+    pub fn invalid_interval(lo: f64, hi: f64) -> Self {
+        Self {
+            kind: ErrorKind::InvalidArguments,
+            msg: format!("error: invalid interval [{lo}, {hi}], lower bound must not be greater than upper bound"),
+            span: None,
+        }
+    }
+
+    // A custom error for division by an interval that contains zero.
+    // Looks like:
+    //
+    // ```
+    // error: division by an interval that contains zero: [-1, 1]
+    //
+    //      "<your input here>"
+    //         |
+    //         | > The divisor could be zero, so the result is unbounded.
+    //         | > hint: split the divisor into negative and positive parts.
+    // ```
+    pub fn interval_division_by_zero(input: String, span: Span, divisor: Interval) -> Self {
+        let message = format!("error: division by an interval that contains zero: {divisor}\n\n");
+
+        let explanation: Vec<&str> = Vec::from([
+            "|",
+            "| > The divisor could be zero, so the result is unbounded.",
+            "| > hint: split the divisor into negative and positive parts.",
+        ]);
+
+        // The error points at the whole divisor.
+        Error::indexed_error(ErrorKind::DivisionByZero, input, span, message, explanation)
+    }
+
+    // A custom error for `±` with a negative or non-finite standard uncertainty.
+    pub fn invalid_uncertainty(sigma: f64) -> Self {
+        Self {
+            kind: ErrorKind::InvalidArguments,
+            msg: format!("error: uncertainty must be a non-negative finite number: {sigma}"),
+            span: None,
+        }
+    }
+
+    // A custom error for operations, which derivative isn't finite at the given point,
+    // like `sqrt(0 ± 0.1)`. First-order propagation can't estimate the uncertainty there.
+    pub fn uncertainty_not_propagated(operation: &str, at: f64) -> Self {
+        Self {
+            kind: ErrorKind::NotFinite,
+            msg: format!("error: uncertainty can't be propagated through `{operation}` at {at}, the derivative is not finite"),
+            span: None,
+        }
+    }
+
+    // A custom error for variables, that hold a value which can't be used by the
+    // current calculation mode. Like an interval, in a plain number calculation.
+    pub fn incompatible_variable(name: &str, kind: &str, mode: &str) -> Self {
+        Self {
+            kind: ErrorKind::UnsupportedInMode,
+            msg: format!("error: variable '{name}' holds {kind}, which can't be used in {mode} calculation"),
+            span: None,
+        }
+    }
+
+    // A custom error for syntax, that isn't supported by the current calculation mode.
+    pub fn unsupported_in_mode(what: &str, mode: &str) -> Self {
+        Self {
+            kind: ErrorKind::UnsupportedInMode,
+            msg: format!("error: {what} can't be used in {mode} calculation"),
+            span: None,
+        }
+    }
+
+    // A custom error for tokens, that are calculated as an operation of two values,
+    // but aren't one. Calculations fail on them, instead of making up a result.
+    pub fn invalid_operation(operation: &TokenType) -> Self {
+        Self {
+            kind: ErrorKind::InvalidSyntax,
+            msg: format!("error: {operation:?} is not an operation of two values"),
+            span: None,
+        }
+    }
+
+    // A custom error for inputs, that are longer than [EvalLimits] allow.
+    pub fn input_too_long(length: usize, max: usize) -> Self {
+        Self {
+            kind: ErrorKind::InputTooLong,
+            msg: format!("error: input is too long: {length} characters, the limit is {max}"),
+            span: None,
+        }
+    }
+
+    // A custom error for inputs, that have more tokens than [EvalLimits] allow.
+    pub fn too_many_tokens(max: usize) -> Self {
+        Self {
+            kind: ErrorKind::TooManyTokens,
+            msg: format!("error: input has too many tokens, the limit is {max}"),
+            span: None,
+        }
+    }
+
+    // A custom error for expressions, that are nested deeper than [EvalLimits] allow.
+    pub fn too_deep(max: usize) -> Self {
+        Self {
+            kind: ErrorKind::TooDeep,
+            msg: format!("error: expression is nested too deeply, the limit is {max} levels"),
+            span: None,
+        }
+    }
+
+    // A custom error for calculations, that need more operations than [EvalLimits] allow.
+    pub fn too_many_operations(max: usize) -> Self {
+        Self {
+            kind: ErrorKind::TooManyOperations,
+            msg: format!("error: calculation needs too many operations, the limit is {max}"),
+            span: None,
+        }
+    }
+
+    // A custom error for calculations, that didn't finish in time.
+    pub fn timed_out(timeout: Duration) -> Self {
+        Self {
+            kind: ErrorKind::Timeout,
+            msg: format!(
+                "error: calculation took longer than {}ms",
+                timeout.as_millis()
+            ),
+            span: None,
+        }
+    }
+
+    pub fn mismatched_parentheses(input: String, span: Span) -> Self {
         let message = String::from("error: mismatched parentheses or brackets\n\n");
 
         let explanation: Vec<&str> = Vec::from([
@@ -182,7 +471,142 @@ impl Error {
             "| > hint: ensure all brackets are properly paired.",
         ]);
 
-        Error::indexed_error(input, point, message, explanation)
+        Error::indexed_error(ErrorKind::MismatchedBrackets, input, span, message, explanation)
+    }
+
+    // A custom error for opening brackets, that aren't closed till the end of input.
+    pub fn unclosed_bracket(input: String, span: Span) -> Self {
+        let message = String::from("error: unclosed bracket\n\n");
+
+        let explanation: Vec<&str> = Vec::from([
+            "|",
+            "| > This bracket is opened, but never closed.",
+            "| > hint: add the closing bracket after its expression.",
+        ]);
+
+        Error::indexed_error(ErrorKind::MismatchedBrackets, input, span, message, explanation)
+    }
+
+    // Wraps an error of a script statement, which starts at [line] and [column] of script.
+    // The column is moved to the start of error's span in [source] of statement,
+    // if the error has one.
+    pub fn in_script(line: usize, column: usize, source: &str, cause: Error) -> Self {
+        let column = column + cause.span.map_or(0, |s| s.start_position(source).column - 1);
+        Self {
+            kind: cause.kind,
+            msg: format!("{line}:{column}: {}", cause.msg),
+            span: cause.span,
+        }
+    }
+
+    // A custom error for files of saved environment, that can't be loaded.
+    pub fn invalid_save_file(line: Option<usize>, expl: &str) -> Self {
+        let expl = expl.trim_start_matches("error: ");
+        let msg = match line {
+            Some(line) => format!("error: invalid save file, at line {line}: {expl}"),
+            None => format!("error: invalid save file: {expl}"),
+        };
+
+        Self {
+            kind: ErrorKind::InvalidFile,
+            msg,
+            span: None,
+        }
+    }
+
+    // A custom error for files, that can't be read or written.
+    pub fn io(path: &Path, cause: &std::io::Error) -> Self {
+        Self {
+            kind: ErrorKind::Io,
+            msg: format!("error: {}: {cause}", path.display()),
+            span: None,
+        }
+    }
+
+    // A custom error for reactive mode, when a formula reads itself through
+    // the chain of other formulas: `a -> b -> a`.
+    pub fn circular_dependency(path: &[String]) -> Self {
+        Self {
+            kind: ErrorKind::CircularDependency,
+            msg: format!("error: circular dependency: {}", path.join(" -> ")),
+            span: None,
+        }
+    }
+
+    // A custom error for reactive mode, when a dependent variable can't be recomputed
+    // with the changed value.
+    pub fn recompute_failed(name: &str, cause: &Error) -> Self {
+        Self {
+            kind: cause.kind(),
+            msg: format!(
+                "error: variable '{name}' couldn't be recomputed: {}",
+                cause.msg.trim_start_matches("error: ")
+            ),
+            span: None,
+        }
+    }
+
+    // A custom error for commands, that don't match their usage.
+    pub fn invalid_command(usage: &str) -> Self {
+        Self {
+            kind: ErrorKind::InvalidArguments,
+            msg: format!("error: invalid command, expected: {usage}"),
+            span: None,
+        }
+    }
+
+    // A custom error for ranges of plots and tables, that are empty or not finite.
+    pub fn invalid_range(from: f64, to: f64) -> Self {
+        Self {
+            kind: ErrorKind::InvalidArguments,
+            msg: format!("error: invalid range from {from} to {to}, start must be less than end"),
+            span: None,
+        }
+    }
+
+    // A custom error for LaTeX input, that's outside of the supported subset.
+    pub fn invalid_latex(expl: &str) -> Self {
+        Self {
+            kind: ErrorKind::InvalidSyntax,
+            msg: format!("error: cannot parse LaTeX: {expl}"),
+            span: None,
+        }
+    }
+
+    // A custom error for names, that can't be used as a variable name.
+    pub fn invalid_name(name: &str) -> Self {
+        Self {
+            kind: ErrorKind::InvalidSyntax,
+            msg: format!("error: `{name}` is not a valid variable name"),
+            span: None,
+        }
+    }
+
+    // A custom error for steps of tables, that aren't positive.
+    pub fn invalid_step(step: f64) -> Self {
+        Self {
+            kind: ErrorKind::InvalidArguments,
+            msg: format!("error: invalid step {step}, it must be a positive number"),
+            span: None,
+        }
+    }
+
+    // A custom error for tables, that would have more rows than allowed.
+    pub fn too_many_rows(rows: f64, max: usize) -> Self {
+        Self {
+            kind: ErrorKind::InvalidArguments,
+            msg: format!("error: table would have {rows} rows, the limit is {max}, use a larger step"),
+            span: None,
+        }
+    }
+
+    // A custom error for expressions, that evaluate to infinity or NaN at a sampled point.
+    pub fn non_finite_result(at: f64) -> Self {
+        Self {
+            kind: ErrorKind::NotFinite,
+            msg: format!("error: result is not finite at {at}"),
+            span: None,
+        }
     }
 }
 
@@ -220,6 +644,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn kind() {
+        let test_data: Vec<(Error, ErrorKind)> = vec![
+            (Error::new(String::from("test message")), ErrorKind::Other),
+            (Error::empty_input(), ErrorKind::EmptyInput),
+            (Error::invalid_order(), ErrorKind::InvalidSyntax),
+            (
+                Error::division_by_zero(String::from("1 / 0"), Span::new(4, 5)),
+                ErrorKind::DivisionByZero,
+            ),
+            (
+                Error::mismatched_parentheses(String::from("(1"), Span::new(0, 1)),
+                ErrorKind::MismatchedBrackets,
+            ),
+            (Error::integral_not_converged(10), ErrorKind::NotConverged),
+            (Error::invalid_operation(&TokenType::FACTORIAL), ErrorKind::InvalidSyntax),
+            (Error::circular_dependency(&[]), ErrorKind::CircularDependency),
+            (
+                Error::recompute_failed("y", &Error::undefined_variable(
+                    String::from("x"),
+                    &Token::from(String::from("x"), Span::new(0, 1)),
+                    None,
+                )),
+                ErrorKind::UndefinedVariable,
+            ),
+            (Error::input_too_long(20, 10), ErrorKind::InputTooLong),
+            (Error::too_many_tokens(10), ErrorKind::TooManyTokens),
+            (Error::too_deep(10), ErrorKind::TooDeep),
+            (Error::too_many_operations(10), ErrorKind::TooManyOperations),
+            (Error::timed_out(Duration::from_millis(10)), ErrorKind::Timeout),
+        ];
+
+        for (error, expected) in test_data {
+            assert_eq!(error.kind(), expected, "Failed for: {error}");
+        }
+    }
+
+    #[test]
+    fn carets() {
+        // The character of input, that is right above the caret of explanation.
+        let caret = |error: &Error| {
+            let lines: Vec<&str> = error.msg.lines().collect();
+            let quoted = lines.iter().find(|l| l.contains('"')).unwrap();
+            let column = lines.iter().find(|l| l.trim() == "|").unwrap().chars().count() - 1;
+            quoted.chars().nth(column)
+        };
+
+        let test_data: Vec<(Error, char, Span)> = vec![
+            (
+                Error::division_by_zero(String::from("2 • 3 / 0"), Span::new(10, 11)),
+                '0',
+                Span::new(10, 11),
+            ),
+            (
+                Error::illegal_token(String::from("é + $"), Token::from(String::from("$"), Span::new(5, 6))),
+                '$',
+                Span::new(5, 6),
+            ),
+            (
+                Error::mismatched_parentheses(String::from("± (2"), Span::new(3, 4)),
+                '(',
+                Span::new(3, 4),
+            ),
+            (
+                Error::undefined_variable(
+                    String::from("2π + sqr(16)"),
+                    &Token::from(String::from("sqr"), Span::new(6, 9)),
+                    Some(String::from("sqrt")),
+                ),
+                's',
+                Span::new(6, 9),
+            ),
+            (Error::missing_some_tokens(String::from("2 • 3 •"), 11), '{', Span::at(11)),
+            (Error::missing_some_tokens(String::from("√ 4"), 3), '{', Span::at(3)),
+        ];
+
+        for (error, expected, span) in test_data {
+            assert_eq!(caret(&error), Some(expected), "Failed for: {error}");
+            assert_eq!(error.span(), Some(span), "Failed for: {error}");
+        }
+    }
+
     #[test]
     fn display() {
         let error: Error = Error::new(String::from("A new message"));
@@ -0,0 +1,433 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::value::Value;
+
+// The amount of significant digits, that results with a fraction are rounded to by default.
+// Floating point artefacts like `0.30000000000000004` live beyond it.
+const DISPLAY_DIGITS: usize = 15;
+
+// How the exponent of a number is shown.
+//
+// - [Auto] writes numbers in plain decimal form, and switches to scientific
+//   notation for very large or very small ones (`1e21` and above, below `1e-7`).
+// - [Fixed] always writes numbers in plain decimal form.
+// - [Scientific] writes numbers with a single digit before the point: `1.5e3`.
+// - [Engineering] writes numbers with an exponent of multiple of three: `15e3`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Notation {
+    #[default]
+    Auto,
+    Fixed,
+    Scientific,
+    Engineering,
+}
+
+// The base that integer values are written in.
+// Values with a fraction are always written in decimal.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Base {
+    #[default]
+    Decimal,
+    Hexadecimal,
+    Binary,
+    Octal,
+}
+
+// Converts results to strings, by the display settings.
+//
+//  ╭─────────────────────────────┬──────────────────────┬─────────────────────╮
+//  │ setting                     │ 1234.5678            │ 0.1 + 0.2           │
+//  ├─────────────────────────────┼──────────────────────┼─────────────────────┤
+//  │ default                     │ 1234.5678            │ 0.3                 │
+//  │ with_precision(2)           │ 1234.57              │ 0.30                │
+//  │ with_significant(3)         │ 1230                 │ 0.300               │
+//  │ Notation::Scientific        │ 1.2345678e3          │ 3e-1                │
+//  │ Notation::Engineering       │ 1.2345678e3          │ 300e-3              │
+//  │ with_grouping(true)         │ 1,234.5678           │ 0.3                 │
+//  │ Base::Hexadecimal (of 255)  │ 0xff                 │ 0.3                 │
+//  ╰─────────────────────────────┴──────────────────────┴─────────────────────╯
+//
+// Fixed decimals and significant figures exclude each other, the last one set wins.
+// Without both, results are rounded to 15 significant digits to trim float artefacts.
+// Integral results have no artefacts to trim, they're written in full.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Formatter {
+    precision: Option<usize>,
+    significant: Option<usize>,
+    notation: Notation,
+    grouping: bool,
+    base: Base,
+}
+
+impl Formatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Sets the amount of digits after the decimal point.
+    pub fn with_precision(mut self, digits: usize) -> Self {
+        self.precision = Some(digits);
+        self.significant = None;
+        self
+    }
+
+    // Sets the amount of significant digits, at least one.
+    pub fn with_significant(mut self, digits: usize) -> Self {
+        self.significant = Some(digits.max(1));
+        self.precision = None;
+        self
+    }
+
+    pub fn with_notation(mut self, notation: Notation) -> Self {
+        self.notation = notation;
+        self
+    }
+
+    // Sets whether the integer part of plain decimal numbers is grouped by thousands: `1,234,567`.
+    pub fn with_grouping(mut self, grouping: bool) -> Self {
+        self.grouping = grouping;
+        self
+    }
+
+    pub fn with_base(mut self, base: Base) -> Self {
+        self.base = base;
+        self
+    }
+
+    pub fn precision(&self) -> Option<usize> {
+        self.precision
+    }
+
+    pub fn significant(&self) -> Option<usize> {
+        self.significant
+    }
+
+    pub fn notation(&self) -> Notation {
+        self.notation
+    }
+
+    pub fn grouping(&self) -> bool {
+        self.grouping
+    }
+
+    pub fn base(&self) -> Base {
+        self.base
+    }
+
+    // Formats each number of the value, keeping the shape of [Value]'s display.
+    pub fn format_value(&self, value: &Value) -> String {
+        match value {
+            Value::Number(n) => self.format(*n),
+            Value::Interval(i) => {
+                format!(
+                    "[{}, {}]",
+                    self.format_bound(i.lo(), false),
+                    self.format_bound(i.hi(), true)
+                )
+            }
+            Value::Uncertain(u) => {
+                format!("{} ± {}", self.format(u.value()), self.format(u.sigma()))
+            }
+        }
+    }
+
+    pub fn format(&self, n: f64) -> String {
+        if !n.is_finite() {
+            return n.to_string();
+        }
+
+        if let Some(integer) = self.integer_in_base(n) {
+            return integer;
+        }
+
+        self.write(self.round(n))
+    }
+
+    // Formats a bound of interval. Trimming float artefacts never makes the interval
+    // narrower, the exact bound is kept if rounding would move it inwards.
+    // Explicitly set digits round the bound as they're.
+    fn format_bound(&self, n: f64, upper: bool) -> String {
+        let rounded = self.round(n);
+        let inwards = if upper { rounded < n } else { rounded > n };
+
+        if inwards && self.precision.is_none() && self.significant.is_none() {
+            return self.write(n);
+        }
+
+        self.format(n)
+    }
+
+    // Writes the (already rounded) number in the notation.
+    fn write(&self, n: f64) -> String {
+        match self.notation {
+            Notation::Fixed => self.fixed(n),
+            Notation::Scientific => self.exponential(n, 1),
+            Notation::Engineering => self.exponential(n, 3),
+            Notation::Auto => {
+                if n != 0.0 && (n.abs() >= 1e21 || n.abs() < 1e-7) {
+                    self.exponential(n, 1)
+                } else {
+                    self.fixed(n)
+                }
+            }
+        }
+    }
+
+    // Writes integer values in the non-decimal base, like `0xff` or `-0b101`.
+    fn integer_in_base(&self, n: f64) -> Option<String> {
+        if self.base == Base::Decimal || n.fract() != 0.0 || n.abs() >= u128::MAX as f64 {
+            return None;
+        }
+
+        let sign = if n < 0.0 { "-" } else { "" };
+        let magnitude = n.abs() as u128;
+        let digits = match self.base {
+            Base::Hexadecimal => format!("0x{magnitude:x}"),
+            Base::Binary => format!("0b{magnitude:b}"),
+            Base::Octal => format!("0o{magnitude:o}"),
+            Base::Decimal => return None,
+        };
+
+        Some(format!("{sign}{digits}"))
+    }
+
+    // Rounds to significant digits, the set ones or the default display digits.
+    // Fixed decimals are rounded while writing instead.
+    fn round(&self, n: f64) -> f64 {
+        match (self.significant, self.precision) {
+            (Some(digits), _) => round_significant(n, digits),
+            (None, None) if n.fract() == 0.0 => n,
+            (None, None) => round_significant(n, DISPLAY_DIGITS),
+            (None, Some(_)) => n,
+        }
+    }
+
+    fn fixed(&self, n: f64) -> String {
+        let fixed = match (self.precision, self.significant) {
+            (Some(digits), _) => format!("{n:.digits$}"),
+            (None, Some(digits)) => {
+                let decimals = (digits as i32 - 1 - exponent(n)).max(0) as usize;
+                format!("{n:.decimals$}")
+            }
+            (None, None) => n.to_string(),
+        };
+
+        if self.grouping {
+            return group(&fixed);
+        }
+
+        fixed
+    }
+
+    // Writes the number as a mantissa and an exponent, that's a multiple of [step].
+    fn exponential(&self, n: f64, step: i32) -> String {
+        let mut exp = exponent(n).div_euclid(step) * step;
+        let mut mantissa = self.mantissa(n / 10f64.powi(exp), exponent(n) - exp);
+
+        // Rounding may carry the mantissa over to the next exponent, like 999.96 to 1000.0.
+        if mantissa
+            .trim_start_matches('-')
+            .parse::<f64>()
+            .unwrap_or(0.0)
+            >= 10f64.powi(step)
+        {
+            exp += step;
+            mantissa = self.mantissa(n / 10f64.powi(exp), exponent(n) - exp);
+        }
+
+        format!("{mantissa}e{exp}")
+    }
+
+    // Writes the mantissa of exponential notation, which has [leading] digits before the point.
+    fn mantissa(&self, m: f64, leading: i32) -> String {
+        match (self.precision, self.significant) {
+            (Some(digits), _) => format!("{m:.digits$}"),
+            (None, Some(digits)) => {
+                let decimals = (digits as i32 - 1 - leading).max(0) as usize;
+                format!("{m:.decimals$}")
+            }
+            (None, None) => round_significant(m, DISPLAY_DIGITS).to_string(),
+        }
+    }
+}
+
+// Rounds the number to the given amount of significant digits.
+fn round_significant(n: f64, digits: usize) -> f64 {
+    if n == 0.0 || !n.is_finite() {
+        return n;
+    }
+
+    let precision = digits.max(1) - 1;
+    format!("{n:.precision$e}").parse().unwrap_or(n)
+}
+
+// The decimal exponent of the number, the power of ten of its first significant digit.
+fn exponent(n: f64) -> i32 {
+    if n == 0.0 || !n.is_finite() {
+        return 0;
+    }
+
+    // Parsed from the scientific form, since log10 isn't exact around powers of ten.
+    let scientific = format!("{n:e}");
+    scientific
+        .split_once('e')
+        .and_then(|(_, exp)| exp.parse().ok())
+        .unwrap_or(0)
+}
+
+// Groups the integer part of a decimal number by thousands.
+fn group(number: &str) -> String {
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", number),
+    };
+
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    match fraction {
+        Some(fraction) => format!("{sign}{grouped}.{fraction}"),
+        None => format!("{sign}{grouped}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interval::Interval, uncertain::Uncertain};
+
+    #[test]
+    fn format() {
+        let test_data: Vec<(Formatter, f64, &str)> = vec![
+            (Formatter::new(), 0.1 + 0.2, "0.3"),
+            (Formatter::new(), 14.0_f64.sqrt(), "3.74165738677394"),
+            (Formatter::new(), 1234.5678, "1234.5678"),
+            (Formatter::new(), 123456789012345678.0, "123456789012345680"),
+            (Formatter::new(), -9007199254740993.0, "-9007199254740992"),
+            (Formatter::new(), 1e21, "1e21"),
+            (Formatter::new(), -2.5e-8, "-2.5e-8"),
+            (Formatter::new(), f64::INFINITY, "inf"),
+            (Formatter::new(), f64::NAN, "NaN"),
+            (Formatter::new().with_precision(2), 1234.5678, "1234.57"),
+            (Formatter::new().with_precision(2), 0.1 + 0.2, "0.30"),
+            (Formatter::new().with_precision(0), 2.5e-3, "0"),
+            (Formatter::new().with_significant(3), 1234.5678, "1230"),
+            (Formatter::new().with_significant(3), 0.1 + 0.2, "0.300"),
+            (Formatter::new().with_significant(2), 0.000123456, "0.00012"),
+            (
+                Formatter::new().with_notation(Notation::Fixed),
+                1e21,
+                "1000000000000000000000",
+            ),
+            (
+                Formatter::new().with_notation(Notation::Scientific),
+                1234.5678,
+                "1.2345678e3",
+            ),
+            (
+                Formatter::new().with_notation(Notation::Scientific),
+                0.1 + 0.2,
+                "3e-1",
+            ),
+            (
+                Formatter::new().with_notation(Notation::Scientific),
+                0.0,
+                "0e0",
+            ),
+            (
+                Formatter::new()
+                    .with_notation(Notation::Scientific)
+                    .with_precision(2),
+                9.999,
+                "1.00e1",
+            ),
+            (
+                Formatter::new().with_notation(Notation::Engineering),
+                15000.0,
+                "15e3",
+            ),
+            (
+                Formatter::new().with_notation(Notation::Engineering),
+                0.1 + 0.2,
+                "300e-3",
+            ),
+            (
+                Formatter::new().with_notation(Notation::Engineering),
+                -0.00047,
+                "-470e-6",
+            ),
+            (
+                Formatter::new()
+                    .with_notation(Notation::Engineering)
+                    .with_significant(3),
+                999_960.0,
+                "1.00e6",
+            ),
+            (
+                Formatter::new().with_grouping(true),
+                1234567.891,
+                "1,234,567.891",
+            ),
+            (Formatter::new().with_grouping(true), -123456.0, "-123,456"),
+            (Formatter::new().with_grouping(true), 999.0, "999"),
+            (Formatter::new().with_base(Base::Hexadecimal), 255.0, "0xff"),
+            (Formatter::new().with_base(Base::Binary), -5.0, "-0b101"),
+            (Formatter::new().with_base(Base::Octal), 8.0, "0o10"),
+            (Formatter::new().with_base(Base::Hexadecimal), 2.5, "2.5"),
+        ];
+
+        for (formatter, n, expected) in test_data {
+            assert_eq!(
+                formatter.format(n),
+                expected,
+                "Failed for: {n} with {formatter:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn format_value() {
+        let formatter = Formatter::new().with_precision(1);
+
+        let test_data: Vec<(Value, &str)> = vec![
+            (Value::Number(2.25), "2.2"),
+            (
+                Value::Interval(Interval::new(1.0, 2.26).unwrap()),
+                "[1.0, 2.3]",
+            ),
+            (
+                Value::Uncertain(Uncertain::new(12.34, 0.2).unwrap()),
+                "12.3 ± 0.2",
+            ),
+        ];
+
+        for (value, expected) in test_data {
+            assert_eq!(
+                formatter.format_value(&value),
+                expected,
+                "Failed for: {value}"
+            );
+        }
+
+        // Bounds of intervals aren't trimmed inwards.
+        let interval = Value::Interval(Interval::new(0.1 + 0.2, 25.532000000000004).unwrap());
+        assert_eq!(
+            Formatter::new().format_value(&interval),
+            "[0.3, 25.532000000000004]"
+        );
+    }
+}
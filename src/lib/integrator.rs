@@ -0,0 +1,353 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{calculator::Calculator, environment::Environment, errors::Error, token::Sub};
+
+// The result of a numeric definite integration.
+// Holds the approximated value of the integral, and the estimated absolute error of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Integral {
+    pub value: f64,
+    pub error: f64,
+}
+
+// The structure used to numerically calculate definite integrals,
+// by the adaptive Simpson's rule.
+//
+//   ╭────────────────────────────────────────────────────╮
+//   │  S(a, b) = (b - a) / 6 * (f(a) + 4f(m) + f(b))     │
+//   ╰────────────────────────────────────────────────────╯
+//
+// Each interval is split in two halves, and if the sum of halves differs from the
+// whole by more than the tolerance allows, each half gets split again.
+// The difference of halves and whole is also used as the error estimate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Integrator {
+    tolerance: f64,
+    max_depth: usize,
+    max_evaluations: usize,
+}
+
+// A single step of adaptive Simpson's rule: the [a, b] interval with its middle point [m],
+// function values at those points, and the Simpson's approximation of the whole interval.
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    a: f64,
+    fa: f64,
+    m: f64,
+    fm: f64,
+    b: f64,
+    fb: f64,
+    whole: f64,
+}
+
+impl Default for Integrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator {
+    // Creates a new integrator with the default absolute tolerance of `1e-10`.
+    pub fn new() -> Self {
+        Self {
+            tolerance: 1e-10,
+            max_depth: 50,
+            max_evaluations: 200_000,
+        }
+    }
+
+    // Sets the absolute tolerance that the estimated error should fit in.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    // Sets the maximum depth of interval splitting.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // Sets the maximum amount of function evaluations, before giving up.
+    pub fn with_max_evaluations(mut self, max_evaluations: usize) -> Self {
+        self.max_evaluations = max_evaluations;
+        self
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    // Integrates the given function [f] from [a] to [b].
+    //
+    // If [a] is greater than [b], the sign of the result is flipped.
+    // Any infinite or NaN value of [f] inside of the interval, results an error.
+    pub fn integrate<F>(&self, mut f: F, a: f64, b: f64) -> Result<Integral, Error>
+    where
+        F: FnMut(f64) -> Result<f64, Error>,
+    {
+        if !a.is_finite() || !b.is_finite() {
+            return Err(Error::invalid_arguments(
+                "integrate",
+                "integration bounds must be finite",
+            ));
+        }
+
+        if !(self.tolerance > 0.0 && self.tolerance.is_finite()) {
+            return Err(Error::invalid_arguments(
+                "integrate",
+                "tolerance must be a positive number",
+            ));
+        }
+
+        if a == b {
+            return Ok(Integral {
+                value: 0.0,
+                error: 0.0,
+            });
+        }
+
+        if a > b {
+            let integral = self.integrate(f, b, a)?;
+            return Ok(Integral {
+                value: -integral.value,
+                error: integral.error,
+            });
+        }
+
+        let mut evaluations: usize = 0;
+        let m = a + (b - a) / 2.0;
+        let fa = self.sample(&mut f, a, &mut evaluations)?;
+        let fm = self.sample(&mut f, m, &mut evaluations)?;
+        let fb = self.sample(&mut f, b, &mut evaluations)?;
+
+        let segment = Segment {
+            a,
+            fa,
+            m,
+            fm,
+            b,
+            fb,
+            whole: (b - a) / 6.0 * (fa + 4.0 * fm + fb),
+        };
+
+        let (value, error) = self.adaptive(
+            &mut f,
+            segment,
+            self.tolerance,
+            self.max_depth,
+            &mut evaluations,
+        )?;
+
+        Ok(Integral { value, error })
+    }
+
+    // Integrates the expression [sub] over the variable [var] from [a] to [b].
+    //
+    // The expression is evaluated by [Calculator], with [var] bound in a child scope of the
+    // [env], so the given environment stays untouched.
+    pub fn integrate_expression(
+        &self,
+        sub: &Sub,
+        var: &str,
+        a: f64,
+        b: f64,
+        input: &str,
+        env: &Environment,
+    ) -> Result<Integral, Error> {
+        let mut scope = env.child();
+
+        self.integrate(
+            |x| {
+                scope.set(var, x);
+                Calculator::calculate_with_env(sub.clone(), input, &mut scope)
+            },
+            a,
+            b,
+        )
+    }
+
+    // The recursive part of adaptive Simpson's rule.
+    // Returns the approximated value and the estimated error of the [segment].
+    fn adaptive<F>(
+        &self,
+        f: &mut F,
+        s: Segment,
+        tolerance: f64,
+        depth: usize,
+        evaluations: &mut usize,
+    ) -> Result<(f64, f64), Error>
+    where
+        F: FnMut(f64) -> Result<f64, Error>,
+    {
+        let lm = s.a + (s.m - s.a) / 2.0;
+        let rm = s.m + (s.b - s.m) / 2.0;
+        let flm = self.sample(f, lm, evaluations)?;
+        let frm = self.sample(f, rm, evaluations)?;
+
+        let left = (s.m - s.a) / 6.0 * (s.fa + 4.0 * flm + s.fm);
+        let right = (s.b - s.m) / 6.0 * (s.fm + 4.0 * frm + s.fb);
+        let delta = left + right - s.whole;
+
+        // Richardson extrapolation of the halves, with the error estimate of it.
+        if delta.abs() <= 15.0 * tolerance {
+            return Ok((left + right + delta / 15.0, delta.abs() / 15.0));
+        }
+
+        // The segment can't be split anymore, but still doesn't fit in the tolerance.
+        if depth == 0 {
+            return Err(Error::integral_not_converged(*evaluations));
+        }
+
+        let l = Segment {
+            a: s.a,
+            fa: s.fa,
+            m: lm,
+            fm: flm,
+            b: s.m,
+            fb: s.fm,
+            whole: left,
+        };
+        let r = Segment {
+            a: s.m,
+            fa: s.fm,
+            m: rm,
+            fm: frm,
+            b: s.b,
+            fb: s.fb,
+            whole: right,
+        };
+
+        let (lv, le) = self.adaptive(f, l, tolerance / 2.0, depth - 1, evaluations)?;
+        let (rv, re) = self.adaptive(f, r, tolerance / 2.0, depth - 1, evaluations)?;
+
+        Ok((lv + rv, le + re))
+    }
+
+    // Evaluates [f] at [x], checking the result to be a finite number.
+    fn sample<F>(&self, f: &mut F, x: f64, evaluations: &mut usize) -> Result<f64, Error>
+    where
+        F: FnMut(f64) -> Result<f64, Error>,
+    {
+        if *evaluations >= self.max_evaluations {
+            return Err(Error::integral_not_converged(*evaluations));
+        }
+        *evaluations += 1;
+
+        let y = f(x)?;
+        if !y.is_finite() {
+            return Err(Error::non_finite_integrand(x));
+        }
+
+        Ok(y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn integrate() {
+        type Case = (fn(f64) -> f64, f64, f64, f64);
+
+        let cases: Vec<Case> = vec![
+            (|x| x * x, 0.0, 1.0, 1.0 / 3.0),
+            (|x| x.sin(), 0.0, PI, 2.0),
+            (|x| x.exp(), 0.0, 1.0, std::f64::consts::E - 1.0),
+            (|x| 1.0 / x, 1.0, 2.0, 2.0_f64.ln()),
+            (|x| x.abs(), -1.0, 1.0, 1.0),
+            (|x| x * x, 1.0, 0.0, -1.0 / 3.0),
+            (|x| x * x, 2.0, 2.0, 0.0),
+        ];
+
+        for (f, a, b, expected) in cases {
+            let result = Integrator::new().integrate(|x| Ok(f(x)), a, b).unwrap();
+            assert!(
+                (result.value - expected).abs() < 1e-9,
+                "Failed for [{a}, {b}], expected: {expected}, got: {}",
+                result.value
+            );
+            assert!(result.error < 1e-9);
+        }
+    }
+
+    #[test]
+    fn tolerance() {
+        let coarse = Integrator::new()
+            .with_tolerance(1e-2)
+            .integrate(|x| Ok(x.sqrt()), 0.0, 1.0)
+            .unwrap();
+        let fine = Integrator::new()
+            .with_tolerance(1e-10)
+            .integrate(|x| Ok(x.sqrt()), 0.0, 1.0)
+            .unwrap();
+
+        assert!((fine.value - 2.0 / 3.0).abs() < 1e-9);
+        assert!(fine.error <= coarse.error);
+
+        let result = Integrator::new()
+            .with_tolerance(0.0)
+            .integrate(Ok, 0.0, 1.0);
+        assert!(result.is_err());
+
+        // A tolerance that the maximum depth can't reach, isn't silently ignored.
+        let unreachable =
+            Integrator::new()
+                .with_tolerance(1e-12)
+                .integrate(|x| Ok(x.sqrt()), 0.0, 1.0);
+        assert!(unreachable.is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let non_finite = Integrator::new().integrate(|x| Ok(1.0 / x), 0.0, 1.0);
+        assert_eq!(non_finite, Err(Error::non_finite_integrand(0.0)));
+
+        let nan = Integrator::new().integrate(|x| Ok((x - 0.5).sqrt()), 0.0, 1.0);
+        assert!(nan.is_err());
+
+        let infinite_bound = Integrator::new().integrate(Ok, 0.0, f64::INFINITY);
+        assert!(infinite_bound.is_err());
+
+        let not_converged = Integrator::new().with_max_evaluations(10).integrate(
+            |x| Ok((1.0 / (x + 1e-3)).sin()),
+            0.0,
+            1.0,
+        );
+        assert_eq!(not_converged, Err(Error::integral_not_converged(10)));
+
+        let too_deep = Integrator::new()
+            .with_max_depth(3)
+            .integrate(|x| Ok(x.sqrt()), 0.0, 1.0);
+        assert_eq!(too_deep, Err(Error::integral_not_converged(11)));
+
+        let shallow = Integrator::new()
+            .with_max_depth(3)
+            .integrate(|x| Ok(x * x), 0.0, 1.0)
+            .unwrap();
+        assert!((shallow.value - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn integrate_expression() {
+        let mut env = Environment::new();
+        env.set("x", 42.0);
+        env.set("k", 3.0);
+
+        let input = "k * x ^ 2";
+        let sub = Lexer::lex(input).unwrap();
+        let result = Integrator::new()
+            .integrate_expression(&sub, "x", 0.0, 1.0, input, &env)
+            .unwrap();
+
+        assert!((result.value - 1.0).abs() < 1e-10);
+        assert_eq!(env.get("x"), Some(42.0));
+    }
+}
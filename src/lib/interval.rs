@@ -0,0 +1,687 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator,
+    errors::Error,
+    span::Span,
+    token::TokenType,
+    value::{Domain, Value},
+};
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    fmt,
+};
+
+// A closed interval of numbers: [lo, hi].
+//
+// Used to evaluate expressions over ranges, like tolerance stack-ups:
+//
+//  ╭──────────────────────────────╮     ╭──────────────────╮
+//  │ [9.8, 9.82] * 2.5 ± 0.1      │ ──▶ │ [23.52, 25.532]  │
+//  ╰──────────────────────────────╯     ╰──────────────────╯
+//
+// Bounds of every operation are rounded outwards, so the exact result
+// is always inside of the resulting interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    // Creates a new interval from its bounds.
+    // Lower bound must not be greater than the upper bound.
+    pub fn new(lo: f64, hi: f64) -> Result<Self, Error> {
+        if lo.is_nan() || hi.is_nan() || lo > hi {
+            return Err(Error::invalid_interval(lo, hi));
+        }
+
+        Ok(Self { lo, hi })
+    }
+
+    // Creates a degenerate interval, that holds exactly one number.
+    pub fn point(x: f64) -> Self {
+        Self { lo: x, hi: x }
+    }
+
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    pub fn midpoint(&self) -> f64 {
+        self.lo + (self.hi - self.lo) / 2.0
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    pub fn is_degenerate(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    pub fn add(&self, other: &Interval) -> Interval {
+        Interval {
+            lo: add_round(self.lo, other.lo).0,
+            hi: add_round(self.hi, other.hi).1,
+        }
+    }
+
+    pub fn sub(&self, other: &Interval) -> Interval {
+        Interval {
+            lo: add_round(self.lo, -other.hi).0,
+            hi: add_round(self.hi, -other.lo).1,
+        }
+    }
+
+    pub fn mul(&self, other: &Interval) -> Interval {
+        let corners = [
+            mul_round(self.lo, other.lo),
+            mul_round(self.lo, other.hi),
+            mul_round(self.hi, other.lo),
+            mul_round(self.hi, other.hi),
+        ];
+
+        Interval::hull(&corners)
+    }
+
+    // Divides the interval by [other].
+    // Dividing by an interval that contains zero results [None].
+    pub fn div(&self, other: &Interval) -> Option<Interval> {
+        if other.contains(0.0) {
+            return None;
+        }
+
+        let corners = [
+            div_round(self.lo, other.lo),
+            div_round(self.lo, other.hi),
+            div_round(self.hi, other.lo),
+            div_round(self.hi, other.hi),
+        ];
+
+        Some(Interval::hull(&corners))
+    }
+
+    pub fn abs(&self) -> Interval {
+        if self.lo >= 0.0 {
+            *self
+        } else if self.hi <= 0.0 {
+            Interval {
+                lo: -self.hi,
+                hi: -self.lo,
+            }
+        } else {
+            Interval {
+                lo: 0.0,
+                hi: f64::max(-self.lo, self.hi),
+            }
+        }
+    }
+
+    // Raises the interval to the [exp] power.
+    //
+    // Integer exponents are calculated by repeated multiplication, so negative bases work too.
+    // Other exponents require a non-negative base.
+    pub fn pow(&self, exp: &Interval) -> Result<Interval, Error> {
+        if exp.is_degenerate() && exp.lo.fract() == 0.0 && exp.lo.abs() <= 1024.0 {
+            let n = exp.lo as i32;
+            let positive = self.powi(n.unsigned_abs());
+            if n >= 0 {
+                return Ok(positive);
+            }
+
+            return Interval::point(1.0).div(&positive).ok_or_else(|| {
+                Error::out_of_domain(format!(
+                    "error: cannot raise {self} to {n}, it contains zero"
+                ))
+            });
+        }
+
+        if self.lo < 0.0 {
+            return Err(Error::out_of_domain(format!(
+                "error: cannot raise {self} to the {exp} power, it contains negative numbers"
+            )));
+        }
+
+        if self.lo == 0.0 && exp.lo <= 0.0 {
+            return Err(Error::out_of_domain(format!(
+                "error: cannot raise {self} to the {exp} power, it contains zero"
+            )));
+        }
+
+        let corners = [
+            widen(self.lo.powf(exp.lo)),
+            widen(self.lo.powf(exp.hi)),
+            widen(self.hi.powf(exp.lo)),
+            widen(self.hi.powf(exp.hi)),
+        ];
+
+        Ok(Interval::hull(&corners).clamp_lo(0.0))
+    }
+
+    // Raises the interval to a non-negative integer power.
+    fn powi(&self, n: u32) -> Interval {
+        if n == 0 {
+            return Interval::point(1.0);
+        }
+
+        if n % 2 == 1 {
+            return Interval {
+                lo: pow_round(self.lo, n).0,
+                hi: pow_round(self.hi, n).1,
+            };
+        }
+
+        let abs = self.abs();
+        Interval {
+            lo: pow_round(abs.lo, n).0,
+            hi: pow_round(abs.hi, n).1,
+        }
+    }
+
+    pub fn sqrt(&self) -> Option<Interval> {
+        if self.lo < 0.0 {
+            return None;
+        }
+
+        Some(Interval {
+            lo: sqrt_round(self.lo).0,
+            hi: sqrt_round(self.hi).1,
+        })
+    }
+
+    // Calculates the range of sine over the interval.
+    pub fn sin(&self) -> Interval {
+        self.periodic(f64::sin, FRAC_PI_2)
+    }
+
+    // Calculates the range of cosine over the interval.
+    pub fn cos(&self) -> Interval {
+        self.periodic(f64::cos, 0.0)
+    }
+
+    // Calculates the range of tangent over the interval.
+    // An interval that contains a pole of tangent results [None].
+    pub fn tan(&self) -> Option<Interval> {
+        let pole = |x: f64| ((x - FRAC_PI_2) / PI).ceil();
+        if self.width() >= PI || pole(self.lo) != pole(self.hi) {
+            return None;
+        }
+
+        Some(self.monotonic(f64::tan))
+    }
+
+    // Calculates the range of a sine-like function [f], that has its maximum at [peak]
+    // and minimum at [peak + PI], repeating in every `2 * PI`.
+    fn periodic(&self, f: fn(f64) -> f64, peak: f64) -> Interval {
+        if !self.lo.is_finite() || !self.hi.is_finite() || self.width() >= 2.0 * PI {
+            return Interval { lo: -1.0, hi: 1.0 };
+        }
+
+        let (a, b) = (f(self.lo), f(self.hi));
+        let mut lo = widen(a.min(b)).0;
+        let mut hi = widen(a.max(b)).1;
+
+        // Walk through extremes of [f] that are inside of the interval.
+        let mut k = ((self.lo - peak) / PI).ceil();
+        while peak + k * PI <= self.hi {
+            if k % 2.0 == 0.0 {
+                hi = 1.0;
+            } else {
+                lo = -1.0;
+            }
+            k += 1.0;
+        }
+
+        Interval {
+            lo: lo.max(-1.0),
+            hi: hi.min(1.0),
+        }
+    }
+
+    // Applies an increasing function to the bounds of interval.
+    // Results of [f] are widened by one unit in the last place, since math functions
+    // of the standard library aren't correctly rounded.
+    fn monotonic(&self, f: fn(f64) -> f64) -> Interval {
+        Interval {
+            lo: widen(f(self.lo)).0,
+            hi: widen(f(self.hi)).1,
+        }
+    }
+
+    // Returns the smallest interval that holds all of the given (down, up) rounded bounds.
+    fn hull(corners: &[(f64, f64)]) -> Interval {
+        let lo = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+        let hi = corners
+            .iter()
+            .map(|c| c.1)
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        Interval { lo, hi }
+    }
+
+    fn clamp_lo(self, min: f64) -> Interval {
+        Interval {
+            lo: self.lo.max(min),
+            hi: self.hi.max(min),
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+impl Domain for Interval {
+    fn from_number(n: f64) -> Self {
+        Interval::point(n)
+    }
+
+    fn from_value(name: &str, value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Number(n) => Ok(Interval::point(*n)),
+            Value::Interval(i) => Ok(*i),
+            _ => Err(Error::incompatible_variable(name, value.kind(), "interval")),
+        }
+    }
+
+    fn into_value(self) -> Value {
+        Value::Interval(self)
+    }
+
+    fn to_number(&self) -> Option<f64> {
+        if self.is_degenerate() {
+            return Some(self.lo);
+        }
+
+        None
+    }
+
+    fn operation(
+        x: Self,
+        y: Self,
+        operation: TokenType,
+        input: &str,
+        span: Span,
+    ) -> Result<Self, Error> {
+        let result = match operation {
+            TokenType::PLUS => x.add(&y),
+            TokenType::MINUS => x.sub(&y),
+            TokenType::PRODUCT => x.mul(&y),
+            TokenType::DIVIDE => x
+                .div(&y)
+                .ok_or_else(|| Error::interval_division_by_zero(input.to_string(), span, y))?,
+            TokenType::PERCENTAGE => {
+                let hundred = Interval::point(100.0);
+                let part = x.div(&hundred).ok_or_else(|| {
+                    Error::interval_division_by_zero(input.to_string(), span, hundred)
+                })?;
+
+                part.mul(&y)
+            }
+            TokenType::POWER => x.pow(&y)?,
+            TokenType::PLUSMINUS => {
+                if y.lo < 0.0 {
+                    return Err(Error::invalid_arguments(
+                        "±",
+                        &format!("the half-width must not be negative: {y}"),
+                    ));
+                }
+
+                Interval {
+                    lo: add_round(x.lo, -y.hi).0,
+                    hi: add_round(x.hi, y.hi).1,
+                }
+            }
+            _ => return Err(Error::invalid_operation(&operation)),
+        };
+
+        Ok(result)
+    }
+
+    fn function(func: TokenType, arg: Self, input: &str, span: Span) -> Result<Self, Error> {
+        let result = match func {
+            TokenType::SQRT => arg.sqrt().ok_or_else(|| {
+                Error::out_of_domain(format!(
+                    "error: cannot take square root of an interval with negative numbers: {arg}"
+                ))
+            })?,
+            TokenType::SIN => arg.sin(),
+            TokenType::COS => arg.cos(),
+            TokenType::TAN => arg.tan().ok_or_else(|| {
+                Error::out_of_domain(format!("error: tangent is undefined inside of {arg}"))
+            })?,
+            TokenType::LOG | TokenType::LN => {
+                if arg.lo <= 0.0 {
+                    return Err(Error::out_of_domain(format!(
+                        "error: logarithm undefined for an interval with non-positive numbers: {arg}"
+                    )));
+                }
+
+                match func {
+                    TokenType::LOG => arg.monotonic(f64::log10),
+                    _ => arg.monotonic(f64::ln),
+                }
+            }
+            TokenType::CBRT => arg.monotonic(f64::cbrt),
+            TokenType::EXP => arg.monotonic(f64::exp).clamp_lo(0.0),
+            TokenType::FLOOR => Interval {
+                lo: arg.lo.floor(),
+                hi: arg.hi.floor(),
+            },
+            TokenType::CEIL => Interval {
+                lo: arg.lo.ceil(),
+                hi: arg.hi.ceil(),
+            },
+            TokenType::ROUND => Interval {
+                lo: arg.lo.round(),
+                hi: arg.hi.round(),
+            },
+            _ => {
+                return Err(Error::missing_some_tokens(input.to_string(), span.end));
+            }
+        };
+
+        Ok(result)
+    }
+
+    fn factorial(self) -> Result<Self, Error> {
+        match self.to_number() {
+            Some(n) => Ok(Interval::point(Calculator::compute_factorial(n)?)),
+            None => Err(Error::out_of_domain(format!(
+                "error: factorial is only defined for exact integers, not for {self}"
+            ))),
+        }
+    }
+
+    fn abs(self) -> Self {
+        Interval::abs(&self)
+    }
+
+    fn interval(lo: Self, hi: Self) -> Result<Self, Error> {
+        Interval::new(lo.lo, hi.hi)
+    }
+}
+
+// Widens a result of an inexact operation by one unit in the last place, on both sides.
+fn widen(x: f64) -> (f64, f64) {
+    if !x.is_finite() {
+        return (x, x);
+    }
+
+    (x.next_down(), x.next_up())
+}
+
+// Returns the (down, up) rounded bounds of an exact result,
+// from its rounded value [r] and the exact error [err] of it, so `exact = r + err`.
+fn directed(r: f64, err: f64) -> (f64, f64) {
+    let down = if err < 0.0 { r.next_down() } else { r };
+    let up = if err > 0.0 { r.next_up() } else { r };
+
+    (down, up)
+}
+
+// Returns the (down, up) rounded bounds of a finite result, that has overflowed to [r].
+fn overflowed(r: f64) -> (f64, f64) {
+    if r > 0.0 {
+        (f64::MAX, f64::INFINITY)
+    } else {
+        (f64::NEG_INFINITY, f64::MIN)
+    }
+}
+
+// Sum of [a] and [b], rounded down and up.
+// The exact error of sum is calculated by the error-free `TwoSum` transformation.
+fn add_round(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    if !a.is_finite() || !b.is_finite() {
+        return (s, s);
+    }
+    if !s.is_finite() {
+        return overflowed(s);
+    }
+
+    let bb = s - a;
+    let err = (a - (s - bb)) + (b - bb);
+
+    directed(s, err)
+}
+
+// Product of [a] and [b], rounded down and up.
+// The exact error of product is calculated by the fused multiply-add.
+fn mul_round(a: f64, b: f64) -> (f64, f64) {
+    // Zero times anything (including infinity) is zero, in interval arithmetic.
+    if a == 0.0 || b == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let p = a * b;
+    if !a.is_finite() || !b.is_finite() {
+        return (p, p);
+    }
+    if !p.is_finite() {
+        return overflowed(p);
+    }
+
+    directed(p, a.mul_add(b, -p))
+}
+
+// Quotient of [a] and [b], rounded down and up.
+// The exact remainder of division is calculated by the fused multiply-add.
+fn div_round(a: f64, b: f64) -> (f64, f64) {
+    let q = a / b;
+    if !a.is_finite() || !b.is_finite() {
+        return (q, q);
+    }
+    if !q.is_finite() {
+        return overflowed(q);
+    }
+
+    let r = (-q).mul_add(b, a);
+    directed(q, r * b.signum())
+}
+
+// Square root of [a], rounded down and up.
+fn sqrt_round(a: f64) -> (f64, f64) {
+    let s = a.sqrt();
+    if !a.is_finite() {
+        return (s, s);
+    }
+
+    directed(s, (-s).mul_add(s, a))
+}
+
+// Integer power of [a], rounded down and up, by repeated multiplication.
+fn pow_round(a: f64, n: u32) -> (f64, f64) {
+    let (mut lo, mut hi) = (1.0, 1.0);
+    let base = a.abs();
+    for _ in 0..n {
+        lo = mul_round(lo, base).0;
+        hi = mul_round(hi, base).1;
+    }
+
+    if a < 0.0 && n % 2 == 1 {
+        return (-hi, -lo);
+    }
+
+    (lo, hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(lo: f64, hi: f64) -> Interval {
+        Interval::new(lo, hi).unwrap()
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(Interval::new(1.0, 2.0), Ok(Interval { lo: 1.0, hi: 2.0 }));
+        assert_eq!(Interval::new(3.0, 3.0), Ok(Interval::point(3.0)));
+        assert!(Interval::new(2.0, 1.0).is_err());
+        assert!(Interval::new(f64::NAN, 1.0).is_err());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = interval(1.0, 2.0);
+        let b = interval(3.0, 4.0);
+
+        assert_eq!(a.add(&b), interval(4.0, 6.0));
+        assert_eq!(a.sub(&b), interval(-3.0, -1.0));
+        assert_eq!(a.mul(&b), interval(3.0, 8.0));
+        assert_eq!(interval(-1.0, 2.0).mul(&b), interval(-4.0, 8.0));
+        assert_eq!(b.div(&interval(1.0, 2.0)), Some(interval(1.5, 4.0)));
+        assert_eq!(b.div(&interval(-1.0, 1.0)), None);
+        assert_eq!(b.div(&interval(0.0, 1.0)), None);
+    }
+
+    #[test]
+    fn outward_rounding() {
+        let tenth = Interval::point(0.1);
+        let sum = tenth.add(&Interval::point(0.2));
+        assert!(sum.contains(0.30000000000000004));
+        assert!(sum.lo() < sum.hi());
+
+        let third = Interval::point(1.0).div(&Interval::point(3.0)).unwrap();
+        assert!(third.lo() < third.hi());
+        assert!(third.lo() <= 1.0 / 3.0 && 1.0 / 3.0 <= third.hi());
+
+        // Exact operations stay exact.
+        assert_eq!(
+            Interval::point(1.5).mul(&Interval::point(2.0)),
+            Interval::point(3.0)
+        );
+        assert_eq!(Interval::point(16.0).sqrt(), Some(Interval::point(4.0)));
+
+        let root = Interval::point(2.0).sqrt().unwrap();
+        assert!(root.mul(&root).contains(2.0));
+    }
+
+    #[test]
+    fn pow() {
+        let cases: Vec<(Interval, Interval, Interval)> = vec![
+            (
+                interval(-2.0, 3.0),
+                Interval::point(2.0),
+                interval(0.0, 9.0),
+            ),
+            (
+                interval(-2.0, 3.0),
+                Interval::point(3.0),
+                interval(-8.0, 27.0),
+            ),
+            (
+                interval(-3.0, -2.0),
+                Interval::point(2.0),
+                interval(4.0, 9.0),
+            ),
+            (
+                interval(2.0, 4.0),
+                Interval::point(-1.0),
+                interval(0.25, 0.5),
+            ),
+            (
+                interval(1.0, 2.0),
+                Interval::point(0.0),
+                Interval::point(1.0),
+            ),
+        ];
+
+        for (base, exp, expected) in cases {
+            assert_eq!(base.pow(&exp), Ok(expected), "{base} ^ {exp}");
+        }
+
+        let root = interval(4.0, 9.0).pow(&Interval::point(0.5)).unwrap();
+        assert!(root.contains(2.0) && root.contains(3.0));
+        assert!(root.lo() > 1.99 && root.hi() < 3.01);
+
+        assert!(interval(-1.0, 4.0).pow(&Interval::point(0.5)).is_err());
+        assert!(interval(-1.0, 1.0).pow(&Interval::point(-2.0)).is_err());
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(interval(-3.0, -1.0).abs(), interval(1.0, 3.0));
+        assert_eq!(interval(-3.0, 1.0).abs(), interval(0.0, 3.0));
+        assert_eq!(interval(-1.0, 4.0).sqrt(), None);
+
+        let sin = interval(0.0, PI).sin();
+        assert_eq!(sin.hi(), 1.0);
+        assert!(sin.lo() <= 0.0 && sin.lo() > -1e-15);
+
+        let cos = interval(-1.0, 4.0).cos();
+        assert_eq!(cos.hi(), 1.0);
+        assert_eq!(cos.lo(), -1.0);
+
+        let wide = interval(0.0, 10.0).sin();
+        assert_eq!(wide, interval(-1.0, 1.0));
+
+        assert!(interval(1.0, 2.0).tan().is_none());
+        let tan = interval(0.0, 1.0).tan().unwrap();
+        assert!(tan.contains(0.0) && tan.contains(1.0_f64.tan()));
+
+        let ln =
+            Interval::function(TokenType::LN, interval(1.0, 2.0), "", Span::default()).unwrap();
+        assert!(ln.contains(0.0) && ln.contains(2.0_f64.ln()));
+        assert!(
+            Interval::function(TokenType::LN, interval(0.0, 2.0), "", Span::default()).is_err()
+        );
+
+        let floor = Interval::function(TokenType::FLOOR, interval(1.5, 3.5), "", Span::default());
+        assert_eq!(floor, Ok(interval(1.0, 3.0)));
+    }
+
+    #[test]
+    fn plus_minus() {
+        let result = Interval::operation(
+            Interval::point(2.5),
+            Interval::point(0.5),
+            TokenType::PLUSMINUS,
+            "",
+            Span::default(),
+        );
+        assert_eq!(result, Ok(interval(2.0, 3.0)));
+
+        let negative = Interval::operation(
+            Interval::point(2.5),
+            Interval::point(-0.5),
+            TokenType::PLUSMINUS,
+            "",
+            Span::default(),
+        );
+        assert!(negative.is_err());
+    }
+
+    #[test]
+    fn invalid_operation() {
+        let result = Interval::operation(
+            Interval::point(2.0),
+            Interval::point(3.0),
+            TokenType::FACTORIAL,
+            "",
+            Span::default(),
+        );
+        assert_eq!(result, Err(Error::invalid_operation(&TokenType::FACTORIAL)));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(interval(-1.5, 2.0).to_string(), "[-1.5, 2]");
+    }
+}
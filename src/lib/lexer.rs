@@ -6,17 +6,28 @@
 
 use crate::{
     errors::Error,
-    token::{Sub, SubMethod, Token},
-    utils::ChUtils,
+    limits::{Budget, EvalLimits},
+    span::Span,
+    token::{Sub, SubMethod, Token, TokenType},
+    utils::{is_space, ChUtils},
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
 };
-use std::{cell::Cell, collections::HashMap};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Lexer<'a> {
     input: &'a str,               // Expression input.
+    size: usize,                  // Amount of chars in input.
     examination_char: Cell<char>, // Current char under examination.
     position: Cell<usize>,        // Current position in input (points to current char).
     read_position: Cell<usize>,   // Current reading position in input (after current char).
+    arguments: RefCell<Vec<bool>>, // Open brackets, marked if they hold an argument list.
+    expects_arguments: Cell<bool>, // Whether the last token was a multi-argument function.
+    bars: Cell<usize>,             // Amount of open absolute value bars.
+    after_value: Cell<bool>,       // Whether the last token closes a value.
+    pending: RefCell<Option<Token>>, // Token that is read, but not yet generated.
 }
 
 impl<'a> Lexer<'a> {
@@ -29,9 +40,15 @@ impl<'a> Lexer<'a> {
 
         Ok(Self {
             input,
+            size: input.chars().count(),
             examination_char: Cell::new(first_char),
             position: Cell::from(0),
             read_position: Cell::from(1),
+            arguments: RefCell::new(Vec::new()),
+            expects_arguments: Cell::new(false),
+            bars: Cell::new(0),
+            after_value: Cell::new(false),
+            pending: RefCell::new(None),
         })
     }
 
@@ -78,15 +95,52 @@ impl<'a> Lexer<'a> {
     //  ╰───────────────────────────────────╯
     //
     pub fn lex(input: &'a str) -> Result<Sub, Error> {
+        Lexer::lex_with_limits(input, &EvalLimits::unlimited())
+    }
+
+    // Same as [lex], but fails with a dedicated error, if the input breaks any of
+    // input length, token count, nesting depth or timeout [limits].
+    //
+    // Chains of powers are nested too, like `2 ^ (2 ^ (2 ^ 2))`, so each power of
+    // a chain counts as one more level of depth.
+    pub fn lex_with_limits(input: &'a str, limits: &EvalLimits) -> Result<Sub, Error> {
+        limits.check_input_length(input)?;
         let lexer: Lexer = Lexer::new(input)?;
+        let budget = Budget::new(*limits);
 
         let mut tokens: Vec<Token> = Vec::new();
+        let mut depth: usize = 0;
+        // Powers and prefix functions in a row, like `2^2^2` or `√√16`, on each nesting level.
+        let mut chains: Vec<usize> = vec![0];
         loop {
             match lexer.generate_token() {
                 None => break,
                 Some(r) => match r {
                     Err(e) => return Err(e),
-                    Ok(r) => tokens.push(r),
+                    Ok(r) => {
+                        budget.check_deadline()?;
+
+                        if r.is_lparen() || r.is_labs() {
+                            depth += 1;
+                            chains.push(0);
+                            limits.check_depth(depth)?;
+                        } else if r.is_rparen() || r.is_rabs() {
+                            depth = depth.saturating_sub(1);
+                            if chains.len() > 1 {
+                                chains.pop();
+                            }
+                        } else if let Some(chain) = chains.last_mut() {
+                            if r.is_power() || r.is_function() {
+                                *chain += 1;
+                                limits.check_depth(depth + *chain)?;
+                            } else if !Lexer::is_value(&r) && !r.is_function() {
+                                *chain = 0;
+                            }
+                        }
+
+                        tokens.push(r);
+                        limits.check_tokens(tokens.len())?;
+                    }
                 },
             }
         }
@@ -100,6 +154,77 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    // Same as [lex], but doesn't stop at the first problem. Illegal characters are kept
+    // as [ILLEGAL] error tokens, unmatched closing brackets are turned into them, and
+    // unclosed brackets are closed at the end of input. Every problem is returned as an
+    // error, in the order of input, next to the recovered tokens.
+    //
+    //  ╭─────────────╮     ╭───────────────────╮
+    //  │ (2 $ 3] + x │ ──▶ │ (2 {$} 3 {]} + x) │ + illegal `$`, mismatched `]`, unclosed `(`
+    //  ╰─────────────╯     ╰───────────────────╯
+    pub fn lex_recovering(input: &'a str) -> (Sub, Vec<Error>) {
+        let mut errors: Vec<Error> = Vec::new();
+        if input.is_empty() {
+            return (Sub::empty(), vec![Error::empty_input()]);
+        }
+
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut open: Vec<Token> = Vec::new();
+        for t in Lexer::tokenize(input) {
+            if t.is_illegal() {
+                errors.push(Error::illegal_token(input.to_string(), t.clone()));
+            } else if t.is_lparen() || t.is_labs() {
+                open.push(t.clone());
+            } else if t.is_rparen() || t.is_rabs() {
+                if !open.last().is_some_and(|o| o.matchto(&t)) {
+                    errors.push(Error::mismatched_parentheses(input.to_string(), t.span));
+                    tokens.push(Token::new(TokenType::ILLEGAL, t.literal, Sub::empty(), t.span));
+                    continue;
+                }
+                open.pop();
+            }
+
+            tokens.push(t);
+        }
+
+        for t in open.iter().rev() {
+            errors.push(Error::unclosed_bracket(input.to_string(), t.span));
+            let typ = if t.is_lparen() { TokenType::RPAREN } else { TokenType::RABS };
+            tokens.push(Token::new(typ, String::new(), Sub::empty(), Span::at(input.len())));
+        }
+        errors.sort_by_key(|e| e.span().map(|s| s.start));
+
+        let sub = Lexer::nest_parentheses(tokens, input)
+            .and_then(|v| Lexer::break_nesting(0, v, input))
+            .map(Lexer::combine_tokens);
+        match sub {
+            Ok(sub) => (sub, errors),
+            Err(e) => {
+                errors.push(e);
+                (Sub::empty(), errors)
+            }
+        }
+    }
+
+    // Generates the flat list of tokens, in the order of input, without nesting them
+    // into sub-expressions. Never fails: unknown characters come as [ILLEGAL] tokens,
+    // and brackets are kept as they're, even if they don't match.
+    //
+    // Used to look at the input as it's, e.g. to highlight it while it's being typed.
+    pub fn tokenize(input: &'a str) -> Vec<Token> {
+        let lexer: Lexer = match Lexer::new(input) {
+            Ok(lexer) => lexer,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut tokens: Vec<Token> = Vec::new();
+        while let Some(Ok(t)) = lexer.generate_token() {
+            tokens.push(t);
+        }
+
+        tokens
+    }
+
     // The nesting-to-tokens algorithm implementation.
     //
     // Nesting-to-tokens algorithm is a hashing algorithm that lexer uses to
@@ -124,31 +249,25 @@ impl<'a> Lexer<'a> {
 
         let mut i: usize = 0;
         let mut startert: Token = Token::empty();
-        while i < tokens.clone().len() {
+        while i < tokens.len() {
             let t: Token = tokens[i].clone();
 
             if t.is_lparen() || t.is_labs() {
                 startert = t.clone();
 
-                let mut base: (Vec<Token>, bool) = match nested.get(&0) {
-                    None => (vec![], false),
-                    Some(v) => v.clone(),
-                };
-
                 level += 1;
 
-                base.0
-                    .push(Token::new_pointer(level, startert.to_submethod()));
-                nested.insert(0, base.clone());
-
-                match Lexer::take_till_end(tokens.clone(), i) {
+                match Lexer::take_till_end(&tokens, i) {
                     None => {
-                        return Err(Error::mismatched_parentheses(
-                            input.to_string(),
-                            t.index.1 + 1,
-                        ))
+                        return Err(Error::mismatched_parentheses(input.to_string(), t.span))
                     }
                     Some(v) => {
+                        // The pointer spans from the opening bracket to the closing one.
+                        let span = tokens.get(v.1).map_or(t.span, |end| t.span.join(end.span));
+                        let base = nested.entry(0).or_insert_with(|| (vec![], false));
+                        base.0
+                            .push(Token::new_pointer(level, startert.to_submethod(), span));
+
                         nested.insert(level, (v.0, v.2));
                         i = v.1;
                     }
@@ -161,19 +280,11 @@ impl<'a> Lexer<'a> {
                     i += 1;
                     continue;
                 }
-                return Err(Error::mismatched_parentheses(
-                    input.to_string(),
-                    t.index.1 + 1,
-                ));
+                return Err(Error::mismatched_parentheses(input.to_string(), t.span));
             }
 
-            let mut base: (Vec<Token>, bool) = match nested.get(&0) {
-                None => (vec![], false),
-                Some(v) => v.clone(),
-            };
-
+            let base = nested.entry(0).or_insert_with(|| (vec![], false));
             base.0.push(t);
-            nested.insert(0, base.clone());
             i += 1;
         }
 
@@ -183,7 +294,7 @@ impl<'a> Lexer<'a> {
     // Collects all tokens from exact one parentheses-expression-clip.
     //
     // If [start] doesn't equals to any kind of opening(left) parentheses, result gonna be [None].
-    fn take_till_end(tokens: Vec<Token>, start: usize) -> Option<(Vec<Token>, usize, bool)> {
+    fn take_till_end(tokens: &[Token], start: usize) -> Option<(Vec<Token>, usize, bool)> {
         let mut iteration_count = start;
         let mut has_to_recall: bool = false;
 
@@ -219,15 +330,11 @@ impl<'a> Lexer<'a> {
 
             if t.is_rparen() || t.is_rabs() {
                 match matcho_collection.last() {
-                    None => return None,
-                    Some(last) => {
-                        if last.matchto(&t) {
-                            level -= 1;
-                            matcho_collection.pop();
-                        } else {
-                            return None;
-                        }
+                    Some(last) if last.matchto(&t) => {
+                        level -= 1;
+                        matcho_collection.pop();
                     }
+                    _ => return None,
                 }
 
                 if level == 0 {
@@ -271,7 +378,7 @@ impl<'a> Lexer<'a> {
                         Some(v) => {
                             if !v.1 {
                                 let combined: Sub = Lexer::combine_tokens(v.0.clone());
-                                result.push(Token::new_sub(combined.tokens, t.clone().sub.method));
+                                result.push(Lexer::bracketed(combined, t));
                                 continue;
                             }
 
@@ -281,10 +388,7 @@ impl<'a> Lexer<'a> {
                                     Err(e) => return Err(e),
                                     Ok(v) => {
                                         let combined: Sub = Lexer::combine_tokens(v);
-                                        result.push(Token::new_sub(
-                                            combined.tokens,
-                                            t.clone().sub.method,
-                                        ));
+                                        result.push(Lexer::bracketed(combined, t));
                                     }
                                 },
                             }
@@ -297,6 +401,14 @@ impl<'a> Lexer<'a> {
         Ok(result)
     }
 
+    // Creates the sub token of brackets, that the [pointer] points at.
+    // The span of sub token includes its brackets, so empty brackets have a span too.
+    fn bracketed(combined: Sub, pointer: &Token) -> Token {
+        let mut token = Token::new_sub(combined.tokens, pointer.sub.method.clone());
+        token.span = pointer.span;
+        token
+    }
+
     // Takes first-party tokens, combines them and returns
     // 1D nested tokens.
     //
@@ -318,15 +430,38 @@ impl<'a> Lexer<'a> {
             let current = &tokens[i];
 
             // Handle prefix functions: sqrt(16), sin(x), etc.
+            // Functions in a row apply to each other, like `√√16` as `√(√16)`.
             if current.is_function() && i + 1 < tokens.len() {
-                let arg = &tokens[i + 1];
-                // Combine function and its argument into a sub-expression
-                let func_call = Token::new_sub(
-                    vec![current.clone(), arg.clone()],
+                let mut end = i + 1;
+                while end + 1 < tokens.len()
+                    && tokens[end].is_function()
+                    && !tokens[end].takes_arguments()
+                {
+                    end += 1;
+                }
+
+                // Combine each function and its argument into a sub-expression, innermost first
+                let mut func_call = tokens[end].clone();
+                for func in tokens[i..end].iter().rev() {
+                    func_call = Token::new_sub(vec![func.clone(), func_call], SubMethod::PAREN);
+                }
+                result.push(func_call);
+                i = end + 1; // Skip the functions and argument
+            }
+            // Handle plus-minus: 2.5 ± 0.1, x ± 0.2
+            // It binds tighter than any other operation, since it describes a single value.
+            else if current.is_plusminus()
+                && i + 1 < tokens.len()
+                && result.last().is_some_and(Lexer::is_value)
+                && Lexer::is_value(&tokens[i + 1])
+            {
+                let center = result.pop().unwrap_or_else(Token::empty);
+                let plusminus = Token::new_sub(
+                    vec![center, current.clone(), tokens[i + 1].clone()],
                     SubMethod::PAREN,
                 );
-                result.push(func_call);
-                i += 2; // Skip both function and argument
+                result.push(plusminus);
+                i += 2; // Skip both sign and the half-width
             }
             // Handle postfix factorial: 5!, (2+3)!
             else if (current.is_number() || current.is_sub_exp())
@@ -349,6 +484,11 @@ impl<'a> Lexer<'a> {
         result
     }
 
+    // Checks if the token represents a value: a number, an identifier or a sub-expression.
+    fn is_value(t: &Token) -> bool {
+        t.is_number() || t.is_sub_exp() || t.is_identifier()
+    }
+
     fn combine_tokens(tokens: Vec<Token>) -> Sub {
         // First pass: combine function tokens with their arguments
         let tokens = Lexer::combine_function_calls(tokens);
@@ -362,7 +502,7 @@ impl<'a> Lexer<'a> {
             let next = if i < tokens.len() - 1 {
                 tokens[i + 1].clone()
             } else {
-                Token::from(String::new(), Token::unknown_index())
+                Token::empty()
             };
 
             // Helper to check if a token is a "value" (number, identifier, or sub-expression)
@@ -380,7 +520,7 @@ impl<'a> Lexer<'a> {
             if is_auto_solids {
                 sub_tokens.append(&mut Vec::from([
                     current.clone(),
-                    Token::from(String::from("*"), Token::unknown_index()),
+                    Token::from(String::from("*"), Span::at(current.span.end)),
                 ]));
                 continue;
             }
@@ -396,11 +536,9 @@ impl<'a> Lexer<'a> {
 
             if !power_subs.is_empty() {
                 sub_tokens.push(Token::new_sub(
-                    Lexer::combine_powers(power_subs.clone(), power_subs.clone().len() - 1),
+                    Lexer::combine_powers(std::mem::take(&mut power_subs)),
                     SubMethod::PAREN,
                 ));
-
-                power_subs.clear();
             }
 
             let current_is_combinable = current.is_div_or_prod() || current.is_percentage();
@@ -412,10 +550,9 @@ impl<'a> Lexer<'a> {
             if is_sub || next_is_combinable && (current_is_value || current.is_function()) {
                 if !power_subs.is_empty() {
                     sub_tokens.push(Token::new_sub(
-                        Lexer::combine_powers(power_subs.clone(), power_subs.len() - 1),
+                        Lexer::combine_powers(std::mem::take(&mut power_subs)),
                         SubMethod::PAREN,
                     ));
-                    power_subs.clear();
                 }
 
                 sub_tokens.push(current);
@@ -437,13 +574,10 @@ impl<'a> Lexer<'a> {
 
         if !power_subs.is_empty() {
             if sub_tokens.is_empty() {
-                sub_tokens.append(&mut Lexer::combine_powers(
-                    power_subs.clone(),
-                    power_subs.len() - 1,
-                ));
+                sub_tokens.append(&mut Lexer::combine_powers(power_subs));
             } else {
                 sub_tokens.push(Token::new_sub(
-                    Lexer::combine_powers(power_subs.clone(), power_subs.len() - 1),
+                    Lexer::combine_powers(power_subs),
                     SubMethod::PAREN,
                 ))
             }
@@ -477,27 +611,28 @@ impl<'a> Lexer<'a> {
     //  ╭───────────────────╮     ╭─────────────╮     ╭─────────╮     ╭───╮
     //  │ 5 ^ (2 ^ (3 ^ 2)) │ ──▶ │ 5 ^ (2 ^ 9) │ ──▶ │ 5 ^ 512 │ ──▶ │ ? │
     //  ╰───────────────────╯     ╰─────────────╯     ╰─────────╯     ╰───╯
-    fn combine_powers(tokens: Vec<Token>, start: usize) -> Vec<Token> {
-        if tokens.len() == 3 {
-            return tokens;
-        }
-
-        let mut combined_tokens: Vec<Token> = Vec::new();
-
-        let end = start as i32 - 2;
-        if end < 0 {
-            return combined_tokens;
+    //
+    //  The chain is folded from its end in a single pass, so long chains don't take
+    //  quadratic time to combine.
+    fn combine_powers(mut tokens: Vec<Token>) -> Vec<Token> {
+        // An incomplete chain, like `5 ^ 2 ^`, has no last power to start from.
+        if tokens.len() < 3 || tokens.len().is_multiple_of(2) {
+            return Vec::new();
         }
 
-        let cpart: Vec<Token> = tokens.clone()[end as usize..=start].to_vec();
-        combined_tokens.append(&mut tokens.clone()[..end as usize].to_vec());
-        combined_tokens.push(Token::new_sub(cpart, SubMethod::PAREN));
+        // The first base and power are kept as they're, next to the combined rest.
+        let mut rest = tokens.split_off(2).into_iter().rev();
+        let mut combined = match rest.next() {
+            Some(last) => last,
+            None => return Vec::new(),
+        };
 
-        if end <= 0 {
-            return combined_tokens;
+        while let (Some(power), Some(base)) = (rest.next(), rest.next()) {
+            combined = Token::new_sub(vec![base, power, combined], SubMethod::PAREN);
         }
 
-        Lexer::combine_powers(combined_tokens, end as usize)
+        tokens.push(combined);
+        tokens
     }
 
     // Converts byte-character to token-structure.
@@ -515,20 +650,106 @@ impl<'a> Lexer<'a> {
     //   ... and so on ...
     //
     fn generate_token(&self) -> Option<Result<Token, Error>> {
+        let token = match self.pending.take() {
+            Some(t) => Ok(t),
+            None => self.read_token()?,
+        };
+
+        if let Ok(t) = &token {
+            self.track_arguments(t);
+            self.track_bars(t);
+        }
+
+        Some(token)
+    }
+
+    // Keeps track of the absolute value bars, like in `|x - 1|`.
+    //
+    // The same bar opens and closes the absolute value, so it's a closing one only
+    // when there is an open bar and the last token has closed a value.
+    fn track_bars(&self, t: &Token) {
+        if t.literal == "|" && t.is_labs() {
+            self.bars.set(self.bars.get() + 1);
+        } else if t.literal == "|" && t.is_rabs() {
+            self.bars.set(self.bars.get().saturating_sub(1));
+        }
+
+        self.after_value.set(
+            t.is_number()
+                || t.is_identifier()
+                || t.is_rparen()
+                || t.is_rabs()
+                || t.is_factorial()
+                || t.is_percentage(),
+        );
+    }
+
+    // Keeps track of the brackets that hold an argument list.
+    //
+    // Commas are separators only inside of a multi-argument function call,
+    // like `integrate(x ^ 2, x, 0, 1)`, or an interval literal, like `[9.8, 9.82]`.
+    // Everywhere else they keep being read as a decimal point.
+    fn track_arguments(&self, t: &Token) {
+        let mut arguments = self.arguments.borrow_mut();
+        if t.is_lparen() {
+            arguments.push(self.expects_arguments.get());
+        } else if t.is_labs() {
+            arguments.push(t.literal == "[");
+        } else if t.is_rparen() || t.is_rabs() {
+            arguments.pop();
+        }
+
+        self.expects_arguments.set(t.takes_arguments());
+    }
+
+    // Checks if the current position is directly inside of an argument list.
+    fn in_arguments(&self) -> bool {
+        self.arguments.borrow().last().copied().unwrap_or(false)
+    }
+
+    // Reads the next token from the input, see [generate_token].
+    fn read_token(&self) -> Option<Result<Token, Error>> {
         // Check if we've reached the end of input
-        if self.position.get() >= self.input.len() {
+        if self.position.get() >= self.size {
             return None;
         }
 
         self.skip_whitespace();
 
         // After skipping whitespace, check again if we're at the end
-        if self.position.get() >= self.input.len() {
+        if self.position.get() >= self.size {
             return None;
         }
 
         let ch: String = self.examination_char.get().to_string();
-        let position: i32 = self.position.get() as i32;
+        let span: Span = self.span(self.position.get(), self.position.get() + 1);
+        if ch == "," && self.in_arguments() {
+            self.read_char()?;
+            return Some(Ok(Token::from(ch, span)));
+        }
+
+        if ch == "|" {
+            self.read_char()?;
+
+            let typ = match self.bars.get() > 0 && self.after_value.get() {
+                true => TokenType::RABS,
+                false => TokenType::LABS,
+            };
+            return Some(Ok(Token::new(typ, ch, Sub::empty(), span)));
+        }
+
+        // Superscripts are an exponent, like in `x²`, so they're generated as a power sign,
+        // and the number of the exponent right after it.
+        if ch.is_superscript() {
+            return Some(Ok(self.read_superscript()));
+        }
+
+        // Constants are numbers on their own, like in `2πr`.
+        if ch.constant().is_some() {
+            self.read_char()?;
+            return Some(Ok(Token::from(ch, span)));
+        }
+
         if ch.is_operation_sign() {
             if ch.is_plus_or_minus() && self.is_free_from_number(1) && self.next_is_number(1) {
                 match self.read_number() {
@@ -539,7 +760,7 @@ impl<'a> Lexer<'a> {
 
             self.read_char()?;
 
-            return Some(Ok(Token::from(ch, (position, position))));
+            return Some(Ok(Token::from(ch, span)));
         }
 
         // Check for a positive number.
@@ -552,7 +773,7 @@ impl<'a> Lexer<'a> {
 
         // Check for identifier (function name).
         let c = self.examination_char.get();
-        if c.is_alphabetic() {
+        if c.is_alphabetic() || c == '_' {
             match self.read_identifier() {
                 None => return None,
                 Some(v) => return Some(Ok(Token::from(v.0, v.1))),
@@ -562,7 +783,13 @@ impl<'a> Lexer<'a> {
         let lit: String = self.examination_char.get().to_string();
         self.read_char()?;
 
-        Some(Ok(Token::from(lit, (position, position))))
+        Some(Ok(Token::from(lit, span)))
+    }
+
+    // Converts the range of character positions to the span of bytes in input.
+    // The [end] is exclusive.
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span::from_chars(self.input, start, end)
     }
 
     // A [char] reading functionality, that also updates state of lexer.
@@ -576,7 +803,7 @@ impl<'a> Lexer<'a> {
                 Some(ch)
             }
             None => {
-                if self.read_position.get() == self.input.len() {
+                if self.read_position.get() == self.size {
                     let ch: char = self.input.chars().nth(self.position.get()).unwrap();
 
                     self.examination_char.set(ch);
@@ -601,7 +828,7 @@ impl<'a> Lexer<'a> {
     //   We need to determine the start and end index
     //   of that full-number in rune array (from digit to digit).
     //
-    fn read_number(&self) -> Option<(String, (i32, i32))> {
+    fn read_number(&self) -> Option<(String, Span)> {
         let input: String = self.input.to_string();
         let start: usize = self.position.get();
 
@@ -614,11 +841,13 @@ impl<'a> Lexer<'a> {
 
         // Keep reading forward chars if l.Char is number or number-point.
         let mut ch: char = self.examination_char.get();
-        while ch.to_string().is_number() || ch.to_string().is_point() || ch == ' ' {
+        while (ch.to_string().is_number() || ch.to_string().is_point() || ch == ' ')
+            && !(ch == ',' && self.in_arguments())
+        {
             match self.read_char() {
                 Some(v) => ch = v,
                 None => {
-                    if self.read_position.get() >= self.input.len() {
+                    if self.read_position.get() >= self.size {
                         break;
                     }
 
@@ -632,32 +861,49 @@ impl<'a> Lexer<'a> {
             .skip(start)
             .take(self.position.get() - start)
             .collect();
-        let end = match num.chars().last() {
-            None => self.position.get(),
-            Some(v) => {
-                if v != ' ' {
-                    self.position.get() - 1
-                } else {
-                    self.position.get() - 2
-                }
-            }
-        };
+        // Spaces after the number aren't a part of it.
+        let end = start + num.trim_end().chars().count();
+        Some((num, self.span(start, end)))
+    }
+
+    // Reads the run of superscript characters, like `⁻¹` in `x⁻¹`, and generates the
+    // power sign of it. The number of exponent is kept to be generated next.
+    fn read_superscript(&self) -> Token {
+        let start: usize = self.position.get();
+        let mut end: usize = start;
+        while self.peek_char(end - start).is_some_and(|c| c.to_string().is_superscript()) {
+            end += 1;
+        }
 
-        Some((num, (start as i32, end as i32)))
+        let exponent: String = self.input.chars().skip(start).take(end - start).collect();
+        for _ in start..end {
+            self.read_char();
+        }
+
+        self.pending.replace(Some(Token::from(exponent, self.span(start, end))));
+        Token::from(String::from("^"), self.span(start, start))
     }
 
     // Reads an identifier (function name or variable name) from the input.
     // Returns the identifier string and its position range.
     // Identifiers can contain letters, digits, and underscores (but must start with a letter).
-    fn read_identifier(&self) -> Option<(String, (i32, i32))> {
+    fn read_identifier(&self) -> Option<(String, Span)> {
         let start: usize = self.position.get();
 
+        // Superscripts and constants are alphanumeric, but aren't a part of identifier:
+        // `x²` is `x ^ 2`, and `rπ` is `r • π`.
+        let is_part = |c: char| {
+            (c.is_alphanumeric() || c == '_')
+                && !c.to_string().is_superscript()
+                && c.to_string().constant().is_none()
+        };
+
         let mut ch: char = self.examination_char.get();
-        while ch.is_alphanumeric() || ch == '_' {
+        while is_part(ch) {
             match self.read_char() {
                 Some(v) => ch = v,
                 None => {
-                    if self.read_position.get() >= self.input.len() {
+                    if self.read_position.get() >= self.size {
                         break;
                     }
                     return None;
@@ -672,19 +918,14 @@ impl<'a> Lexer<'a> {
             .take(self.position.get() - start)
             .collect();
 
-        let end = if ident.is_empty() {
-            start
-        } else {
-            self.position.get() - 1
-        };
-
-        Some((ident, (start as i32, end as i32)))
+        let end = start + ident.chars().count();
+        Some((ident, self.span(start, end)))
     }
 
     // Eats all type of empty(white) spaces.
     fn skip_whitespace(&self) {
         let mut c: char = self.examination_char.get();
-        while c == ' ' || c == '\t' || c == '\n' || c == '\r' {
+        while is_space(c) {
             match self.read_char() {
                 Some(v) => c = v,
                 None => break,
@@ -698,7 +939,7 @@ impl<'a> Lexer<'a> {
     // As default (when you wanna go for one step next) you should make [step] <1>.
     fn peek_char(&self, step: usize) -> Option<char> {
         let index: usize = self.position.get() + step;
-        if index >= self.input.len() {
+        if index >= self.size {
             return None;
         }
 
@@ -725,13 +966,14 @@ impl<'a> Lexer<'a> {
     fn is_free_from_number(&self, step: usize) -> bool {
         match self.peek_char_back(step) {
             None => true, // if there is nothing in back, then it's free from number.
+            Some('|') => !self.after_value.get(),
             Some(v) => {
-                if v != ' ' {
+                if !is_space(v) {
                     let is_paren: (bool, bool) = v.to_string().is_parentheses();
                     let is_abs: (bool, bool) = v.to_string().is_abs();
                     let is_factorial = v == '!';
                     // Also check if previous char is alphanumeric (part of identifier/number)
-                    let is_alphanum = v.is_alphanumeric() || v == '_';
+                    let is_alphanum = v.is_alphanumeric() || v == '_' || v == '∞';
 
                     return !is_paren.1 && !is_abs.1 && !is_factorial && !is_alphanum;
                 }
@@ -749,7 +991,7 @@ impl<'a> Lexer<'a> {
         match self.peek_char(step) {
             None => false, // nothing != number
             Some(v) => {
-                if v != ' ' {
+                if !is_space(v) {
                     return v.to_string().is_number();
                 }
 
@@ -764,6 +1006,13 @@ mod test {
     use super::*;
     use std::collections::HashMap;
 
+    // The sub token of brackets, that spans from the opening bracket to the closing one.
+    fn bracketed(tokens: Vec<Token>, method: SubMethod, span: Span) -> Token {
+        let mut token = Token::new_sub(tokens, method);
+        token.span = span;
+        token
+    }
+
     #[test]
     fn new() {
         let test_data: HashMap<&str, Result<Lexer, Error>> = HashMap::from([
@@ -772,9 +1021,15 @@ mod test {
                 "4 + 2",
                 Ok(Lexer {
                     input: "4 + 2",
+                    size: 5,
                     examination_char: Cell::new('4'),
                     position: Cell::from(0),
                     read_position: Cell::from(1),
+                    arguments: RefCell::new(Vec::new()),
+                    expects_arguments: Cell::new(false),
+                    bars: Cell::new(0),
+                    after_value: Cell::new(false),
+                    pending: RefCell::new(None),
                 }),
             ),
         ]);
@@ -792,23 +1047,24 @@ mod test {
             (
                 String::from("25"),
                 Ok(Sub::new(
-                    vec![Token::from(String::from("25"), (0, 1))],
+                    vec![Token::from(String::from("25"), Span::new(0, 2))],
                     SubMethod::PAREN,
                 )),
             ),
             (
                 String::from("-25"),
                 Ok(Sub::new(
-                    vec![Token::from(String::from("-25"), (0, 2))],
+                    vec![Token::from(String::from("-25"), Span::new(0, 3))],
                     SubMethod::PAREN,
                 )),
             ),
             (
                 String::from("(25)"),
                 Ok(Sub::new(
-                    vec![Token::new_sub(
-                        vec![Token::from(String::from("25"), (1, 2))],
+                    vec![bracketed(
+                        vec![Token::from(String::from("25"), Span::new(1, 3))],
                         SubMethod::PAREN,
+                        Span::new(0, 4),
                     )],
                     SubMethod::PAREN,
                 )),
@@ -816,9 +1072,10 @@ mod test {
             (
                 String::from("(-25)"),
                 Ok(Sub::new(
-                    vec![Token::new_sub(
-                        vec![Token::from(String::from("-25"), (1, 3))],
+                    vec![bracketed(
+                        vec![Token::from(String::from("-25"), Span::new(1, 4))],
                         SubMethod::PAREN,
+                        Span::new(0, 5),
                     )],
                     SubMethod::PAREN,
                 )),
@@ -827,9 +1084,9 @@ mod test {
                 String::from("-25 + 5"),
                 Ok(Sub::new(
                     vec![
-                        Token::from(String::from("-25"), (0, 2)),
-                        Token::from(String::from("+"), (4, 4)),
-                        Token::from(String::from("5"), (6, 6)),
+                        Token::from(String::from("-25"), Span::new(0, 3)),
+                        Token::from(String::from("+"), Span::new(4, 5)),
+                        Token::from(String::from("5"), Span::new(6, 7)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -838,10 +1095,10 @@ mod test {
                 String::from("- - 2 + - 5"),
                 Ok(Sub::new(
                     vec![
-                        Token::from(String::from("-"), (0, 0)),
-                        Token::from(String::from("-2"), (2, 4)),
-                        Token::from(String::from("+"), (6, 6)),
-                        Token::from(String::from("-5"), (8, 10)),
+                        Token::from(String::from("-"), Span::new(0, 1)),
+                        Token::from(String::from("-2"), Span::new(2, 5)),
+                        Token::from(String::from("+"), Span::new(6, 7)),
+                        Token::from(String::from("-5"), Span::new(8, 11)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -850,9 +1107,9 @@ mod test {
                 String::from("42 * 5"),
                 Ok(Sub::new(
                     vec![
-                        Token::from(String::from("42"), (0, 1)),
-                        Token::from(String::from("*"), (3, 3)),
-                        Token::from(String::from("5"), (5, 5)),
+                        Token::from(String::from("42"), Span::new(0, 2)),
+                        Token::from(String::from("*"), Span::new(3, 4)),
+                        Token::from(String::from("5"), Span::new(5, 6)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -863,20 +1120,20 @@ mod test {
                     vec![
                         Token::new_sub(
                             vec![
-                                Token::from(String::from("-2"), (0, 2)),
-                                Token::from(String::from("*"), (4, 4)),
-                                Token::from(String::from("7"), (6, 6)),
-                                Token::from(String::from("/"), (8, 8)),
-                                Token::from(String::from("5"), (10, 10)),
+                                Token::from(String::from("-2"), Span::new(0, 3)),
+                                Token::from(String::from("*"), Span::new(4, 5)),
+                                Token::from(String::from("7"), Span::new(6, 7)),
+                                Token::from(String::from("/"), Span::new(8, 9)),
+                                Token::from(String::from("5"), Span::new(10, 11)),
                             ],
                             SubMethod::PAREN,
                         ),
-                        Token::from(String::from("+"), (12, 12)),
+                        Token::from(String::from("+"), Span::new(12, 13)),
                         Token::new_sub(
                             vec![
-                                Token::from(String::from("-20"), (14, 17)),
-                                Token::from(String::from("/"), (19, 19)),
-                                Token::from(String::from("-5"), (21, 23)),
+                                Token::from(String::from("-20"), Span::new(14, 18)),
+                                Token::from(String::from("/"), Span::new(19, 20)),
+                                Token::from(String::from("-5"), Span::new(21, 24)),
                             ],
                             SubMethod::PAREN,
                         ),
@@ -888,16 +1145,17 @@ mod test {
                 String::from("(5 - 9) - 10"),
                 Ok(Sub::new(
                     vec![
-                        Token::new_sub(
+                        bracketed(
                             vec![
-                                Token::from(String::from("5"), (1, 1)),
-                                Token::from(String::from("-"), (3, 3)),
-                                Token::from(String::from("9"), (5, 5)),
+                                Token::from(String::from("5"), Span::new(1, 2)),
+                                Token::from(String::from("-"), Span::new(3, 4)),
+                                Token::from(String::from("9"), Span::new(5, 6)),
                             ],
                             SubMethod::PAREN,
+                            Span::new(0, 7),
                         ),
-                        Token::from(String::from("-"), (8, 8)),
-                        Token::from(String::from("10"), (10, 11)),
+                        Token::from(String::from("-"), Span::new(8, 9)),
+                        Token::from(String::from("10"), Span::new(10, 12)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -906,22 +1164,24 @@ mod test {
                 String::from("(10 - 5) - (10 / 2)"),
                 Ok(Sub::new(
                     vec![
-                        Token::new_sub(
+                        bracketed(
                             vec![
-                                Token::from(String::from("10"), (1, 2)),
-                                Token::from(String::from("-"), (4, 4)),
-                                Token::from(String::from("5"), (6, 6)),
+                                Token::from(String::from("10"), Span::new(1, 3)),
+                                Token::from(String::from("-"), Span::new(4, 5)),
+                                Token::from(String::from("5"), Span::new(6, 7)),
                             ],
                             SubMethod::PAREN,
+                            Span::new(0, 8),
                         ),
-                        Token::from(String::from("-"), (9, 9)),
-                        Token::new_sub(
+                        Token::from(String::from("-"), Span::new(9, 10)),
+                        bracketed(
                             vec![
-                                Token::from(String::from("10"), (12, 13)),
-                                Token::from(String::from("/"), (15, 15)),
-                                Token::from(String::from("2"), (17, 17)),
+                                Token::from(String::from("10"), Span::new(12, 14)),
+                                Token::from(String::from("/"), Span::new(15, 16)),
+                                Token::from(String::from("2"), Span::new(17, 18)),
                             ],
                             SubMethod::PAREN,
+                            Span::new(11, 19),
                         ),
                     ],
                     SubMethod::PAREN,
@@ -931,30 +1191,33 @@ mod test {
                 String::from("((10 - 5) - (10 / 2)) / 2"),
                 Ok(Sub::new(
                     vec![
-                        Token::new_sub(
+                        bracketed(
                             vec![
-                                Token::new_sub(
+                                bracketed(
                                     vec![
-                                        Token::from(String::from("10"), (2, 3)),
-                                        Token::from(String::from("-"), (5, 5)),
-                                        Token::from(String::from("5"), (7, 7)),
+                                        Token::from(String::from("10"), Span::new(2, 4)),
+                                        Token::from(String::from("-"), Span::new(5, 6)),
+                                        Token::from(String::from("5"), Span::new(7, 8)),
                                     ],
                                     SubMethod::PAREN,
+                                    Span::new(1, 9),
                                 ),
-                                Token::from(String::from("-"), (10, 10)),
-                                Token::new_sub(
+                                Token::from(String::from("-"), Span::new(10, 11)),
+                                bracketed(
                                     vec![
-                                        Token::from(String::from("10"), (13, 14)),
-                                        Token::from(String::from("/"), (16, 16)),
-                                        Token::from(String::from("2"), (18, 18)),
+                                        Token::from(String::from("10"), Span::new(13, 15)),
+                                        Token::from(String::from("/"), Span::new(16, 17)),
+                                        Token::from(String::from("2"), Span::new(18, 19)),
                                     ],
                                     SubMethod::PAREN,
+                                    Span::new(12, 20),
                                 ),
                             ],
                             SubMethod::PAREN,
+                            Span::new(0, 21),
                         ),
-                        Token::from(String::from("/"), (22, 22)),
-                        Token::from(String::from("2"), (24, 24)),
+                        Token::from(String::from("/"), Span::new(22, 23)),
+                        Token::from(String::from("2"), Span::new(24, 25)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -963,36 +1226,39 @@ mod test {
                 String::from("(2 + 5) * (5 - 9 / (8 - 5))"),
                 Ok(Sub::new(
                     vec![
-                        Token::new_sub(
+                        bracketed(
                             vec![
-                                Token::from(String::from("2"), (1, 1)),
-                                Token::from(String::from("+"), (3, 3)),
-                                Token::from(String::from("5"), (5, 5)),
+                                Token::from(String::from("2"), Span::new(1, 2)),
+                                Token::from(String::from("+"), Span::new(3, 4)),
+                                Token::from(String::from("5"), Span::new(5, 6)),
                             ],
                             SubMethod::PAREN,
+                            Span::new(0, 7),
                         ),
-                        Token::from(String::from("*"), (8, 8)),
-                        Token::new_sub(
+                        Token::from(String::from("*"), Span::new(8, 9)),
+                        bracketed(
                             vec![
-                                Token::from(String::from("5"), (11, 11)),
-                                Token::from(String::from("-"), (13, 13)),
+                                Token::from(String::from("5"), Span::new(11, 12)),
+                                Token::from(String::from("-"), Span::new(13, 14)),
                                 Token::new_sub(
                                     vec![
-                                        Token::from(String::from("9"), (15, 15)),
-                                        Token::from(String::from("/"), (17, 17)),
-                                        Token::new_sub(
+                                        Token::from(String::from("9"), Span::new(15, 16)),
+                                        Token::from(String::from("/"), Span::new(17, 18)),
+                                        bracketed(
                                             vec![
-                                                Token::from(String::from("8"), (20, 20)),
-                                                Token::from(String::from("-"), (22, 22)),
-                                                Token::from(String::from("5"), (24, 24)),
+                                                Token::from(String::from("8"), Span::new(20, 21)),
+                                                Token::from(String::from("-"), Span::new(22, 23)),
+                                                Token::from(String::from("5"), Span::new(24, 25)),
                                             ],
                                             SubMethod::PAREN,
+                                            Span::new(19, 26),
                                         ),
                                     ],
                                     SubMethod::PAREN,
                                 ),
                             ],
                             SubMethod::PAREN,
+                            Span::new(10, 27),
                         ),
                     ],
                     SubMethod::PAREN,
@@ -1002,27 +1268,29 @@ mod test {
                 String::from("5(5 / 2)(9 * 3)11"),
                 Ok(Sub::new(
                     vec![
-                        Token::from(String::from("5"), (0, 0)),
-                        Token::from(String::from("*"), Token::unknown_index()),
-                        Token::new_sub(
+                        Token::from(String::from("5"), Span::new(0, 1)),
+                        Token::from(String::from("*"), Span::at(1)),
+                        bracketed(
                             vec![
-                                Token::from(String::from("5"), (2, 2)),
-                                Token::from(String::from("/"), (4, 4)),
-                                Token::from(String::from("2"), (6, 6)),
+                                Token::from(String::from("5"), Span::new(2, 3)),
+                                Token::from(String::from("/"), Span::new(4, 5)),
+                                Token::from(String::from("2"), Span::new(6, 7)),
                             ],
                             SubMethod::PAREN,
+                            Span::new(1, 8),
                         ),
-                        Token::from(String::from("*"), Token::unknown_index()),
-                        Token::new_sub(
+                        Token::from(String::from("*"), Span::at(8)),
+                        bracketed(
                             vec![
-                                Token::from(String::from("9"), (9, 9)),
-                                Token::from(String::from("*"), (11, 11)),
-                                Token::from(String::from("3"), (13, 13)),
+                                Token::from(String::from("9"), Span::new(9, 10)),
+                                Token::from(String::from("*"), Span::new(11, 12)),
+                                Token::from(String::from("3"), Span::new(13, 14)),
                             ],
                             SubMethod::PAREN,
+                            Span::new(8, 15),
                         ),
-                        Token::from(String::from("*"), Token::unknown_index()),
-                        Token::from(String::from("11"), (15, 16)),
+                        Token::from(String::from("*"), Span::at(15)),
+                        Token::from(String::from("11"), Span::new(15, 17)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -1035,17 +1303,17 @@ mod test {
                             vec![
                                 Token::new_sub(
                                     vec![
-                                        Token::from(String::from("5"), (0, 0)),
-                                        Token::from(String::from("^"), (2, 2)),
+                                        Token::from(String::from("5"), Span::new(0, 1)),
+                                        Token::from(String::from("^"), Span::new(2, 3)),
                                         Token::new_sub(
                                             vec![
-                                                Token::from(String::from("3"), (4, 4)),
-                                                Token::from(String::from("^"), (6, 6)),
+                                                Token::from(String::from("3"), Span::new(4, 5)),
+                                                Token::from(String::from("^"), Span::new(6, 7)),
                                                 Token::new_sub(
                                                     vec![
-                                                        Token::from(String::from("2"), (8, 8)),
-                                                        Token::from(String::from("^"), (10, 10)),
-                                                        Token::from(String::from("5"), (12, 12)),
+                                                        Token::from(String::from("2"), Span::new(8, 9)),
+                                                        Token::from(String::from("^"), Span::new(10, 11)),
+                                                        Token::from(String::from("5"), Span::new(12, 13)),
                                                     ],
                                                     SubMethod::PAREN,
                                                 ),
@@ -1055,13 +1323,13 @@ mod test {
                                     ],
                                     SubMethod::PAREN,
                                 ),
-                                Token::from(String::from("*"), (14, 14)),
-                                Token::from(String::from("19"), (16, 17)),
+                                Token::from(String::from("*"), Span::new(14, 15)),
+                                Token::from(String::from("19"), Span::new(16, 18)),
                             ],
                             SubMethod::PAREN,
                         ),
-                        Token::from(String::from("-"), (19, 19)),
-                        Token::from(String::from("50"), (21, 22)),
+                        Token::from(String::from("-"), Span::new(19, 20)),
+                        Token::from(String::from("50"), Span::new(21, 23)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -1070,13 +1338,13 @@ mod test {
                 String::from("5 ^ 3 ^ 19"),
                 Ok(Sub::new(
                     vec![
-                        Token::from(String::from("5"), (0, 0)),
-                        Token::from(String::from("^"), (2, 2)),
+                        Token::from(String::from("5"), Span::new(0, 1)),
+                        Token::from(String::from("^"), Span::new(2, 3)),
                         Token::new_sub(
                             vec![
-                                Token::from(String::from("3"), (4, 4)),
-                                Token::from(String::from("^"), (6, 6)),
-                                Token::from(String::from("19"), (8, 9)),
+                                Token::from(String::from("3"), Span::new(4, 5)),
+                                Token::from(String::from("^"), Span::new(6, 7)),
+                                Token::from(String::from("19"), Span::new(8, 10)),
                             ],
                             SubMethod::PAREN,
                         ),
@@ -1088,23 +1356,24 @@ mod test {
                 String::from("(2 + 3 ^ 5) ^ 9"),
                 Ok(Sub::new(
                     vec![
-                        Token::new_sub(
+                        bracketed(
                             vec![
-                                Token::from(String::from("2"), (1, 1)),
-                                Token::from(String::from("+"), (3, 3)),
+                                Token::from(String::from("2"), Span::new(1, 2)),
+                                Token::from(String::from("+"), Span::new(3, 4)),
                                 Token::new_sub(
                                     vec![
-                                        Token::from(String::from("3"), (5, 5)),
-                                        Token::from(String::from("^"), (7, 7)),
-                                        Token::from(String::from("5"), (9, 9)),
+                                        Token::from(String::from("3"), Span::new(5, 6)),
+                                        Token::from(String::from("^"), Span::new(7, 8)),
+                                        Token::from(String::from("5"), Span::new(9, 10)),
                                     ],
                                     SubMethod::PAREN,
                                 ),
                             ],
                             SubMethod::PAREN,
+                            Span::new(0, 11),
                         ),
-                        Token::from(String::from("^"), (12, 12)),
-                        Token::from(String::from("9"), (14, 14)),
+                        Token::from(String::from("^"), Span::new(12, 13)),
+                        Token::from(String::from("9"), Span::new(14, 15)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -1113,16 +1382,17 @@ mod test {
                 String::from("[2 - 12] - 10"),
                 Ok(Sub::new(
                     vec![
-                        Token::new_sub(
+                        bracketed(
                             vec![
-                                Token::from(String::from("2"), (1, 1)),
-                                Token::from(String::from("-"), (3, 3)),
-                                Token::from(String::from("12"), (5, 6)),
+                                Token::from(String::from("2"), Span::new(1, 2)),
+                                Token::from(String::from("-"), Span::new(3, 4)),
+                                Token::from(String::from("12"), Span::new(5, 7)),
                             ],
                             SubMethod::ABS,
+                            Span::new(0, 8),
                         ),
-                        Token::from(String::from("-"), (9, 9)),
-                        Token::from(String::from("10"), (11, 12)),
+                        Token::from(String::from("-"), Span::new(9, 10)),
+                        Token::from(String::from("10"), Span::new(11, 13)),
                     ],
                     SubMethod::PAREN,
                 )),
@@ -1131,36 +1401,65 @@ mod test {
                 String::from("[7 - 14] * [5 - 9 / [5 - 3]]"),
                 Ok(Sub::new(
                     vec![
-                        Token::new_sub(
+                        bracketed(
                             vec![
-                                Token::from(String::from("7"), (1, 1)),
-                                Token::from(String::from("-"), (3, 3)),
-                                Token::from(String::from("14"), (5, 6)),
+                                Token::from(String::from("7"), Span::new(1, 2)),
+                                Token::from(String::from("-"), Span::new(3, 4)),
+                                Token::from(String::from("14"), Span::new(5, 7)),
                             ],
                             SubMethod::ABS,
+                            Span::new(0, 8),
                         ),
-                        Token::from(String::from("*"), (9, 9)),
-                        Token::new_sub(
+                        Token::from(String::from("*"), Span::new(9, 10)),
+                        bracketed(
                             vec![
-                                Token::from(String::from("5"), (12, 12)),
-                                Token::from(String::from("-"), (14, 14)),
+                                Token::from(String::from("5"), Span::new(12, 13)),
+                                Token::from(String::from("-"), Span::new(14, 15)),
                                 Token::new_sub(
                                     vec![
-                                        Token::from(String::from("9"), (16, 16)),
-                                        Token::from(String::from("/"), (18, 18)),
-                                        Token::new_sub(
+                                        Token::from(String::from("9"), Span::new(16, 17)),
+                                        Token::from(String::from("/"), Span::new(18, 19)),
+                                        bracketed(
                                             vec![
-                                                Token::from(String::from("5"), (21, 21)),
-                                                Token::from(String::from("-"), (23, 23)),
-                                                Token::from(String::from("3"), (25, 25)),
+                                                Token::from(String::from("5"), Span::new(21, 22)),
+                                                Token::from(String::from("-"), Span::new(23, 24)),
+                                                Token::from(String::from("3"), Span::new(25, 26)),
                                             ],
                                             SubMethod::ABS,
+                                            Span::new(20, 27),
                                         ),
                                     ],
                                     SubMethod::PAREN,
                                 ),
                             ],
                             SubMethod::ABS,
+                            Span::new(11, 28),
+                        ),
+                    ],
+                    SubMethod::PAREN,
+                )),
+            ),
+            (
+                String::from("[1, 2] * 3 ± 0.5"),
+                Ok(Sub::new(
+                    vec![
+                        bracketed(
+                            vec![
+                                Token::from(String::from("1"), Span::new(1, 2)),
+                                Token::from(String::from(","), Span::new(2, 3)),
+                                Token::from(String::from("2"), Span::new(4, 5)),
+                            ],
+                            SubMethod::ABS,
+                            Span::new(0, 6),
+                        ),
+                        Token::from(String::from("*"), Span::new(7, 8)),
+                        Token::new_sub(
+                            vec![
+                                Token::from(String::from("3"), Span::new(9, 10)),
+                                Token::from(String::from("±"), Span::new(11, 13)),
+                                Token::from(String::from("0.5"), Span::new(14, 17)),
+                            ],
+                            SubMethod::PAREN,
                         ),
                     ],
                     SubMethod::PAREN,
@@ -1200,6 +1499,57 @@ mod test {
         }
     }
 
+    #[test]
+    fn lex_recovering() {
+        let test_data: Vec<(&str, Vec<(&str, Span)>)> = vec![
+            ("2 + 2", vec![]),
+            ("", vec![("error: cannot parse an empty input", Span::default())]),
+            ("2 $ 3", vec![("error: found an illegal character: `$`", Span::new(2, 3))]),
+            (
+                "(2 $ 3] + x",
+                vec![
+                    ("error: unclosed bracket", Span::new(0, 1)),
+                    ("error: found an illegal character: `$`", Span::new(3, 4)),
+                    ("error: mismatched parentheses or brackets", Span::new(6, 7)),
+                ],
+            ),
+            (
+                "5 + (3 * [2)] ]",
+                vec![
+                    ("error: unclosed bracket", Span::new(4, 5)),
+                    ("error: mismatched parentheses or brackets", Span::new(11, 12)),
+                    ("error: mismatched parentheses or brackets", Span::new(14, 15)),
+                ],
+            ),
+        ];
+
+        for (input, expected) in test_data {
+            let (_, errors) = Lexer::lex_recovering(input);
+            let errors: Vec<(String, Span)> = errors
+                .iter()
+                .map(|e| {
+                    let message = e.to_string();
+                    let headline = message.lines().next().unwrap_or_default().trim().to_string();
+                    (headline, e.span().unwrap_or_default())
+                })
+                .collect();
+
+            let expected: Vec<(String, Span)> =
+                expected.into_iter().map(|(m, s)| (m.to_string(), s)).collect();
+            assert_eq!(errors, expected, "Failed for: {input}");
+        }
+
+        // Valid inputs are lexed the same way as by [lex].
+        let (sub, _) = Lexer::lex_recovering("2 * (3 + [x - 1])");
+        assert_eq!(Ok(sub), Lexer::lex("2 * (3 + [x - 1])"));
+
+        // Recovered tokens keep the error nodes, and close the unclosed brackets.
+        let (sub, _) = Lexer::lex_recovering("(2 $ 3] + x");
+        let group = &sub.tokens[0];
+        assert_eq!(group.sub.method, SubMethod::PAREN);
+        assert_eq!(group.sub.tokens.iter().filter(|t| t.is_illegal()).count(), 2);
+    }
+
     #[test]
     fn valid_parentheses() {
         let test_cases: Vec<&str> = vec![
@@ -1223,4 +1573,68 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn tokenize() {
+        type Tokens = Vec<(TokenType, Span)>;
+
+        let test_data: Vec<(&str, Tokens)> = vec![
+            ("", vec![]),
+            (
+                "sqrt(x) + 25",
+                vec![
+                    (TokenType::SQRT, Span::new(0, 4)),
+                    (TokenType::LPAREN, Span::new(4, 5)),
+                    (TokenType::IDENTIFIER, Span::new(5, 6)),
+                    (TokenType::RPAREN, Span::new(6, 7)),
+                    (TokenType::PLUS, Span::new(8, 9)),
+                    (TokenType::NUMBER, Span::new(10, 12)),
+                ],
+            ),
+            (
+                "(2 ] $",
+                vec![
+                    (TokenType::LPAREN, Span::new(0, 1)),
+                    (TokenType::NUMBER, Span::new(1, 2)),
+                    (TokenType::RABS, Span::new(3, 4)),
+                    (TokenType::ILLEGAL, Span::new(5, 6)),
+                ],
+            ),
+            (
+                "x² × |−3|",
+                vec![
+                    (TokenType::IDENTIFIER, Span::new(0, 1)),
+                    (TokenType::POWER, Span::at(1)),
+                    (TokenType::NUMBER, Span::new(1, 3)),
+                    (TokenType::PRODUCT, Span::new(4, 6)),
+                    (TokenType::LABS, Span::new(7, 8)),
+                    (TokenType::NUMBER, Span::new(8, 12)),
+                    (TokenType::RABS, Span::new(12, 13)),
+                ],
+            ),
+            (
+                "√2πr",
+                vec![
+                    (TokenType::SQRT, Span::new(0, 3)),
+                    (TokenType::NUMBER, Span::new(3, 4)),
+                    (TokenType::NUMBER, Span::new(4, 6)),
+                    (TokenType::IDENTIFIER, Span::new(6, 7)),
+                ],
+            ),
+            // Zero width spaces aren't whitespace for Rust, but they're skipped like spaces.
+            (
+                "2\u{200b}+\u{2009}3\u{200b}",
+                vec![
+                    (TokenType::NUMBER, Span::new(0, 1)),
+                    (TokenType::PLUS, Span::new(4, 5)),
+                    (TokenType::NUMBER, Span::new(8, 9)),
+                ],
+            ),
+        ];
+
+        for (input, expected) in test_data {
+            let tokens: Tokens = Lexer::tokenize(input).into_iter().map(|t| (t.typ, t.span)).collect();
+            assert_eq!(tokens, expected, "Failed for: {input}");
+        }
+    }
 }
@@ -98,11 +98,29 @@ match result {
 > For details refer to [repository](https://github.com/theiskaa/mate).
 */
 
+pub mod analysis;
 pub mod calculator;
 pub mod environment;
 pub mod errors;
+pub mod formatter;
+pub mod integrator;
+pub mod interval;
 pub mod lexer;
+pub mod limits;
+pub mod markup;
 pub mod mate;
 pub mod monitor;
+pub mod persistence;
+pub mod plot;
+pub mod rpc;
+pub mod script;
+pub mod session;
+pub mod span;
+pub mod suggest;
+pub mod syntax;
+pub mod table;
 pub mod token;
+pub mod trace;
+pub mod uncertain;
 pub mod utils;
+pub mod value;
@@ -0,0 +1,240 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::errors::Error;
+use std::time::{Duration, Instant};
+
+// Resource limits of a single evaluation, used to safely calculate untrusted input.
+//
+// Each limit is optional, and each breach results a dedicated [ErrorKind]:
+//
+//  ╭──────────────────┬──────────────────────────────────────────┬───────────────────╮
+//  │ max_input_length │ amount of characters in input            │ InputTooLong      │
+//  │ max_tokens       │ amount of tokens, generated by [Lexer]   │ TooManyTokens     │
+//  │ max_depth        │ nesting level of sub-expressions         │ TooDeep           │
+//  │ max_operations   │ amount of operations and function calls  │ TooManyOperations │
+//  │ timeout          │ wall-clock time of calculation           │ Timeout           │
+//  ╰──────────────────┴──────────────────────────────────────────┴───────────────────╯
+//
+// The default limits are tight enough for untrusted input, see [EvalLimits::unlimited]
+// for the behaviour of plain [Calculator::calculate].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EvalLimits {
+    max_input_length: Option<usize>,
+    max_tokens: Option<usize>,
+    max_depth: Option<usize>,
+    max_operations: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+impl Default for EvalLimits {
+    fn default() -> Self {
+        Self {
+            max_input_length: Some(10_000),
+            max_tokens: Some(5_000),
+            max_depth: Some(100),
+            max_operations: Some(1_000_000),
+            timeout: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+impl EvalLimits {
+    // Creates limits, that don't limit anything.
+    pub fn unlimited() -> Self {
+        Self {
+            max_input_length: None,
+            max_tokens: None,
+            max_depth: None,
+            max_operations: None,
+            timeout: None,
+        }
+    }
+
+    // Sets the maximum amount of characters in input.
+    pub fn with_max_input_length(mut self, max: usize) -> Self {
+        self.max_input_length = Some(max);
+        self
+    }
+
+    // Sets the maximum amount of tokens in input.
+    pub fn with_max_tokens(mut self, max: usize) -> Self {
+        self.max_tokens = Some(max);
+        self
+    }
+
+    // Sets the maximum nesting level of parentheses, absolute values, powers and functions.
+    pub fn with_max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    // Sets the maximum amount of operations and function calls, during the calculation.
+    pub fn with_max_operations(mut self, max: usize) -> Self {
+        self.max_operations = Some(max);
+        self
+    }
+
+    // Sets the maximum wall-clock time of the calculation.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn max_input_length(&self) -> Option<usize> {
+        self.max_input_length
+    }
+
+    pub fn max_tokens(&self) -> Option<usize> {
+        self.max_tokens
+    }
+
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    pub fn max_operations(&self) -> Option<usize> {
+        self.max_operations
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub(crate) fn check_input_length(&self, input: &str) -> Result<(), Error> {
+        match self.max_input_length {
+            Some(max) => {
+                let length = input.chars().count();
+                if length > max {
+                    return Err(Error::input_too_long(length, max));
+                }
+
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_tokens(&self, count: usize) -> Result<(), Error> {
+        match self.max_tokens {
+            Some(max) if count > max => Err(Error::too_many_tokens(max)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_depth(&self, depth: usize) -> Result<(), Error> {
+        match self.max_depth {
+            Some(max) if depth > max => Err(Error::too_deep(max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+// The resources, that were used by the calculation so far.
+// Shared by every nested sub-expression of the calculation.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Budget {
+    limits: EvalLimits,
+    started: Instant,
+    operations: usize,
+    depth: usize,
+}
+
+impl Budget {
+    pub(crate) fn new(limits: EvalLimits) -> Self {
+        Self {
+            limits,
+            started: Instant::now(),
+            operations: 0,
+            depth: 0,
+        }
+    }
+
+    // Counts a single operation, and checks the operation count and the deadline.
+    pub(crate) fn operation(&mut self) -> Result<(), Error> {
+        self.operations += 1;
+        if let Some(max) = self.limits.max_operations {
+            if self.operations > max {
+                return Err(Error::too_many_operations(max));
+            }
+        }
+
+        self.check_deadline()
+    }
+
+    // Checks that the calculation hasn't run out of its time.
+    pub(crate) fn check_deadline(&self) -> Result<(), Error> {
+        match self.limits.timeout {
+            Some(timeout) if self.started.elapsed() > timeout => Err(Error::timed_out(timeout)),
+            _ => Ok(()),
+        }
+    }
+
+    // Enters a nested sub-expression.
+    pub(crate) fn enter(&mut self) -> Result<(), Error> {
+        self.depth += 1;
+        self.limits.check_depth(self.depth)
+    }
+
+    // Leaves a nested sub-expression.
+    pub(crate) fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn checks() {
+        let limits = EvalLimits::unlimited()
+            .with_max_input_length(5)
+            .with_max_tokens(3)
+            .with_max_depth(2);
+
+        assert!(limits.check_input_length("2 + 2").is_ok());
+        assert_eq!(
+            limits.check_input_length("2 + 22").map_err(|e| e.kind()),
+            Err(ErrorKind::InputTooLong)
+        );
+        assert!(limits.check_input_length("√√√√√").is_ok());
+
+        assert!(limits.check_tokens(3).is_ok());
+        assert_eq!(limits.check_tokens(4), Err(Error::too_many_tokens(3)));
+
+        assert!(limits.check_depth(2).is_ok());
+        assert_eq!(limits.check_depth(3), Err(Error::too_deep(2)));
+
+        let unlimited = EvalLimits::unlimited();
+        assert!(unlimited.check_input_length(&"1".repeat(100_000)).is_ok());
+        assert!(unlimited.check_tokens(usize::MAX).is_ok());
+        assert!(unlimited.check_depth(usize::MAX).is_ok());
+    }
+
+    #[test]
+    fn budget() {
+        let mut budget = Budget::new(EvalLimits::unlimited().with_max_operations(2));
+        assert!(budget.operation().is_ok());
+        assert!(budget.operation().is_ok());
+        assert_eq!(budget.operation(), Err(Error::too_many_operations(2)));
+
+        let mut budget = Budget::new(EvalLimits::unlimited().with_max_depth(1));
+        assert!(budget.enter().is_ok());
+        assert_eq!(budget.enter(), Err(Error::too_deep(1)));
+        budget.leave();
+        budget.leave();
+        assert!(budget.enter().is_ok());
+
+        let timeout = Duration::from_millis(1);
+        let mut budget = Budget::new(EvalLimits::unlimited().with_timeout(timeout));
+        assert!(budget.check_deadline().is_ok());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(budget.check_deadline(), Err(Error::timed_out(timeout)));
+        assert_eq!(budget.operation(), Err(Error::timed_out(timeout)));
+    }
+}
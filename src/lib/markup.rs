@@ -0,0 +1,943 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    errors::Error,
+    formatter::Formatter,
+    lexer::Lexer,
+    token::{Sub, SubMethod, Token, TokenType},
+    utils::ChUtils,
+    value::Value,
+};
+
+// The names of greek letters, that are written as `\alpha` in LaTeX.
+const GREEK: [(&str, char); 24] = [
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("iota", 'ι'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("pi", 'π'),
+    ("rho", 'ρ'),
+    ("sigma", 'σ'),
+    ("tau", 'τ'),
+    ("upsilon", 'υ'),
+    ("phi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+    ("varphi", 'φ'),
+];
+
+// The expression tree of a token tree, that's used to render it as math markup.
+//
+// The lexer groups tokens by priority into sub-expressions, but doesn't keep which
+// parentheses were written in input. So, the tree is rebuilt by priority of operations,
+// and parentheses are added back only where the priority needs them:
+//
+//  2 * (3 + 4) / [x]  ──▶  \frac{2 \cdot \left(3 + 4\right)}{\lvert x \rvert}
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(String),
+    Variable(String),
+    Binary(Box<Node>, TokenType, Box<Node>),
+    Factorial(Box<Node>),
+    Abs(Box<Node>),
+    Interval(Box<Node>, Box<Node>),
+    Function(TokenType, Vec<Node>),
+}
+
+impl Node {
+    // Builds the node of a flat list of tokens, by climbing the priority of operations.
+    fn parse(tokens: &[Token]) -> Result<Node, Error> {
+        if tokens.is_empty() {
+            return Err(Error::empty_tokens());
+        }
+
+        let mut i = 0;
+        let node = Node::expression(tokens, &mut i, 0)?;
+        if i < tokens.len() {
+            return Err(Error::invalid_order());
+        }
+
+        Ok(node)
+    }
+
+    fn expression(tokens: &[Token], i: &mut usize, min: u8) -> Result<Node, Error> {
+        let mut lhs = Node::operand(tokens, i)?;
+
+        while let Some((priority, right)) = tokens.get(*i).and_then(|t| Node::binding(&t.typ)) {
+            if priority < min {
+                break;
+            }
+
+            let op = tokens[*i].typ.clone();
+            *i += 1;
+
+            let rhs = Node::expression(tokens, i, if right { priority } else { priority + 1 })?;
+            lhs = Node::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    // The priority of binary operation, and whether it's right-associative.
+    fn binding(typ: &TokenType) -> Option<(u8, bool)> {
+        match typ {
+            TokenType::ASSIGN => Some((1, true)),
+            TokenType::PLUS | TokenType::MINUS => Some((2, false)),
+            TokenType::PRODUCT | TokenType::DIVIDE | TokenType::PERCENTAGE => Some((3, false)),
+            TokenType::PLUSMINUS => Some((4, false)),
+            TokenType::POWER => Some((5, true)),
+            _ => None,
+        }
+    }
+
+    fn operand(tokens: &[Token], i: &mut usize) -> Result<Node, Error> {
+        let token = tokens.get(*i).ok_or_else(Error::invalid_order)?;
+        *i += 1;
+
+        let mut node = match token.typ {
+            TokenType::NUMBER => Node::Number(token.normalized()),
+            TokenType::IDENTIFIER => Node::Variable(token.literal.clone()),
+            TokenType::SUBEXP if token.sub.method == SubMethod::ABS => {
+                let parts = Node::arguments(&token.sub.tokens)?;
+                match <[Node; 2]>::try_from(parts) {
+                    Ok([lo, hi]) => Node::Interval(Box::new(lo), Box::new(hi)),
+                    Err(parts) if parts.len() == 1 => Node::Abs(Box::new(parts[0].clone())),
+                    Err(_) => return Err(Error::invalid_order()),
+                }
+            }
+            TokenType::SUBEXP => Node::parse(&token.sub.tokens)?,
+            _ if token.is_function() => {
+                let arguments = match tokens.get(*i) {
+                    Some(t) if t.is_sub_exp() && t.sub.method == SubMethod::PAREN => {
+                        Node::arguments(&t.sub.tokens)?
+                    }
+                    // A single value without parentheses, like `√16` or `sqrt x`.
+                    Some(t) if t.is_number() || t.is_identifier() || t.is_sub_exp() => {
+                        vec![Node::parse(std::slice::from_ref(t))?]
+                    }
+                    _ => return Err(Error::invalid_order()),
+                };
+                *i += 1;
+                Node::Function(token.typ.clone(), arguments)
+            }
+            _ => return Err(Error::invalid_order()),
+        };
+
+        while tokens.get(*i).is_some_and(Token::is_factorial) {
+            node = Node::Factorial(Box::new(node));
+            *i += 1;
+        }
+
+        Ok(node)
+    }
+
+    // Builds the nodes of a comma separated list of tokens.
+    fn arguments(tokens: &[Token]) -> Result<Vec<Node>, Error> {
+        tokens.split(Token::is_comma).map(Node::parse).collect()
+    }
+
+    // The priority of node, as it's shown: fractions are drawn as a whole,
+    // so they don't need parentheses around them like other divisions.
+    fn priority(&self) -> u8 {
+        match self {
+            Node::Binary(_, TokenType::DIVIDE, _) => 7,
+            Node::Binary(_, op, _) => Node::binding(op).map(|(p, _)| p).unwrap_or(7),
+            Node::Number(n) if n.starts_with('-') => 2,
+            Node::Factorial(_) => 6,
+            _ => 7,
+        }
+    }
+
+    // Checks if the node can be a base of power, or be followed by `!` without parentheses.
+    fn is_atom(&self) -> bool {
+        match self {
+            Node::Number(n) => !n.starts_with('-') && !n.contains(['e', 'E']),
+            Node::Variable(_) | Node::Abs(_) | Node::Interval(..) => true,
+            Node::Function(typ, _) => {
+                matches!(
+                    typ,
+                    TokenType::SQRT | TokenType::CBRT | TokenType::FLOOR | TokenType::CEIL
+                )
+            }
+            _ => false,
+        }
+    }
+
+    fn latex(&self) -> String {
+        let wrap = |node: &Node, parentheses: bool| {
+            if parentheses {
+                return format!("\\left({}\\right)", node.latex());
+            }
+            node.latex()
+        };
+
+        match self {
+            Node::Number(n) => number_latex(n),
+            Node::Variable(name) => variable_latex(name),
+            Node::Binary(lhs, op, rhs) => {
+                let (l, r) = (lhs.priority(), rhs.priority());
+                match op {
+                    TokenType::ASSIGN => format!("{} = {}", lhs.latex(), rhs.latex()),
+                    TokenType::PLUS => format!("{} + {}", wrap(lhs, l < 2), wrap(rhs, r < 2)),
+                    TokenType::MINUS => format!("{} - {}", wrap(lhs, l < 2), wrap(rhs, r <= 2)),
+                    TokenType::PRODUCT => {
+                        format!("{} \\cdot {}", wrap(lhs, l < 3), wrap(rhs, r < 3))
+                    }
+                    TokenType::PERCENTAGE => {
+                        format!("{}\\% \\cdot {}", wrap(lhs, l < 3), wrap(rhs, r <= 3))
+                    }
+                    TokenType::DIVIDE => format!("\\frac{{{}}}{{{}}}", lhs.latex(), rhs.latex()),
+                    TokenType::PLUSMINUS => {
+                        format!("{} \\pm {}", wrap(lhs, l < 4), wrap(rhs, r <= 4))
+                    }
+                    _ => format!("{}^{{{}}}", wrap(lhs, !lhs.is_atom()), rhs.latex()),
+                }
+            }
+            Node::Factorial(n) => format!("{}!", wrap(n, !n.is_atom())),
+            Node::Abs(n) => format!("\\lvert {} \\rvert", n.latex()),
+            Node::Interval(lo, hi) => format!("\\left[{}, {}\\right]", lo.latex(), hi.latex()),
+            Node::Function(typ, args) => {
+                let arg = |i: usize| args.get(i).map(Node::latex).unwrap_or_default();
+                let call = |name: &str| match args.first() {
+                    Some(n @ (Node::Number(_) | Node::Variable(_))) if n.is_atom() => {
+                        format!("{name} {}", n.latex())
+                    }
+                    _ => format!("{name}\\left({}\\right)", arg(0)),
+                };
+
+                match typ {
+                    TokenType::SQRT => format!("\\sqrt{{{}}}", arg(0)),
+                    TokenType::CBRT => format!("\\sqrt[3]{{{}}}", arg(0)),
+                    TokenType::SIN => call("\\sin"),
+                    TokenType::COS => call("\\cos"),
+                    TokenType::TAN => call("\\tan"),
+                    TokenType::LOG => call("\\log_{10}"),
+                    TokenType::LN => call("\\ln"),
+                    TokenType::EXP => call("\\exp"),
+                    TokenType::FLOOR => format!("\\lfloor {} \\rfloor", arg(0)),
+                    TokenType::CEIL => format!("\\lceil {} \\rceil", arg(0)),
+                    TokenType::ROUND => call("\\operatorname{round}"),
+                    _ => {
+                        let integrand = args
+                            .first()
+                            .map(|n| wrap(n, n.priority() < 3))
+                            .unwrap_or_default();
+                        format!(
+                            "\\int_{{{}}}^{{{}}} {integrand} \\, d{}",
+                            arg(2),
+                            arg(3),
+                            arg(1)
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    fn mathml(&self) -> String {
+        let wrap = |node: &Node, parentheses: bool| {
+            if parentheses {
+                return format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", node.mathml());
+            }
+            node.mathml()
+        };
+        let binary =
+            |lhs: String, op: &str, rhs: String| format!("<mrow>{lhs}<mo>{op}</mo>{rhs}</mrow>");
+
+        match self {
+            Node::Number(n) => number_mathml(n),
+            Node::Variable(name) => variable_mathml(name),
+            Node::Binary(lhs, op, rhs) => {
+                let (l, r) = (lhs.priority(), rhs.priority());
+                match op {
+                    TokenType::ASSIGN => binary(lhs.mathml(), "=", rhs.mathml()),
+                    TokenType::PLUS => binary(wrap(lhs, l < 2), "+", wrap(rhs, r < 2)),
+                    TokenType::MINUS => binary(wrap(lhs, l < 2), "-", wrap(rhs, r <= 2)),
+                    TokenType::PRODUCT => binary(wrap(lhs, l < 3), "⋅", wrap(rhs, r < 3)),
+                    TokenType::PERCENTAGE => binary(
+                        format!("<mrow>{}<mo>%</mo></mrow>", wrap(lhs, l < 3)),
+                        "⋅",
+                        wrap(rhs, r <= 3),
+                    ),
+                    TokenType::DIVIDE => format!("<mfrac>{}{}</mfrac>", lhs.mathml(), rhs.mathml()),
+                    TokenType::PLUSMINUS => binary(wrap(lhs, l < 4), "±", wrap(rhs, r <= 4)),
+                    _ => format!("<msup>{}{}</msup>", wrap(lhs, !lhs.is_atom()), rhs.mathml()),
+                }
+            }
+            Node::Factorial(n) => format!("<mrow>{}<mo>!</mo></mrow>", wrap(n, !n.is_atom())),
+            Node::Abs(n) => format!("<mrow><mo>|</mo>{}<mo>|</mo></mrow>", n.mathml()),
+            Node::Interval(lo, hi) => {
+                format!(
+                    "<mrow><mo>[</mo>{}<mo>,</mo>{}<mo>]</mo></mrow>",
+                    lo.mathml(),
+                    hi.mathml()
+                )
+            }
+            Node::Function(typ, args) => {
+                let arg = |i: usize| args.get(i).map(Node::mathml).unwrap_or_default();
+                let call = |name: String| match args.first() {
+                    Some(n @ (Node::Number(_) | Node::Variable(_))) if n.is_atom() => {
+                        format!("<mrow>{name}<mo>&#x2061;</mo>{}</mrow>", n.mathml())
+                    }
+                    _ => format!(
+                        "<mrow>{name}<mo>&#x2061;</mo><mrow><mo>(</mo>{}<mo>)</mo></mrow></mrow>",
+                        arg(0)
+                    ),
+                };
+                let name = |name: &str| format!("<mi>{name}</mi>");
+
+                match typ {
+                    TokenType::SQRT => format!("<msqrt>{}</msqrt>", arg(0)),
+                    TokenType::CBRT => format!("<mroot><mrow>{}</mrow><mn>3</mn></mroot>", arg(0)),
+                    TokenType::SIN => call(name("sin")),
+                    TokenType::COS => call(name("cos")),
+                    TokenType::TAN => call(name("tan")),
+                    TokenType::LOG => call(String::from("<msub><mi>log</mi><mn>10</mn></msub>")),
+                    TokenType::LN => call(name("ln")),
+                    TokenType::EXP => call(name("exp")),
+                    TokenType::FLOOR => format!("<mrow><mo>⌊</mo>{}<mo>⌋</mo></mrow>", arg(0)),
+                    TokenType::CEIL => format!("<mrow><mo>⌈</mo>{}<mo>⌉</mo></mrow>", arg(0)),
+                    TokenType::ROUND => call(name("round")),
+                    _ => {
+                        let integrand = args
+                            .first()
+                            .map(|n| wrap(n, n.priority() < 3))
+                            .unwrap_or_default();
+                        format!(
+                            "<mrow><msubsup><mo>∫</mo>{}{}</msubsup>{integrand}<mspace width=\"0.167em\"/><mi>d</mi>{}</mrow>",
+                            arg(2),
+                            arg(3),
+                            arg(1)
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Math markup of parsed expressions.
+//
+//  sqrt(x) / 2 + 5!  ──▶  \frac{\sqrt{x}}{2} + 5!
+//
+// Fails if the tokens aren't in a calculable order, like the ones of `2 +`.
+impl Sub {
+    pub fn to_latex(&self) -> Result<String, Error> {
+        Ok(Node::parse(&self.tokens)?.latex())
+    }
+
+    pub fn to_mathml(&self) -> Result<String, Error> {
+        Ok(math(&Node::parse(&self.tokens)?.mathml()))
+    }
+}
+
+// Math markup of results, with numbers in the default format of [Formatter].
+impl Value {
+    pub fn to_latex(&self) -> String {
+        let formatter = Formatter::new();
+        match self {
+            Value::Number(n) => number_latex(&formatter.format(*n)),
+            Value::Interval(_) => {
+                let (lo, hi) = bounds(&formatter.format_value(self));
+                format!(
+                    "\\left[{}, {}\\right]",
+                    number_latex(&lo),
+                    number_latex(&hi)
+                )
+            }
+            Value::Uncertain(u) => format!(
+                "{} \\pm {}",
+                number_latex(&formatter.format(u.value())),
+                number_latex(&formatter.format(u.sigma()))
+            ),
+        }
+    }
+
+    pub fn to_mathml(&self) -> String {
+        let formatter = Formatter::new();
+        let row = match self {
+            Value::Number(n) => number_mathml(&formatter.format(*n)),
+            Value::Interval(_) => {
+                let (lo, hi) = bounds(&formatter.format_value(self));
+                format!(
+                    "<mrow><mo>[</mo>{}<mo>,</mo>{}<mo>]</mo></mrow>",
+                    number_mathml(&lo),
+                    number_mathml(&hi)
+                )
+            }
+            Value::Uncertain(u) => format!(
+                "<mrow>{}<mo>±</mo>{}</mrow>",
+                number_mathml(&formatter.format(u.value())),
+                number_mathml(&formatter.format(u.sigma()))
+            ),
+        };
+
+        math(&row)
+    }
+}
+
+// Parses a practical subset of LaTeX math into the token tree of the same expression:
+//
+//  \frac{1}{2} + \sqrt{3}  ──▶  ((1) / (2)) + sqrt(3)  ──▶  [Lexer::lex]
+//
+// Supported are numbers, variables with subscripts and greek letters, `+ - * / ! =`,
+// `\cdot`, `\times`, `\div`, `\pm`, `\%`, `\frac`, `\sqrt` (and `\sqrt[n]`), powers,
+// groups and `\left`/`\right` delimiters, absolute values, floors, ceilings and the
+// functions of mate. Indexes of tokens point into the translated expression.
+pub fn from_latex(input: &str) -> Result<Sub, Error> {
+    let source = Translator::new(input).translate()?;
+    Lexer::lex(&source)
+}
+
+// Translates LaTeX into an expression of mate, character by character.
+struct Translator {
+    chars: Vec<char>,
+    i: usize,
+    // The amount of open `|`, to tell the opening ones from the closing ones.
+    bars: usize,
+}
+
+impl Translator {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            i: 0,
+            bars: 0,
+        }
+    }
+
+    fn translate(&mut self) -> Result<String, Error> {
+        let mut source = String::new();
+        while self.i < self.chars.len() {
+            let part = self.next()?;
+            source.push_str(&part);
+        }
+
+        Ok(source.split_whitespace().collect::<Vec<&str>>().join(" "))
+    }
+
+    // Translates the next piece of input.
+    fn next(&mut self) -> Result<String, Error> {
+        let c = self.chars[self.i];
+        self.i += 1;
+
+        let part = match c {
+            '\\' => return self.command(),
+            '{' => String::from("("),
+            '}' => String::from(")"),
+            '^' => {
+                // A power without a group takes a single character: `x^2`.
+                self.skip_spaces();
+                match self.chars.get(self.i) {
+                    Some('{') | Some('\\') | None => String::from(" ^ "),
+                    Some(c) => {
+                        let c = *c;
+                        self.i += 1;
+                        format!(" ^ ({c})")
+                    }
+                }
+            }
+            '_' => {
+                // Subscripts are a part of the name: `x_1` and `x_{12}` are `x_1` and `x_12`.
+                let group = self.group_text()?;
+                format!("_{group}")
+            }
+            '|' => self.bar(true),
+            '*' | '/' | '+' | '-' | '=' | '!' | ',' => format!(" {c} "),
+            c if c.is_alphanumeric()
+                || c.is_whitespace()
+                || matches!(c, '.' | '(' | ')' | '[' | ']') =>
+            {
+                c.to_string()
+            }
+            c => return Err(Error::invalid_latex(&format!("unexpected character `{c}`"))),
+        };
+
+        Ok(part)
+    }
+
+    fn command(&mut self) -> Result<String, Error> {
+        let start = self.i;
+        while self
+            .chars
+            .get(self.i)
+            .is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            self.i += 1;
+        }
+
+        // Commands of a single symbol: `\,`, `\%`, `\{`.
+        if self.i == start {
+            let symbol = self.chars.get(self.i).copied().unwrap_or(' ');
+            self.i += 1;
+            return match symbol {
+                ',' | ';' | ':' | '!' | ' ' => Ok(String::from(" ")),
+                '%' => Ok(String::from(" % ")),
+                '{' => Ok(String::from("(")),
+                '}' => Ok(String::from(")")),
+                '|' => Ok(self.bar(true)),
+                c => Err(Error::invalid_latex(&format!("unknown command `\\{c}`"))),
+            };
+        }
+
+        let name: String = self.chars[start..self.i].iter().collect();
+        let part = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.group()?;
+                let denominator = self.group()?;
+                format!(" (({numerator}) / ({denominator})) ")
+            }
+            "sqrt" => {
+                self.skip_spaces();
+                if self.chars.get(self.i) == Some(&'[') {
+                    self.i += 1;
+                    let degree = self.until(']')?;
+                    let radicand = self.group()?;
+                    match degree.trim() {
+                        "3" => format!(" cbrt({radicand}) "),
+                        _ => format!(" (({radicand}) ^ (1 / ({degree}))) "),
+                    }
+                } else {
+                    format!(" sqrt({}) ", self.group()?)
+                }
+            }
+            "cdot" | "times" | "ast" => String::from(" * "),
+            "div" => String::from(" / "),
+            "pm" => String::from(" ± "),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => {
+                self.skip_spaces();
+                match self.chars.get(self.i) {
+                    Some('|') => {
+                        self.i += 1;
+                        self.bar(name == "left")
+                    }
+                    Some('.') => {
+                        self.i += 1;
+                        String::new()
+                    }
+                    _ => String::new(),
+                }
+            }
+            "lvert" | "vert" if name == "lvert" || self.bars == 0 => self.bar(true),
+            "rvert" | "vert" => self.bar(false),
+            "lfloor" => String::from(" floor("),
+            "lceil" => String::from(" ceil("),
+            "rfloor" | "rceil" => String::from(") "),
+            "sin" | "cos" | "tan" | "ln" | "exp" => self.function(&name)?,
+            "log" => {
+                // Only the common logarithm is supported, with or without its base.
+                self.skip_spaces();
+                if self.chars.get(self.i) == Some(&'_') {
+                    self.i += 1;
+                    let base = self.group_text()?;
+                    if base.trim() != "10" {
+                        return Err(Error::invalid_latex(&format!(
+                            "unsupported logarithm base `{base}`"
+                        )));
+                    }
+                }
+                self.function("log")?
+            }
+            "operatorname" | "mathrm" | "mathit" | "text" => {
+                let text = self.group_text()?;
+                let text = text.trim().to_string();
+                match text.as_str() {
+                    "round" | "floor" | "ceil" | "sqrt" | "cbrt" | "sin" | "cos" | "tan"
+                    | "log" | "ln" | "exp" => self.function(&text)?,
+                    _ => format!(" {text} "),
+                }
+            }
+            "quad" | "qquad" => String::from(" "),
+            // Constants are read as numbers, not as the variables of their names.
+            "pi" => String::from(" π "),
+            "tau" => String::from(" τ "),
+            "infty" => String::from(" ∞ "),
+            _ => match GREEK.iter().find(|(greek, _)| *greek == name) {
+                Some((greek, _)) => format!(" {greek} "),
+                None => return Err(Error::invalid_latex(&format!("unknown command `\\{name}`"))),
+            },
+        };
+
+        Ok(part)
+    }
+
+    // Translates a function, its argument without parentheses is a single word: `\sin x`.
+    fn function(&mut self, name: &str) -> Result<String, Error> {
+        self.skip_spaces();
+        let start = self.i;
+        while self
+            .chars
+            .get(self.i)
+            .is_some_and(|c| c.is_alphanumeric() || *c == '.')
+        {
+            self.i += 1;
+        }
+
+        if self.i > start {
+            let word: String = self.chars[start..self.i].iter().collect();
+            return Ok(format!(" {name}({word}) "));
+        }
+
+        Ok(format!(" {name}"))
+    }
+
+    // Translates `|`, that's opening if it comes after an operator or at the start.
+    fn bar(&mut self, opening: bool) -> String {
+        if opening && self.bars > 0 && !self.after_operator() {
+            self.bars -= 1;
+            return String::from("] ");
+        }
+
+        if opening {
+            self.bars += 1;
+            return String::from(" [");
+        }
+
+        self.bars = self.bars.saturating_sub(1);
+        String::from("] ")
+    }
+
+    fn after_operator(&self) -> bool {
+        let before = self.chars[..self.i.saturating_sub(1)]
+            .iter()
+            .rev()
+            .find(|c| !c.is_whitespace());
+        matches!(
+            before,
+            None | Some('+' | '-' | '*' | '/' | '=' | '(' | '{' | '[' | ',' | '^')
+        )
+    }
+
+    // Translates the next group, or a single character if there's no group: `\frac12`.
+    fn group(&mut self) -> Result<String, Error> {
+        self.skip_spaces();
+        match self.chars.get(self.i) {
+            Some('{') => {
+                self.i += 1;
+                let mut source = String::new();
+                loop {
+                    match self.chars.get(self.i) {
+                        Some('}') => {
+                            self.i += 1;
+                            return Ok(source);
+                        }
+                        Some(_) => source.push_str(&self.next()?),
+                        None => return Err(Error::invalid_latex("missing `}`")),
+                    }
+                }
+            }
+            Some('\\') => {
+                self.i += 1;
+                self.command()
+            }
+            Some(c) => {
+                let c = *c;
+                self.i += 1;
+                Ok(c.to_string())
+            }
+            None => Err(Error::invalid_latex("missing an argument")),
+        }
+    }
+
+    // Takes the raw text of the next group, or a single character.
+    fn group_text(&mut self) -> Result<String, Error> {
+        self.skip_spaces();
+        match self.chars.get(self.i) {
+            Some('{') => {
+                self.i += 1;
+                self.until('}')
+            }
+            Some(c) => {
+                let c = *c;
+                self.i += 1;
+                Ok(c.to_string())
+            }
+            None => Err(Error::invalid_latex("missing an argument")),
+        }
+    }
+
+    // Translates the input until [end].
+    fn until(&mut self, end: char) -> Result<String, Error> {
+        let start = self.i;
+        while self.chars.get(self.i).is_some_and(|c| *c != end) {
+            self.i += 1;
+        }
+
+        if self.i >= self.chars.len() {
+            return Err(Error::invalid_latex(&format!("missing `{end}`")));
+        }
+
+        let text: String = self.chars[start..self.i].iter().collect();
+        self.i += 1;
+        Translator::new(&text).translate()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.chars.get(self.i).is_some_and(|c| c.is_whitespace()) {
+            self.i += 1;
+        }
+    }
+}
+
+fn math(row: &str) -> String {
+    format!("<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{row}</math>")
+}
+
+// Splits the formatted interval, `[lo, hi]` to its bounds.
+fn bounds(formatted: &str) -> (String, String) {
+    let inner = formatted.trim_start_matches('[').trim_end_matches(']');
+    let (lo, hi) = inner.split_once(", ").unwrap_or((inner, inner));
+    (lo.to_string(), hi.to_string())
+}
+
+// Splits a number in scientific notation to its mantissa and exponent: `1.5e-7`.
+fn scientific(n: &str) -> Option<(&str, &str)> {
+    n.split_once(['e', 'E'])
+}
+
+fn number_latex(n: &str) -> String {
+    match n {
+        "inf" | "∞" => return String::from("\\infty"),
+        "-inf" => return String::from("-\\infty"),
+        "π" => return String::from("\\pi"),
+        "τ" => return String::from("\\tau"),
+        "NaN" => return String::from("\\mathrm{NaN}"),
+        _ => {}
+    }
+
+    match scientific(n) {
+        Some((mantissa, exponent)) => format!(
+            "{mantissa} \\times 10^{{{}}}",
+            exponent.trim_start_matches('+')
+        ),
+        None => n.to_string(),
+    }
+}
+
+fn number_mathml(n: &str) -> String {
+    if let Some(n) = n.strip_prefix('-') {
+        return format!("<mrow><mo>-</mo>{}</mrow>", number_mathml(n));
+    }
+
+    match (n, scientific(n)) {
+        ("inf" | "∞", _) => String::from("<mi>∞</mi>"),
+        ("π" | "τ", _) => format!("<mi>{n}</mi>"),
+        ("NaN", _) => String::from("<mi>NaN</mi>"),
+        (_, Some((mantissa, exponent))) => format!(
+            "<mrow><mn>{mantissa}</mn><mo>×</mo><msup><mn>10</mn>{}</msup></mrow>",
+            number_mathml(exponent.trim_start_matches('+'))
+        ),
+        (n, None) => format!("<mn>{n}</mn>"),
+    }
+}
+
+// Variables are italic letters, longer names are upright: `x`, `\mathrm{radius}`, `x_{1}`.
+fn variable_latex(name: &str) -> String {
+    if let Some((base, index)) = name.split_once('_').filter(|(base, _)| !base.is_empty()) {
+        return format!("{}_{{{}}}", variable_latex(base), index.replace('_', "\\_"));
+    }
+
+    if GREEK.iter().any(|(greek, _)| *greek == name) {
+        return format!("\\{name}");
+    }
+
+    if name.chars().count() == 1 {
+        return name.to_string();
+    }
+
+    format!("\\mathrm{{{}}}", name.replace('_', "\\_"))
+}
+
+fn variable_mathml(name: &str) -> String {
+    if let Some((base, index)) = name.split_once('_').filter(|(base, _)| !base.is_empty()) {
+        return format!("<msub>{}<mi>{index}</mi></msub>", variable_mathml(base));
+    }
+
+    match GREEK.iter().find(|(greek, _)| *greek == name) {
+        Some((_, letter)) => format!("<mi>{letter}</mi>"),
+        None if name.chars().count() == 1 => format!("<mi>{name}</mi>"),
+        None => format!("<mi mathvariant=\"normal\">{name}</mi>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculator::Calculator, environment::Environment};
+
+    #[test]
+    fn to_latex() {
+        let test_data: Vec<(&str, &str)> = vec![
+            ("sqrt(x)", "\\sqrt{x}"),
+            ("[x - 1]", "\\lvert x - 1 \\rvert"),
+            ("[1, 2] * 3", "\\left[1, 2\\right] \\cdot 3"),
+            ("x ^ 2 + y ^ (1 + z)", "x^{2} + y^{1 + z}"),
+            ("(x + 1) ^ 2", "\\left(x + 1\\right)^{2}"),
+            ("2 ^ 3 ^ 2", "2^{3^{2}}"),
+            ("(2 ^ 3) ^ 2", "\\left(2^{3}\\right)^{2}"),
+            ("1 / 2 / 3", "\\frac{\\frac{1}{2}}{3}"),
+            ("(1 + 2) / (3 * 4)", "\\frac{1 + 2}{3 \\cdot 4}"),
+            ("2 * (3 + 4)", "2 \\cdot \\left(3 + 4\\right)"),
+            ("(2 * 3) + 4", "2 \\cdot 3 + 4"),
+            ("1 - (2 - 3)", "1 - \\left(2 - 3\\right)"),
+            ("1 - (2 * 3)", "1 - 2 \\cdot 3"),
+            ("2 - -3", "2 - \\left(-3\\right)"),
+            ("5! + (2 + 1)!", "5! + \\left(2 + 1\\right)!"),
+            ("50 % 10", "50\\% \\cdot 10"),
+            ("sin(x) + cos(x + 1)", "\\sin x + \\cos\\left(x + 1\\right)"),
+            ("sin(x) ^ 2", "\\left(\\sin x\\right)^{2}"),
+            (
+                "log(100) + ln(e) + exp(2)",
+                "\\log_{10} 100 + \\ln e + \\exp 2",
+            ),
+            (
+                "floor(x) + ceil(y) + round(z)",
+                "\\lfloor x \\rfloor + \\lceil y \\rceil + \\operatorname{round} z",
+            ),
+            (
+                "integrate(x ^ 2 + 1, x, 0, 1)",
+                "\\int_{0}^{1} \\left(x^{2} + 1\\right) \\, dx",
+            ),
+            ("radius * pi + x_1", "\\mathrm{radius} \\cdot \\pi + x_{1}"),
+            ("x = 2 ± 0.1", "x = 2 \\pm 0.1"),
+            ("x² × 2π − ∛8", "x^{2} \\cdot 2 \\cdot \\pi - \\sqrt[3]{8}"),
+            (
+                "sqrt(x) / 2 + [y - 1] ^ 2 + 5!",
+                "\\frac{\\sqrt{x}}{2} + \\lvert y - 1 \\rvert^{2} + 5!",
+            ),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            assert_eq!(
+                sub.to_latex(),
+                Ok(expected.to_string()),
+                "Failed for: {input}"
+            );
+        }
+
+        assert_eq!(
+            Lexer::lex("2 +").unwrap().to_latex(),
+            Err(Error::invalid_order())
+        );
+    }
+
+    #[test]
+    fn to_mathml() {
+        let test_data: Vec<(&str, &str)> = vec![
+            ("1 / 2", "<mfrac><mn>1</mn><mn>2</mn></mfrac>"),
+            ("sqrt(x) * 2", "<mrow><msqrt><mi>x</mi></msqrt><mo>⋅</mo><mn>2</mn></mrow>"),
+            (
+                "(x + 1) ^ 2",
+                "<msup><mrow><mo>(</mo><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mo>)</mo></mrow><mn>2</mn></msup>",
+            ),
+            ("[x]", "<mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>"),
+            ("sin(x)", "<mrow><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi></mrow>"),
+            ("5!", "<mrow><mn>5</mn><mo>!</mo></mrow>"),
+            ("τ ÷ ∞", "<mfrac><mi>τ</mi><mi>∞</mi></mfrac>"),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = Lexer::lex(input).unwrap();
+            assert_eq!(sub.to_mathml(), Ok(math(expected)), "Failed for: {input}");
+        }
+    }
+
+    #[test]
+    fn value_markup() {
+        let test_data: Vec<(Value, &str)> = vec![
+            (Value::Number(0.5), "0.5"),
+            (Value::Number(-1.5e21), "-1.5 \\times 10^{21}"),
+            (Value::Number(f64::INFINITY), "\\infty"),
+            (
+                Value::Interval(crate::interval::Interval::new(1.0, 2.5).unwrap()),
+                "\\left[1, 2.5\\right]",
+            ),
+            (
+                Value::Uncertain(crate::uncertain::Uncertain::new(12.3, 0.2).unwrap()),
+                "12.3 \\pm 0.2",
+            ),
+        ];
+
+        for (value, expected) in test_data {
+            assert_eq!(value.to_latex(), expected, "Failed for: {value}");
+        }
+
+        assert_eq!(
+            Value::Number(-2.0e-9).to_mathml(),
+            math("<mrow><mo>-</mo><mrow><mn>2</mn><mo>×</mo><msup><mn>10</mn><mrow><mo>-</mo><mn>9</mn></mrow></msup></mrow></mrow>")
+        );
+    }
+
+    #[test]
+    fn from_latex() {
+        let mut env = Environment::new();
+        env.set("x", 4.0);
+        env.set("x_1", 2.0);
+        env.set("alpha", 0.5);
+
+        let test_data: Vec<(&str, f64)> = vec![
+            ("\\frac{1}{2} + \\sqrt{9}", 3.5),
+            ("\\frac12", 0.5),
+            ("2 \\cdot 3 \\times 4 \\div 8", 3.0),
+            ("x^2 + x^{1 + 1}", 32.0),
+            ("\\sqrt[3]{27}", 3.0),
+            ("\\left(1 + 2\\right) \\cdot 3", 9.0),
+            ("|1 - x| + \\lvert 2 - x \\rvert", 5.0),
+            ("\\left| -3 \\right| \\cdot 2", 6.0),
+            ("\\lfloor 2.5 \\rfloor + \\lceil 2.5 \\rceil", 5.0),
+            ("\\sin 0 + \\cos(0) + \\log_{10} 100 + \\ln{1}", 3.0),
+            ("\\operatorname{round}(2.4)", 2.0),
+            ("x_1 + x_{1} + \\alpha", 4.5),
+            ("3! \\, + 50 \\% 10", 11.0),
+            ("\\frac{\\tau}{2 \\pi} + \\frac{1}{\\infty}", 1.0),
+        ];
+
+        for (input, expected) in test_data {
+            let sub = super::from_latex(input).unwrap_or_else(|e| panic!("{input}: {e}"));
+            let result = Calculator::calculate_with_env(sub, input, &mut env);
+            assert_eq!(result, Ok(expected), "Failed for: {input}");
+        }
+
+        // The rendered LaTeX is parsed back into an expression of the same result.
+        for input in [
+            "2 * (3 + 4) / [x - 10]",
+            "(x + 1) ^ 2 - sqrt(x) / 2",
+            "floor(x / 3) + 4!",
+        ] {
+            let latex = Lexer::lex(input).unwrap().to_latex().unwrap();
+            let sub = super::from_latex(&latex).unwrap();
+            let expected =
+                Calculator::calculate_with_env(Lexer::lex(input).unwrap(), input, &mut env);
+            assert_eq!(
+                Calculator::calculate_with_env(sub, &latex, &mut env),
+                expected,
+                "Failed for: {latex}"
+            );
+        }
+
+        assert_eq!(
+            super::from_latex("\\frac{1}{2} + \\unknown").err(),
+            Some(Error::invalid_latex("unknown command `\\unknown`"))
+        );
+        assert_eq!(
+            super::from_latex("\\frac{1}{2").err(),
+            Some(Error::invalid_latex("missing `}`"))
+        );
+    }
+}
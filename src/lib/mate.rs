@@ -4,7 +4,10 @@
 // that can be found in the LICENSE file.
 //
 
-use crate::{calculator::Calculator, errors::Error, lexer::Lexer};
+use crate::{
+    calculator::Calculator, environment::Environment, errors::Error, interval::Interval,
+    lexer::Lexer, limits::EvalLimits, uncertain::Uncertain, value::Value,
+};
 
 // A main structure that takes string input, parses it via [Lexer],
 // and calculates result via [Calculator].
@@ -18,4 +21,28 @@ impl Mate {
 
         Calculator::calculate(sub, input)
     }
+
+    // Takes an arithmetic expression with intervals, like `[9.8, 9.82] * 2.5 ± 0.1`,
+    // and calculates the interval that surely contains the result.
+    pub fn calculate_interval(input: &str) -> Result<Interval, Error> {
+        let sub = Lexer::lex(input)?;
+
+        Calculator::calculate_interval(sub, input, &mut Environment::new())
+    }
+
+    // Takes an arithmetic expression with uncertain values, like `(12.3 ± 0.2) * 2`,
+    // and calculates the result with its propagated standard uncertainty.
+    pub fn calculate_uncertain(input: &str) -> Result<Uncertain, Error> {
+        let sub = Lexer::lex(input)?;
+
+        Calculator::calculate_uncertain(sub, input, &mut Environment::new())
+    }
+
+    // Takes an untrusted arithmetic expression, and calculates it within the given [limits].
+    // Breaches of limits result errors with a dedicated [ErrorKind].
+    pub fn calculate_with_limits(input: &str, limits: &EvalLimits) -> Result<Value, Error> {
+        let sub = Lexer::lex_with_limits(input, limits)?;
+
+        Calculator::calculate_with_limits(sub, input, &mut Environment::new(), limits)
+    }
 }
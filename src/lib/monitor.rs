@@ -4,7 +4,11 @@
 // that can be found in the LICENSE file.
 //
 
-use crate::token::{Sub, SubMethod, Token, TokenType};
+use crate::{
+    span::Span,
+    token::{Sub, SubMethod, Token, TokenType},
+};
+use serde_json::{json, Value as Json};
 
 pub trait Monitor {
     // Converts the [&self] object to the
@@ -48,6 +52,7 @@ impl Monitor for TokenType {
             TokenType::NUMBER => "NUMBER",
             TokenType::IDENTIFIER => "IDENTIFIER",
             TokenType::ASSIGN => "ASSIGN",
+            TokenType::COMMA => "COMMA",
             TokenType::ILLEGAL => "ILLEGAL",
             TokenType::SUBEXP => "SUB-EXPRESSION",
             TokenType::LPAREN => "LEFT-PARENTHESES",
@@ -60,9 +65,11 @@ impl Monitor for TokenType {
             TokenType::PERCENTAGE => "PERCENTAGE",
             TokenType::POWER => "POWER",
             TokenType::FACTORIAL => "FACTORIAL",
+            TokenType::PLUSMINUS => "PLUS-MINUS",
             TokenType::LABS => "LEFT-ABS",
             TokenType::RABS => "RIGHT-ABS",
             TokenType::SQRT => "SQRT",
+            TokenType::CBRT => "CBRT",
             TokenType::SIN => "SIN",
             TokenType::COS => "COS",
             TokenType::TAN => "TAN",
@@ -72,6 +79,7 @@ impl Monitor for TokenType {
             TokenType::FLOOR => "FLOOR",
             TokenType::CEIL => "CEIL",
             TokenType::ROUND => "ROUND",
+            TokenType::INTEGRATE => "INTEGRATE",
         };
 
         String::from(data)
@@ -102,6 +110,132 @@ impl Monitor for SubMethod {
     }
 }
 
+// Exports of the whole token tree, to see how the lexer grouped an expression.
+// Each node is labelled with its type, literal, span and method of sub-expression.
+// Spans are byte ranges of input, the end is exclusive. The span of bracketed
+// sub-expression includes its brackets.
+//
+//  2 * (3 + 4)
+//
+//  PARENTHESES [0..11]
+//  ├─ NUMBER(2) [0..1]
+//  ├─ PRODUCT(*) [2..3]
+//  └─ SUB-EXPRESSION -> {PARENTHESES} [4..11]
+//     ├─ NUMBER(3) [5..6]
+//     ├─ PLUS(+) [7..8]
+//     └─ NUMBER(4) [9..10]
+pub fn to_tree(sub: &Sub) -> String {
+    let mut tree = format!("{}{}", sub.to_string(0), span_label(sub_span(sub)));
+    tree_lines(&sub.tokens, "", &mut tree);
+    tree
+}
+
+fn tree_lines(tokens: &[Token], indent: &str, tree: &mut String) {
+    for (i, token) in tokens.iter().enumerate() {
+        let last = i + 1 == tokens.len();
+        let (branch, next) = if last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+
+        let label = if token.is_sub_exp() {
+            format!("{} -> {{{}}}", token.typ.to_string(0), token.sub.to_string(0))
+        } else {
+            format!("{}({})", token.typ.to_string(0), token.literal)
+        };
+        tree.push_str(&format!("\n{indent}{branch}{label}{}", span_label(Some(token.span))));
+
+        if token.is_sub_exp() {
+            tree_lines(&token.sub.tokens, &format!("{indent}{next}"), tree);
+        }
+    }
+}
+
+fn span_label(span: Option<Span>) -> String {
+    span.map(|span| format!(" [{span}]")).unwrap_or_default()
+}
+
+// Exports the token tree as a Graphviz DOT graph, that keeps the order of tokens:
+//
+//  mate --tokens=dot "2 * (3 + 4)" | dot -Tsvg > tokens.svg
+pub fn to_dot(sub: &Sub) -> String {
+    let mut dot = String::from("digraph tokens {\n");
+    dot.push_str("  graph [ordering=out];\n");
+    dot.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+
+    let root = [sub.to_string(0)];
+    dot.push_str(&dot_node(0, &root, sub_span(sub)));
+
+    let mut next = 1;
+    dot_nodes(0, &sub.tokens, &mut next, &mut dot);
+    dot.push('}');
+    dot
+}
+
+fn dot_nodes(parent: usize, tokens: &[Token], next: &mut usize, dot: &mut String) {
+    for token in tokens.iter() {
+        let id = *next;
+        *next += 1;
+
+        let lines = if token.is_sub_exp() {
+            [token.typ.to_string(0), token.sub.to_string(0)]
+        } else {
+            [token.typ.to_string(0), token.literal.clone()]
+        };
+        dot.push_str(&dot_node(id, &lines, Some(token.span)));
+        dot.push_str(&format!("  n{parent} -> n{id};\n"));
+
+        if token.is_sub_exp() {
+            dot_nodes(id, &token.sub.tokens, next, dot);
+        }
+    }
+}
+
+fn dot_node(id: usize, lines: &[String], span: Option<Span>) -> String {
+    let mut label: Vec<String> = lines.iter().map(|l| l.replace('\\', "\\\\").replace('"', "\\\"")).collect();
+    if let Some(span) = span {
+        label.push(span.to_string());
+    }
+
+    format!("  n{id} [label=\"{}\"];\n", label.join("\\n"))
+}
+
+// Exports the token tree as JSON, in the same shape as the `tokens` method of [Server]:
+//
+//  { "method": "PAREN", "span": { "start": 0, "end": 5 }, "tokens": [
+//      { "type": "NUMBER", "literal": "2", "span": { "start": 0, "end": 1 } }, ... ] }
+pub fn to_json(sub: &Sub) -> Json {
+    json!({
+        "method": format!("{:?}", sub.method),
+        "span": span_json(sub_span(sub)),
+        "tokens": Json::Array(sub.tokens.iter().map(token_json).collect()),
+    })
+}
+
+pub(crate) fn token_json(token: &Token) -> Json {
+    let mut json = json!({
+        "type": format!("{:?}", token.typ),
+        "literal": token.literal,
+        "span": span_json(Some(token.span)),
+    });
+
+    if token.is_sub_exp() {
+        json["method"] = Json::String(format!("{:?}", token.sub.method));
+        json["tokens"] = Json::Array(token.sub.tokens.iter().map(token_json).collect());
+    }
+
+    json
+}
+
+pub(crate) fn span_json(span: Option<Span>) -> Json {
+    match span {
+        Some(span) => json!({ "start": span.start, "end": span.end }),
+        None => Json::Null,
+    }
+}
+
+// The root sub-expression isn't a token, so its span is calculated from its tokens.
+fn sub_span(sub: &Sub) -> Option<Span> {
+    sub.tokens.iter().map(|t| t.span).reduce(Span::join)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,11 +245,11 @@ mod tests {
     fn token_to_string() {
         let test_data: HashMap<String, String> = HashMap::from([
             (
-                Token::from(String::from("-25"), (0, 1)).to_string(0),
+                Token::from(String::from("-25"), Span::new(0, 2)).to_string(0),
                 String::from("NUMBER(-25)"),
             ),
             (
-                Token::from(String::from("/"), (0, 0)).to_string(0),
+                Token::from(String::from("/"), Span::new(0, 1)).to_string(0),
                 String::from("DIVIDE(/)"),
             ),
         ]);
@@ -178,4 +312,34 @@ mod tests {
             assert_eq!(s, expected);
         }
     }
+
+    #[test]
+    fn exports() {
+        let sub = crate::lexer::Lexer::lex("2 + [3 * x]").unwrap();
+
+        assert_eq!(
+            to_tree(&sub),
+            [
+                "PARENTHESES [0..11]",
+                "├─ NUMBER(2) [0..1]",
+                "├─ PLUS(+) [2..3]",
+                "└─ SUB-EXPRESSION -> {ABSOLUTE-VALUE} [4..11]",
+                "   ├─ NUMBER(3) [5..6]",
+                "   ├─ PRODUCT(*) [7..8]",
+                "   └─ IDENTIFIER(x) [9..10]",
+            ]
+            .join("\n")
+        );
+
+        let dot = to_dot(&sub);
+        assert!(dot.starts_with("digraph tokens {\n"));
+        assert!(dot.contains("  n3 [label=\"SUB-EXPRESSION\\nABSOLUTE-VALUE\\n4..11\"];\n  n0 -> n3;\n"));
+        assert!(dot.contains("  n6 [label=\"IDENTIFIER\\nx\\n9..10\"];\n  n3 -> n6;\n"));
+        assert!(dot.ends_with('}'));
+
+        let json = to_json(&sub);
+        assert_eq!(json["span"], json!({ "start": 0, "end": 11 }));
+        assert_eq!(json["tokens"][2]["method"], "ABS");
+        assert_eq!(json["tokens"][2]["tokens"][2]["literal"], "x");
+    }
 }
@@ -0,0 +1,496 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator, environment::Environment, errors::Error, interval::Interval,
+    lexer::Lexer, uncertain::Uncertain, value::Value,
+};
+use serde_json::{json, Map, Value as Json};
+use std::{fs, path::Path};
+
+// Saving and loading of [Environment].
+//
+// The text format is a list of mate assignments, one per line, so it can be edited by hand
+// and re-evaluated as is. Numbers are written in their shortest exact form, so values
+// round-trip exactly. Non-finite numbers are written as `inf`, `-inf` and `nan`.
+//
+//  ╭───────────────────────────╮
+//  │ # mate environment        │
+//  │ g = 9.81                  │
+//  │ range = [9.8, 9.82]       │
+//  │ x = 12.3 ± 0.2            │
+//  │ limit = inf               │
+//  │ y = x * 2                 │ ◀── formulas of reactive mode
+//  ╰───────────────────────────╯
+//
+// Lines, that aren't plain values, are calculated like in REPL, with the variables above them.
+// Uncertain values are saved by their value and sigma, so correlations between them are lost.
+//
+// The JSON form holds the same data:
+//
+//  { "variables": { "g": 9.81, "range": { "interval": [9.8, 9.82] },
+//                   "x": { "value": 12.3, "sigma": 0.2 }, "limit": "inf" },
+//    "formulas": { "y": "x * 2" } }
+//
+impl<'p> Environment<'p> {
+    // Serializes the environment to the text format.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# mate environment\n");
+
+        for (name, value) in self.sorted_inputs() {
+            text.push_str(&format!("{name} = {}\n", format_value(value)));
+        }
+
+        for name in self.formula_order() {
+            if let Some(formula) = self.formula(&name) {
+                text.push_str(&format!("{name} = {}\n", formula.expression()));
+            }
+        }
+
+        text
+    }
+
+    // Loads variables from the text format, on top of existing ones.
+    pub fn load_text(&mut self, text: &str) -> Result<(), Error> {
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            self.load_line(line)
+                .map_err(|e| Error::invalid_save_file(Some(i + 1), &e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    // Serializes the environment to the JSON form.
+    pub fn to_json(&self) -> String {
+        let mut variables = Map::new();
+        for (name, value) in self.sorted_inputs() {
+            let value = match value {
+                Value::Number(n) => json_number(*n),
+                Value::Interval(i) => {
+                    json!({ "interval": [json_number(i.lo()), json_number(i.hi())] })
+                }
+                Value::Uncertain(u) => {
+                    json!({ "value": json_number(u.value()), "sigma": json_number(u.sigma()) })
+                }
+            };
+            variables.insert(name.clone(), value);
+        }
+
+        let mut formulas = Map::new();
+        for name in self.formula_order() {
+            if let Some(formula) = self.formula(&name) {
+                formulas.insert(name.clone(), Json::String(formula.expression().to_string()));
+            }
+        }
+
+        let json = json!({ "variables": variables, "formulas": formulas });
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    }
+
+    // Loads variables from the JSON form, on top of existing ones.
+    pub fn load_json(&mut self, text: &str) -> Result<(), Error> {
+        let json: Json = serde_json::from_str(text)
+            .map_err(|e| Error::invalid_save_file(Some(e.line()), &e.to_string()))?;
+
+        if let Some(variables) = json.get("variables").and_then(Json::as_object) {
+            for (name, value) in variables {
+                let value = parse_json_value(value).ok_or_else(|| {
+                    Error::invalid_save_file(None, &format!("invalid value of variable '{name}'"))
+                })?;
+                self.set_value(name, value);
+            }
+        }
+
+        // Formulas are stored in the order of recomputation.
+        if let Some(formulas) = json.get("formulas").and_then(Json::as_object) {
+            let mut pending: Vec<(&String, &str)> = formulas
+                .iter()
+                .filter_map(|(name, f)| f.as_str().map(|f| (name, f)))
+                .collect();
+
+            while !pending.is_empty() {
+                let before = pending.len();
+                let mut failed: Option<Error> = None;
+
+                pending.retain(|(name, expression)| {
+                    match self.load_line(&format!("{name} = {expression}")) {
+                        Ok(()) => false,
+                        Err(e) => {
+                            failed = Some(e);
+                            true
+                        }
+                    }
+                });
+
+                if pending.len() == before {
+                    let e = failed.unwrap_or_else(Error::empty_input);
+                    return Err(Error::invalid_save_file(None, &e.to_string()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Saves the environment to a file.
+    // Files with the `.json` extension are written in the JSON form, others in the text format.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let content = if is_json(path) {
+            self.to_json() + "\n"
+        } else {
+            self.to_text()
+        };
+
+        fs::write(path, content).map_err(|e| Error::io(path, &e))
+    }
+
+    // Loads variables from a file, see [save].
+    pub fn load(&mut self, path: &Path) -> Result<(), Error> {
+        let content = fs::read_to_string(path).map_err(|e| Error::io(path, &e))?;
+        if is_json(path) {
+            self.load_json(&content)
+        } else {
+            self.load_text(&content)
+        }
+    }
+
+    // Loads a single assignment of the text format.
+    fn load_line(&mut self, line: &str) -> Result<(), Error> {
+        let literal = line.split_once('=').and_then(|(name, value)| {
+            let name = name.trim();
+            let is_name = name
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+            if !is_name {
+                return None;
+            }
+
+            parse_value(value.trim()).map(|v| (name, v))
+        });
+
+        if let Some((name, value)) = literal {
+            self.forget(name);
+            self.set_value(name, value);
+            return Ok(());
+        }
+
+        let sub = Lexer::lex(line)?;
+        Calculator::calculate_value(sub, line, self).map(|_| ())
+    }
+
+    // The variables, that aren't defined by formulas, sorted by name.
+    fn sorted_inputs(&self) -> Vec<(&String, &Value)> {
+        let mut inputs: Vec<(&String, &Value)> = self
+            .names()
+            .into_iter()
+            .filter(|name| self.formula(name).is_none())
+            .filter_map(|name| self.get_value(name).map(|v| (name, v)))
+            .collect();
+
+        inputs.sort_by(|a, b| a.0.cmp(b.0));
+        inputs
+    }
+
+    // The variables, that are defined by formulas, in the order of recomputation.
+    fn formula_order(&self) -> Vec<String> {
+        let mut order: Vec<String> = self
+            .names()
+            .into_iter()
+            .filter(|name| self.formula(name).is_some())
+            .cloned()
+            .collect();
+        order.sort();
+
+        // Each formula comes after the formulas, that it reads.
+        let mut sorted: Vec<String> = Vec::new();
+        while sorted.len() < order.len() {
+            let before = sorted.len();
+            for name in order.iter() {
+                let ready = !sorted.contains(name)
+                    && self.formula(name).is_some_and(|f| {
+                        f.dependencies()
+                            .iter()
+                            .all(|d| self.formula(d).is_none() || sorted.contains(d))
+                    });
+
+                if ready {
+                    sorted.push(name.clone());
+                }
+            }
+
+            if sorted.len() == before {
+                break;
+            }
+        }
+
+        sorted
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+// Formats a value in mate syntax, that parses back to exactly the same value.
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Number(n) => format_number(*n),
+        Value::Interval(i) => format!("[{}, {}]", format_number(i.lo()), format_number(i.hi())),
+        Value::Uncertain(u) => format!(
+            "{} ± {}",
+            format_number(u.value()),
+            format_number(u.sigma())
+        ),
+    }
+}
+
+// The [Display] of f64 is the shortest exact form, except non-finite numbers.
+fn format_number(n: f64) -> String {
+    match n {
+        n if n.is_nan() => String::from("nan"),
+        n if n == f64::INFINITY => String::from("inf"),
+        n if n == f64::NEG_INFINITY => String::from("-inf"),
+        n => n.to_string(),
+    }
+}
+
+// Parses a plain value of the text format: a number, an interval or an uncertain value.
+fn parse_value(text: &str) -> Option<Value> {
+    if let Some(bounds) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        let (lo, hi) = bounds.split_once(',')?;
+        let interval = Interval::new(parse_number(lo)?, parse_number(hi)?).ok()?;
+        return Some(Value::Interval(interval));
+    }
+
+    if let Some((value, sigma)) = text.split_once('±') {
+        let uncertain = Uncertain::new(parse_number(value)?, parse_number(sigma)?).ok()?;
+        return Some(Value::Uncertain(uncertain));
+    }
+
+    parse_number(text).map(Value::Number)
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+
+    // Rust also accepts `infinity` and exponents, which aren't a part of the format.
+    let is_number = text == "inf"
+        || text == "-inf"
+        || text == "nan"
+        || text
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-');
+
+    if !is_number {
+        return None;
+    }
+
+    text.parse::<f64>().ok()
+}
+
+// JSON has no non-finite numbers, so they are written as strings.
+fn json_number(n: f64) -> Json {
+    if n.is_finite() {
+        json!(n)
+    } else {
+        Json::String(format_number(n))
+    }
+}
+
+fn parse_json_number(json: &Json) -> Option<f64> {
+    match json {
+        Json::Number(n) => n.as_f64(),
+        Json::String(s) => parse_number(s).filter(|n| !n.is_finite()),
+        _ => None,
+    }
+}
+
+fn parse_json_value(json: &Json) -> Option<Value> {
+    if let Some(bounds) = json.get("interval").and_then(Json::as_array) {
+        let (lo, hi) = match bounds.as_slice() {
+            [lo, hi] => (parse_json_number(lo)?, parse_json_number(hi)?),
+            _ => return None,
+        };
+        return Interval::new(lo, hi).ok().map(Value::Interval);
+    }
+
+    if let (Some(value), Some(sigma)) = (json.get("value"), json.get("sigma")) {
+        let uncertain = Uncertain::new(parse_json_number(value)?, parse_json_number(sigma)?);
+        return uncertain.ok().map(Value::Uncertain);
+    }
+
+    parse_json_number(json).map(Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculate(input: &str, env: &mut Environment) {
+        Calculator::calculate_value(Lexer::lex(input).unwrap(), input, env).unwrap();
+    }
+
+    fn sample() -> Environment<'static> {
+        let mut env = Environment::reactive();
+        env.set("a", 0.1 + 0.2);
+        env.set("b", -0.0);
+        env.set("c", f64::INFINITY);
+        env.set("d", f64::NEG_INFINITY);
+        env.set("e", 1e-300);
+        env.set("f", f64::MAX);
+        env.set_value(
+            "g",
+            Value::Interval(Interval::new(f64::NEG_INFINITY, 0.5).unwrap()),
+        );
+        env.set_value("h", Value::Uncertain(Uncertain::new(12.3, 0.2).unwrap()));
+        calculate("y = a * 2", &mut env);
+        calculate("z = y + e", &mut env);
+        env
+    }
+
+    fn assert_same(loaded: &Environment, saved: &Environment) {
+        let mut names = saved.names();
+        names.sort();
+        let mut loaded_names = loaded.names();
+        loaded_names.sort();
+        assert_eq!(loaded_names, names);
+
+        for name in names {
+            let (l, s) = (
+                loaded.get_value(name).unwrap(),
+                saved.get_value(name).unwrap(),
+            );
+            match (l, s) {
+                (Value::Number(l), Value::Number(s)) => {
+                    assert_eq!(l.to_bits(), s.to_bits(), "Failed for: {name}")
+                }
+                (Value::Interval(l), Value::Interval(s)) => assert_eq!(l, s, "Failed for: {name}"),
+                (Value::Uncertain(l), Value::Uncertain(s)) => {
+                    assert_eq!(
+                        l.value().to_bits(),
+                        s.value().to_bits(),
+                        "Failed for: {name}"
+                    );
+                    assert_eq!(
+                        l.sigma().to_bits(),
+                        s.sigma().to_bits(),
+                        "Failed for: {name}"
+                    );
+                }
+                _ => panic!("Failed for: {name}"),
+            }
+
+            let expression =
+                |env: &Environment| env.formula(name).map(|f| f.expression().to_string());
+            assert_eq!(expression(loaded), expression(saved), "Failed for: {name}");
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let saved = sample();
+        let text = saved.to_text();
+        assert!(text.contains("b = -0\n"));
+        assert!(text.contains("c = inf\n"));
+        assert!(text.contains("g = [-inf, 0.5]\n"));
+        assert!(text.contains("h = 12.3 ± 0.2\n"));
+        assert!(text.ends_with("y = a * 2\nz = y + e\n"));
+
+        let mut loaded = Environment::reactive();
+        loaded.load_text(&text).unwrap();
+        assert_same(&loaded, &saved);
+
+        // Formulas are still reactive after loading.
+        loaded.update("a", 1.0).unwrap();
+        assert_eq!(loaded.get("y"), Some(2.0));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let saved = sample();
+        let json = saved.to_json();
+
+        let mut loaded = Environment::reactive();
+        loaded.load_json(&json).unwrap();
+        assert_same(&loaded, &saved);
+    }
+
+    #[test]
+    fn random_round_trip() {
+        // Numbers with all digits in use, which are easy to be read back off by one bit.
+        let mut saved = Environment::new();
+        saved.set("known", 2.291712365432881e-9);
+
+        let mut bits: u64 = 0x9E37_79B9_7F4A_7C15;
+        for i in 0..5_000 {
+            bits ^= bits << 13;
+            bits ^= bits >> 7;
+            bits ^= bits << 17;
+
+            let n = f64::from_bits(bits);
+            if n.is_finite() {
+                saved.set(&format!("n{i}"), n);
+            }
+        }
+
+        let mut loaded = Environment::new();
+        loaded.load_json(&saved.to_json()).unwrap();
+        assert_same(&loaded, &saved);
+
+        let mut loaded = Environment::new();
+        loaded.load_text(&saved.to_text()).unwrap();
+        assert_same(&loaded, &saved);
+    }
+
+    #[test]
+    fn load_text() {
+        let text = "
+            # constants
+            g = 9.81
+            two_g = g * 2   # calculated, like in REPL
+
+            r = sqrt(two_g)
+        ";
+
+        let mut env = Environment::new();
+        env.load_text(text).unwrap();
+        assert_eq!(env.get("g"), Some(9.81));
+        assert_eq!(env.get("two_g"), Some(19.62));
+        assert!(env.exists("r"));
+    }
+
+    #[test]
+    fn load_errors() {
+        let test_data: Vec<(&str, usize)> = vec![
+            ("x = 1\ny = 2 +", 2),
+            ("x = 1\n\n\nx = [2, 1]", 4),
+            ("x = unknown", 1),
+        ];
+
+        for (text, line) in test_data {
+            let error = Environment::new().load_text(text).unwrap_err();
+            let expected = format!("error: invalid save file, at line {line}");
+            assert!(
+                error.to_string().starts_with(&expected),
+                "Failed for: {text}"
+            );
+        }
+
+        assert!(Environment::new()
+            .load_json("{ \"variables\": { \"x\": true } }")
+            .is_err());
+        assert!(Environment::new().load_json("not json").is_err());
+    }
+}
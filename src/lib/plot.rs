@@ -0,0 +1,412 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator,
+    environment::Environment,
+    errors::Error,
+    formatter::Formatter,
+    lexer::Lexer,
+    table::{Sample, Samples},
+};
+
+// The characters that a plot is drawn with.
+//
+// - [Braille] draws 2x4 dots in each character cell, for a smoother curve.
+// - [Ascii] draws a single `*` in each cell, for terminals without Unicode.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PlotStyle {
+    #[default]
+    Braille,
+    Ascii,
+}
+
+impl PlotStyle {
+    // The amount of dots in a single character cell, horizontally and vertically.
+    fn dots(&self) -> (usize, usize) {
+        match self {
+            PlotStyle::Braille => (2, 4),
+            PlotStyle::Ascii => (1, 1),
+        }
+    }
+}
+
+// The chart of a single variable expression, to be shown in a terminal.
+//
+// The expression is sampled through [Calculator], with the variable bound in a child scope
+// of the environment, once for each column of dots. Points where the expression fails,
+// like `ln(x)` for non-positive `x`, are left as gaps, and jumps over half of the height
+// are treated as discontinuities, instead of being connected.
+//
+//  plot sin(x) * x from -10 to 10
+//
+//  7.907 ┤  ⢀⡞⠹⡄            │            ⡼⠙⣆
+//        │  ⡼  ⢹⡀           │           ⣸⠁ ⠸⡄
+//        │ ⢰⠃   ⢧      ⢀⣀⣀  │ ⢀⣀⣀      ⢠⠇   ⢳
+//      0 ┼─⡼────⠘⡆────⡴⠋─⠈⠙⠲⠴⠚⠉─⠈⠳⡄────⡞────⠸⡄
+//        │⢠⠇     ⠹⡄ ⢀⡼⠁     │     ⠹⣄  ⡼⠁     ⢧
+//  -5.44 ┤⣸       ⠹⠤⠞       │      ⠘⠦⠼⠁      ⢸
+//        └────────────────────────────────────
+//        -10                                10
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plot {
+    expression: String,
+    variable: String,
+    from: f64,
+    to: f64,
+    width: usize,
+    height: usize,
+    style: PlotStyle,
+}
+
+impl Plot {
+    // Creates a plot of [expression] over `x`, from [from] to [to].
+    pub fn new(expression: &str, from: f64, to: f64) -> Self {
+        Self {
+            expression: expression.trim().to_string(),
+            variable: String::from("x"),
+            from,
+            to,
+            width: 80,
+            height: 20,
+            style: PlotStyle::Braille,
+        }
+    }
+
+    // Parses the plot command, without the leading `plot`: `sin(x) * x from -10 to 10`.
+    // Both bounds can be expressions, that are calculated in [env].
+    pub fn parse(command: &str, env: &Environment) -> Result<Self, Error> {
+        let usage = "plot EXPRESSION from A to B";
+        let lowercase = command.to_ascii_lowercase();
+
+        let from_at = lowercase
+            .rfind(" from ")
+            .ok_or_else(|| Error::invalid_command(usage))?;
+        let to_at = lowercase[from_at..]
+            .find(" to ")
+            .map(|i| from_at + i)
+            .ok_or_else(|| Error::invalid_command(usage))?;
+
+        let expression = command[..from_at].trim();
+        if expression.is_empty() {
+            return Err(Error::invalid_command(usage));
+        }
+
+        let from = Plot::bound(&command[from_at + 6..to_at], env, usage)?;
+        let to = Plot::bound(&command[to_at + 4..], env, usage)?;
+        Ok(Plot::new(expression, from, to))
+    }
+
+    // Sets the variable of expression, `x` by default.
+    pub fn with_variable(mut self, variable: &str) -> Self {
+        self.variable = variable.to_string();
+        self
+    }
+
+    // Sets the size of the whole chart in characters, including its labels.
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_style(mut self, style: PlotStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn range(&self) -> (f64, f64) {
+        (self.from, self.to)
+    }
+
+    // Samples the expression at [count] evenly spaced points of the range.
+    // Each point holds the result, or the error of calculation at that point.
+    pub fn samples(&self, count: usize, env: &Environment) -> Result<Vec<Sample>, Error> {
+        if !(self.from.is_finite() && self.to.is_finite() && self.from < self.to) {
+            return Err(Error::invalid_range(self.from, self.to));
+        }
+
+        let count = count.max(2);
+        let step = (self.to - self.from) / (count - 1) as f64;
+        let samples = Samples::new(
+            &self.expression,
+            &self.variable,
+            self.from,
+            step,
+            count,
+            env,
+        )?
+        .map(|(x, y)| {
+            let y = y.and_then(|y| {
+                if y.is_finite() {
+                    Ok(y)
+                } else {
+                    Err(Error::non_finite_result(x))
+                }
+            });
+            (x, y)
+        })
+        .collect();
+
+        Ok(samples)
+    }
+
+    // Draws the chart. Fails if the expression can't be lexed, or can't be calculated
+    // at any point of the range.
+    pub fn render(&self, env: &Environment) -> Result<String, Error> {
+        let (dx, dy) = self.style.dots();
+        // Labels are rounded to 4 significant digits, and to zero if it's tiny for the range.
+        let label = |n: f64, span: f64| {
+            let n = if n.abs() < span * 1e-3 { 0.0 } else { n };
+            Formatter::new().format(format!("{n:.3e}").parse().unwrap_or(n))
+        };
+
+        // The width of labels is known only after sampling, so a typical one is reserved.
+        let columns = self.width.saturating_sub(10).max(10);
+        let rows = self.height.saturating_sub(2).max(3);
+        let samples = self.samples(columns * dx, env)?;
+
+        let values: Vec<f64> = samples
+            .iter()
+            .filter_map(|(_, y)| y.as_ref().ok().copied())
+            .collect();
+        if values.is_empty() {
+            return match samples.into_iter().next() {
+                Some((_, Err(e))) => Err(e),
+                _ => Err(Error::invalid_range(self.from, self.to)),
+            };
+        }
+
+        let mut min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let mut max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if min == max {
+            min -= 1.0;
+            max += 1.0;
+        }
+
+        // Maps a value to the row of dots, the top one is zero.
+        let height = rows * dy;
+        let dot_row = |y: f64| (((max - y) / (max - min)) * (height - 1) as f64).round() as usize;
+
+        let mut dots = vec![vec![false; columns * dx]; height];
+        let mut previous: Option<usize> = None;
+        for (column, (_, y)) in samples.iter().enumerate() {
+            let row = match y {
+                Ok(y) => dot_row(*y),
+                Err(_) => {
+                    previous = None;
+                    continue;
+                }
+            };
+
+            // Neighbour points are connected, unless the jump looks like a discontinuity.
+            let (top, bottom) = match previous {
+                Some(p) if p.abs_diff(row) <= height / 2 => (p.min(row), p.max(row)),
+                _ => (row, row),
+            };
+            for line in dots.iter_mut().take(bottom + 1).skip(top) {
+                line[column] = true;
+            }
+            previous = Some(row);
+        }
+
+        // The axes cross at zero, if it's inside of the ranges.
+        let zero_row = (min <= 0.0 && 0.0 <= max).then(|| dot_row(0.0) / dy);
+        let zero_column = (self.from <= 0.0 && 0.0 <= self.to).then(|| {
+            let at = -self.from / (self.to - self.from) * (columns * dx - 1) as f64;
+            at.round() as usize / dx
+        });
+
+        let mut labels: Vec<String> = vec![String::new(); rows];
+        labels[0] = label(max, max - min);
+        labels[rows - 1] = label(min, max - min);
+        if let Some(r) = zero_row.filter(|r| *r > 0 && *r < rows - 1) {
+            labels[r] = String::from("0");
+        }
+        let margin = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        let ascii = self.style == PlotStyle::Ascii;
+        let mut chart = String::new();
+        for (r, row_label) in labels.iter().enumerate() {
+            let on_axis = Some(r) == zero_row;
+            let border = match (row_label.is_empty(), on_axis, ascii) {
+                (false, true, false) => '┼',
+                (false, false, false) => '┤',
+                (true, _, false) => '│',
+                (false, _, true) => '+',
+                (true, _, true) => '|',
+            };
+            chart.push_str(&format!("{row_label:>margin$} {border}"));
+
+            for c in 0..columns {
+                let cell = self.cell(&dots, r, c);
+                let axis = match (on_axis, Some(c) == zero_column, ascii) {
+                    _ if cell.is_some() => None,
+                    (true, true, false) => Some('┼'),
+                    (true, false, false) => Some('─'),
+                    (false, true, false) => Some('│'),
+                    (true, true, true) => Some('+'),
+                    (true, false, true) => Some('-'),
+                    (false, true, true) => Some('|'),
+                    (false, false, _) => Some(' '),
+                };
+                chart.push(cell.or(axis).unwrap_or(' '));
+            }
+            chart.truncate(chart.trim_end().len());
+            chart.push('\n');
+        }
+
+        let (corner, line) = if ascii { ('+', "-") } else { ('└', "─") };
+        chart.push_str(&format!(
+            "{:margin$} {corner}{}\n",
+            "",
+            line.repeat(columns)
+        ));
+
+        let span = self.to - self.from;
+        let (from, to) = (label(self.from, span), label(self.to, span));
+        let gap = (columns + 1)
+            .saturating_sub(from.chars().count() + to.chars().count())
+            .max(1);
+        chart.push_str(&format!("{:margin$} {from}{}{to}", "", " ".repeat(gap)));
+
+        Ok(chart)
+    }
+
+    // The character of a cell, if any of its dots is drawn.
+    fn cell(&self, dots: &[Vec<bool>], row: usize, column: usize) -> Option<char> {
+        let (dx, dy) = self.style.dots();
+        if self.style == PlotStyle::Ascii {
+            return dots[row][column].then_some('*');
+        }
+
+        // The bits of braille dots, by their row and column in the cell.
+        const BITS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+        let mut bits: u32 = 0;
+        for (y, bit_row) in BITS.iter().enumerate().take(dy) {
+            for (x, bit) in bit_row.iter().enumerate().take(dx) {
+                if dots[row * dy + y][column * dx + x] {
+                    bits |= bit;
+                }
+            }
+        }
+
+        if bits == 0 {
+            return None;
+        }
+
+        char::from_u32(0x2800 + bits)
+    }
+
+    // Calculates a bound of range in a command, that's shared with tables.
+    pub(crate) fn bound(text: &str, env: &Environment, usage: &str) -> Result<f64, Error> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(Error::invalid_command(usage));
+        }
+
+        let mut scope = env.child();
+        Calculator::calculate_with_env(Lexer::lex(text)?, text, &mut scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let mut env = Environment::new();
+        env.set("r", 2.0);
+
+        type Parsed<'a> = Result<(&'a str, f64, f64), Error>;
+
+        let test_data: Vec<(&str, Parsed)> = vec![
+            ("sin(x) * x from -10 to 10", Ok(("sin(x) * x", -10.0, 10.0))),
+            ("x ^ r FROM r * -1 TO r", Ok(("x ^ r", -2.0, 2.0))),
+            (
+                "x from 0",
+                Err(Error::invalid_command("plot EXPRESSION from A to B")),
+            ),
+            (
+                "x to 10",
+                Err(Error::invalid_command("plot EXPRESSION from A to B")),
+            ),
+            (
+                " from 0 to 1",
+                Err(Error::invalid_command("plot EXPRESSION from A to B")),
+            ),
+        ];
+
+        for (command, expected) in test_data {
+            let plot = Plot::parse(command, &env);
+            let plot = plot
+                .as_ref()
+                .map(|p| (p.expression(), p.range().0, p.range().1));
+            assert_eq!(plot, expected.as_ref().map(|e| *e), "Failed for: {command}");
+        }
+    }
+
+    #[test]
+    fn samples() {
+        let env = Environment::new();
+        let samples = Plot::new("ln(x)", -1.0, 1.0).samples(3, &env).unwrap();
+
+        assert_eq!(samples.len(), 3);
+        assert!(samples[0].1.is_err());
+        assert!(samples[1].1.is_err());
+        assert_eq!(samples[2], (1.0, Ok(0.0)));
+
+        assert_eq!(
+            Plot::new("x", 1.0, 1.0).samples(3, &env),
+            Err(Error::invalid_range(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn render() {
+        let env = Environment::new();
+
+        let chart = Plot::new("x", -1.0, 1.0)
+            .with_size(16, 5)
+            .with_style(PlotStyle::Ascii)
+            .render(&env)
+            .unwrap();
+        assert_eq!(
+            chart,
+            [
+                " 1 +     | ***",
+                " 0 +---*****--",
+                "-1 +**** |",
+                "   +----------",
+                "   -1        1",
+            ]
+            .join("\n")
+        );
+
+        // Gaps are left where the expression isn't defined.
+        let chart = Plot::new("ln(x)", -1.0, 1.0)
+            .with_size(30, 6)
+            .render(&env)
+            .unwrap();
+        for line in chart.lines().take(4) {
+            let left: String = line.chars().skip(8).take(10).collect();
+            assert!(!left.chars().any(|c| ('⠁'..='⣿').contains(&c)), "{chart}");
+            assert!(
+                line.chars().skip(18).any(|c| ('⠁'..='⣿').contains(&c)),
+                "{chart}"
+            );
+        }
+
+        // An expression that fails everywhere results its error.
+        assert!(Plot::new("y", 0.0, 1.0).render(&env).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    analysis::{self, Severity},
+    calculator::Calculator,
+    environment::Environment,
+    errors::Error,
+    lexer::Lexer,
+    monitor::{span_json, token_json},
+    value::Value,
+};
+use serde_json::{json, Map, Value as Json};
+use std::io::{self, BufRead, Write};
+
+// The codes of JSON-RPC 2.0 errors.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// The code of calculation errors, from the range of server errors.
+const CALCULATION_ERROR: i64 = -32000;
+
+// A line-delimited JSON-RPC 2.0 server, that calculates in a session-scoped [Environment].
+// Each line of input is a single request, and each response is written as a single line.
+//
+//  ╭───────────────┬────────────────────────────┬─────────────────────────────────────────╮
+//  │ method        │ params                     │ result                                  │
+//  ├───────────────┼────────────────────────────┼─────────────────────────────────────────┤
+//  │ evaluate      │ { "expression": "x * 2" }  │ { "value": "10", "kind": "number",      │
+//  │               │                            │   "number": 10 }                        │
+//  │ tokens        │ { "expression": "2 + 2" }  │ [{ "type": "NUMBER", "literal": "2",    │
+//  │               │                            │    "span": { "start": 0, "end": 1 } }]  │
+//  │ check         │ { "expression": "(x $" }   │ [{ "severity": "error",                 │
+//  │               │                            │    "span": { "start": 3, "end": 4 },    │
+//  │               │                            │    "message": "error: ..." }, ...]      │
+//  │ setVariable   │ { "name": "x",             │ { "name": "x", "value": "5", ... }      │
+//  │               │   "value": 5 or "2 + 3" }  │                                         │
+//  │ listVariables │                            │ { "x": { "value": "5", ... } }          │
+//  │ reset         │                            │ null                                    │
+//  ╰───────────────┴────────────────────────────┴─────────────────────────────────────────╯
+//
+// Failed calculations are answered with a JSON-RPC error, which carries the [Error] in its data:
+//
+//  { "code": -32000, "message": "error: division by zero",
+//    "data": { "kind": "DivisionByZero", "span": { "start": 4, "end": 5 }, "message": "<full message>" } }
+//
+// Spans are byte ranges of expression, the end is exclusive.
+#[derive(Debug, Default)]
+pub struct Server {
+    env: Environment<'static>,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Creates a server, that starts with variables of [env].
+    pub fn with_environment(env: Environment<'static>) -> Self {
+        Self { env }
+    }
+
+    pub fn environment(&self) -> &Environment<'static> {
+        &self.env
+    }
+
+    // Answers requests from [reader] line by line, until the end of input.
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: R, mut writer: W) -> io::Result<()> {
+        for line in reader.lines() {
+            if let Some(response) = self.handle(&line?) {
+                writeln!(writer, "{response}")?;
+                writer.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Answers a single request. Notifications, the requests without id, have no answer.
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        let request: Json = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(failure(&Json::Null, PARSE_ERROR, &e.to_string(), None)),
+        };
+
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Json::as_str);
+        let is_valid = request.get("jsonrpc").and_then(Json::as_str) == Some("2.0");
+
+        let (method, id) = match (method, id) {
+            (Some(method), id) if is_valid => (method, id),
+            (_, id) => {
+                let id = id.unwrap_or(Json::Null);
+                return Some(failure(&id, INVALID_REQUEST, "invalid request", None));
+            }
+        };
+
+        let params = request.get("params").cloned().unwrap_or(Json::Null);
+        let result = self.call(method, &params);
+
+        let id = id?;
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(Failure::Calculation(e)) => {
+                let message = e.to_string();
+                let headline = message.lines().next().unwrap_or_default().trim();
+                return Some(failure(
+                    &id,
+                    CALCULATION_ERROR,
+                    headline,
+                    Some(error_data(&e)),
+                ));
+            }
+            Err(Failure::Rpc(code, message)) => return Some(failure(&id, code, &message, None)),
+        };
+
+        Some(response.to_string())
+    }
+
+    fn call(&mut self, method: &str, params: &Json) -> Result<Json, Failure> {
+        match method {
+            "evaluate" => {
+                let expression = string_param(params, "expression")?;
+                let value = self.calculate(expression)?;
+                Ok(value_json(&value))
+            }
+            "tokens" => {
+                let expression = string_param(params, "expression")?;
+                let sub = Lexer::lex(expression).map_err(Failure::Calculation)?;
+                Ok(Json::Array(sub.tokens.iter().map(token_json).collect()))
+            }
+            "check" => {
+                let expression = string_param(params, "expression")?;
+                let problems = analysis::check(expression, &self.env);
+                Ok(Json::Array(problems.iter().map(problem_json).collect()))
+            }
+            "setVariable" => {
+                let name = string_param(params, "name")?;
+                let is_name = name
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphabetic() || c == '_')
+                    && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+                if !is_name {
+                    return Err(Failure::Rpc(
+                        INVALID_PARAMS,
+                        format!("invalid variable name: `{name}`"),
+                    ));
+                }
+
+                let value = match params.get("value") {
+                    Some(Json::Number(n)) => {
+                        let n = n.as_f64().unwrap_or_default();
+                        self.env.set(name, n);
+                        Value::Number(n)
+                    }
+                    Some(Json::String(expression)) => {
+                        self.calculate(&format!("{name} = {expression}"))?
+                    }
+                    _ => {
+                        return Err(Failure::Rpc(
+                            INVALID_PARAMS,
+                            String::from("missing `value` param"),
+                        ))
+                    }
+                };
+
+                let mut result = value_json(&value);
+                result["name"] = Json::String(name.to_string());
+                Ok(result)
+            }
+            "listVariables" => {
+                let mut variables = Map::new();
+                for name in self.env.names() {
+                    if let Some(value) = self.env.get_value(name) {
+                        variables.insert(name.clone(), value_json(value));
+                    }
+                }
+                Ok(Json::Object(variables))
+            }
+            "reset" => {
+                self.env.clear();
+                Ok(Json::Null)
+            }
+            _ => Err(Failure::Rpc(
+                METHOD_NOT_FOUND,
+                format!("method not found: `{method}`"),
+            )),
+        }
+    }
+
+    fn calculate(&mut self, expression: &str) -> Result<Value, Failure> {
+        Lexer::lex(expression)
+            .and_then(|sub| Calculator::calculate_value(sub, expression, &mut self.env))
+            .map_err(Failure::Calculation)
+    }
+}
+
+// The reason of a failed request.
+enum Failure {
+    Calculation(Error),
+    Rpc(i64, String),
+}
+
+fn failure(id: &Json, code: i64, message: &str, data: Option<Json>) -> String {
+    let mut error = json!({ "code": code, "message": message });
+    if let Some(data) = data {
+        error["data"] = data;
+    }
+
+    json!({ "jsonrpc": "2.0", "id": id, "error": error }).to_string()
+}
+
+fn string_param<'a>(params: &'a Json, name: &str) -> Result<&'a str, Failure> {
+    params
+        .get(name)
+        .and_then(Json::as_str)
+        .ok_or_else(|| Failure::Rpc(INVALID_PARAMS, format!("missing `{name}` param")))
+}
+
+fn error_data(e: &Error) -> Json {
+    json!({
+        "kind": format!("{:?}", e.kind()),
+        "span": span_json(e.span()),
+        "message": e.to_string().trim_end(),
+    })
+}
+
+fn problem_json(problem: &analysis::Problem) -> Json {
+    let severity = match problem.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+
+    json!({
+        "severity": severity,
+        "span": span_json(Some(problem.span)),
+        "message": problem.message,
+    })
+}
+
+// Encodes the value for JSON output, as it's written in results of the server.
+// The text of value is always there, and finite plain numbers are also given as [number].
+pub fn value_json(value: &Value) -> Json {
+    let kind = match value {
+        Value::Number(_) => "number",
+        Value::Interval(_) => "interval",
+        Value::Uncertain(_) => "uncertain",
+    };
+
+    // JSON has no non-finite numbers, so only finite ones are given as [number].
+    let number = value.as_number().filter(|n| n.is_finite());
+    json!({ "value": value.to_string(), "kind": kind, "number": number })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(server: &mut Server, method: &str, params: Json) -> Json {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = server.handle(&request.to_string()).unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn methods() {
+        let mut server = Server::new();
+
+        let response = request(
+            &mut server,
+            "setVariable",
+            json!({ "name": "x", "value": 5 }),
+        );
+        assert_eq!(response["result"]["value"], "5");
+
+        let response = request(
+            &mut server,
+            "setVariable",
+            json!({ "name": "y", "value": "x * 2" }),
+        );
+        assert_eq!(response["result"]["number"], 10.0);
+
+        let response = request(&mut server, "evaluate", json!({ "expression": "x + y" }));
+        assert_eq!(
+            response,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "value": "15", "kind": "number", "number": 15.0 }
+            })
+        );
+
+        let response = request(
+            &mut server,
+            "evaluate",
+            json!({ "expression": "[1, 2] * x" }),
+        );
+        assert_eq!(response["result"]["kind"], "interval");
+        assert_eq!(response["result"]["number"], Json::Null);
+
+        let response = request(&mut server, "listVariables", Json::Null);
+        assert_eq!(response["result"]["x"]["value"], "5");
+        assert_eq!(response["result"]["y"]["value"], "10");
+
+        let response = request(&mut server, "tokens", json!({ "expression": "2 * (x)" }));
+        let tokens = response["result"].as_array().unwrap();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0]["type"], "NUMBER");
+        assert_eq!(tokens[0]["span"], json!({ "start": 0, "end": 1 }));
+        assert_eq!(tokens[2]["method"], "PAREN");
+        assert_eq!(tokens[2]["tokens"][0]["literal"], "x");
+
+        let response = request(&mut server, "check", json!({ "expression": "(x $ z" }));
+        assert_eq!(
+            response["result"],
+            json!([
+                { "severity": "error", "span": { "start": 0, "end": 1 }, "message": "error: unclosed bracket" },
+                { "severity": "error", "span": { "start": 3, "end": 4 }, "message": "error: found an illegal character: `$`" },
+                { "severity": "warning", "span": { "start": 5, "end": 6 }, "message": "warning: undefined variable 'z'" },
+            ])
+        );
+
+        let response = request(&mut server, "reset", Json::Null);
+        assert_eq!(response["result"], Json::Null);
+        assert!(server.environment().names().is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        let mut server = Server::new();
+
+        let response = request(&mut server, "evaluate", json!({ "expression": "1 / 0" }));
+        let error = &response["error"];
+        assert_eq!(error["code"], CALCULATION_ERROR);
+        assert_eq!(error["message"], "error: division by zero");
+        assert_eq!(error["data"]["kind"], "DivisionByZero");
+        assert_eq!(error["data"]["span"], json!({ "start": 4, "end": 5 }));
+
+        let response = request(&mut server, "evaluate", json!({ "expression": "unknown" }));
+        assert_eq!(
+            response["error"]["data"]["span"],
+            json!({ "start": 0, "end": 7 })
+        );
+
+        let response = request(&mut server, "evaluate", json!({ "expression": "sqr(16)" }));
+        assert!(response["error"]["data"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("did you mean `sqrt`?"));
+
+        let test_data: Vec<(&str, i64)> = vec![
+            ("not json", PARSE_ERROR),
+            ("{\"id\": 1, \"method\": \"evaluate\"}", INVALID_REQUEST),
+            ("{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"unknown\"}", METHOD_NOT_FOUND),
+            ("{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"evaluate\"}", INVALID_PARAMS),
+            (
+                "{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"setVariable\", \"params\": {\"name\": \"1x\", \"value\": 1}}",
+                INVALID_PARAMS,
+            ),
+        ];
+
+        for (line, code) in test_data {
+            let response: Json = serde_json::from_str(&server.handle(line).unwrap()).unwrap();
+            assert_eq!(response["error"]["code"], code, "Failed for: {line}");
+        }
+
+        // Notifications aren't answered.
+        let notification =
+            "{\"jsonrpc\": \"2.0\", \"method\": \"evaluate\", \"params\": {\"expression\": \"1\"}}";
+        assert_eq!(server.handle(notification), None);
+    }
+}
@@ -0,0 +1,182 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator, environment::Environment, errors::Error, lexer::Lexer, value::Value,
+};
+
+// A single statement of script, with its position in the script text.
+// Both [line] and [column] start from one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement {
+    pub line: usize,
+    pub column: usize,
+    pub source: String,
+}
+
+impl Statement {
+    // Checks if the statement is an assignment, like `x = 5`.
+    pub fn is_assignment(&self) -> bool {
+        match Lexer::lex(&self.source) {
+            Ok(sub) => matches!(
+                sub.tokens.as_slice(),
+                [name, assign, ..] if name.is_identifier() && assign.is_assign()
+            ),
+            Err(_) => false,
+        }
+    }
+}
+
+// A script of mate statements, executed one by one in a shared [Environment].
+//
+// Statements are separated by new lines or `;`, and `#` starts a comment till
+// the end of line:
+//
+//  ╭──────────────────────────────╮     ╭───────────────────────────╮
+//  │ # circle                     │     │ 1:1  r = 7                │
+//  │ r = 7; pi = 3.14159          │ ──▶ │ 2:1  r = 7                │
+//  │ pi * r ^ 2   # the area      │     │ 2:8  pi = 3.14159         │
+//  ╰──────────────────────────────╯     │ 3:1  pi * r ^ 2           │
+//                                       ╰───────────────────────────╯
+#[derive(Clone, Debug, PartialEq)]
+pub struct Script {
+    statements: Vec<Statement>,
+}
+
+impl Script {
+    // Splits the script text to statements.
+    pub fn parse(text: &str) -> Self {
+        let mut statements: Vec<Statement> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let code = line.split('#').next().unwrap_or_default();
+
+            let mut column: usize = 1;
+            for part in code.split(';') {
+                let leading = part.chars().take_while(|c| c.is_whitespace()).count();
+                let source = part.trim();
+
+                if !source.is_empty() {
+                    statements.push(Statement {
+                        line: i + 1,
+                        column: column + leading,
+                        source: source.to_string(),
+                    });
+                }
+
+                column += part.chars().count() + 1;
+            }
+        }
+
+        Self { statements }
+    }
+
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    // Executes statements in order, and calls [on_result] with the result of each one.
+    // Stops at the first failed statement, with an error that tells its line and column.
+    //
+    // Returns the result of the last statement, or none for an empty script.
+    pub fn run<F>(&self, env: &mut Environment, mut on_result: F) -> Result<Option<Value>, Error>
+    where
+        F: FnMut(&Statement, &Value),
+    {
+        let mut last: Option<Value> = None;
+
+        for statement in self.statements.iter() {
+            let value = Lexer::lex(&statement.source)
+                .and_then(|sub| Calculator::calculate_value(sub, &statement.source, env))
+                .map_err(|e| {
+                    Error::in_script(statement.line, statement.column, &statement.source, e)
+                })?;
+
+            on_result(statement, &value);
+            last = Some(value);
+        }
+
+        Ok(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let text = "# circle\nr = 7; pi = 3.14159\n\n  pi * r ^ 2   # the area\n;;";
+
+        let script = Script::parse(text);
+        let statements: Vec<(usize, usize, &str)> = script
+            .statements()
+            .iter()
+            .map(|s| (s.line, s.column, s.source.as_str()))
+            .collect();
+
+        assert_eq!(
+            statements,
+            vec![
+                (2, 1, "r = 7"),
+                (2, 8, "pi = 3.14159"),
+                (4, 3, "pi * r ^ 2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn run() {
+        let script = Script::parse("a = 2; b = a * 3\nb + 1\n");
+        let mut env = Environment::new();
+        let mut results: Vec<(String, bool, Value)> = Vec::new();
+
+        let last = script.run(&mut env, |statement, value| {
+            results.push((
+                statement.source.clone(),
+                statement.is_assignment(),
+                value.clone(),
+            ))
+        });
+
+        assert_eq!(last, Ok(Some(Value::Number(7.0))));
+        assert_eq!(
+            results,
+            vec![
+                (String::from("a = 2"), true, Value::Number(2.0)),
+                (String::from("b = a * 3"), true, Value::Number(6.0)),
+                (String::from("b + 1"), false, Value::Number(7.0)),
+            ]
+        );
+        assert_eq!(env.get("b"), Some(6.0));
+
+        assert_eq!(
+            Script::parse("# nothing").run(&mut env, |_, _| {}),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let test_data: Vec<(&str, &str)> = vec![
+            ("x = 1\ny = x / 0", "2:9: error: division by zero"),
+            ("x = 1;   y = x +", "1:17: error: missing some tokens"),
+            (
+                "x = 1\n  unknown * 2",
+                "2:3: error: undefined variable 'unknown'",
+            ),
+        ];
+
+        for (text, expected) in test_data {
+            let mut env = Environment::new();
+            let error = Script::parse(text).run(&mut env, |_, _| {}).unwrap_err();
+            assert!(
+                error.to_string().starts_with(expected),
+                "Failed for: {text}, got: {error}"
+            );
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator, environment::Environment, errors::Error, lexer::Lexer, value::Value,
+};
+
+// The operators, that continue the last result when input starts with them.
+// Plus and minus continue only if they're followed by a space, since `-2` is a number.
+const CONTINUATIONS: [char; 7] = ['*', '•', '/', ':', '^', '%', '!'];
+
+// A calculated input of [Session], with its number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub number: usize,
+    pub input: String,
+    pub value: Value,
+}
+
+// An interactive session of calculations over an [Environment], that remembers results.
+//
+// Each result is bound to `ans`, and to a numbered variable of its entry:
+//
+//  ╭────────────────╮     ╭────────────────────────────╮
+//  │ 2 + 2          │ ──▶ │ _1 = 4         ans = 4     │
+//  │ * 10           │ ──▶ │ _2 = 40        ans = 40    │
+//  │ _1 + _2        │ ──▶ │ _3 = 44        ans = 44    │
+//  ╰────────────────╯     ╰────────────────────────────╯
+//
+// Input that starts with an operator continues the last result, `* 10` means `ans * 10`.
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    env: Environment<'static>,
+    entries: Vec<Entry>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Creates a session, that starts with variables of [env].
+    pub fn with_environment(env: Environment<'static>) -> Self {
+        Self {
+            env,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn environment(&self) -> &Environment<'static> {
+        &self.env
+    }
+
+    pub fn environment_mut(&mut self) -> &mut Environment<'static> {
+        &mut self.env
+    }
+
+    // The calculated inputs, in the order of calculation.
+    pub fn history(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    // The last result, that's bound to `ans`.
+    pub fn last(&self) -> Option<&Value> {
+        self.entries.last().map(|e| &e.value)
+    }
+
+    // Expands the input that starts with an operator, to continue the last result.
+    // Any other input, or the input of a session without results is kept as it's.
+    pub fn expand(&self, input: &str) -> String {
+        let input = input.trim();
+        if self.last().is_none() {
+            return input.to_string();
+        }
+
+        let mut chars = input.chars();
+        let continues = match chars.next() {
+            Some(c) if CONTINUATIONS.contains(&c) => true,
+            Some('+') | Some('-') => chars.next().is_some_and(char::is_whitespace),
+            _ => false,
+        };
+
+        if continues {
+            return format!("ans {input}");
+        }
+
+        input.to_string()
+    }
+
+    // Calculates the (expanded) input, and records its result.
+    pub fn calculate(&mut self, input: &str) -> Result<Value, Error> {
+        let input = self.expand(input);
+        let sub = Lexer::lex(&input)?;
+        let value = Calculator::calculate_value(sub, &input, &mut self.env)?;
+
+        self.record(&input, value.clone());
+        Ok(value)
+    }
+
+    // Records the result of input, that's calculated in the environment of session.
+    // Binds it to `ans` and to the numbered variable, and returns the number of entry.
+    pub fn record(&mut self, input: &str, value: Value) -> usize {
+        let number = self.entries.len() + 1;
+
+        for name in ["ans".to_string(), format!("_{number}")] {
+            self.env.forget(&name);
+            self.env.set_value(&name, value.clone());
+        }
+
+        self.entries.push(Entry {
+            number,
+            input: input.to_string(),
+            value,
+        });
+        number
+    }
+
+    // Clears both variables and history.
+    pub fn clear(&mut self) {
+        self.env.clear();
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand() {
+        let mut session = Session::new();
+        assert_eq!(session.expand("* 2"), "* 2");
+
+        session.record("3", Value::Number(3.0));
+
+        let test_data: Vec<(&str, &str)> = vec![
+            ("* 2", "ans * 2"),
+            ("/2", "ans /2"),
+            ("^ 2", "ans ^ 2"),
+            ("!", "ans !"),
+            ("+ 2", "ans + 2"),
+            ("- 2", "ans - 2"),
+            ("-2", "-2"),
+            ("  x * 2 ", "x * 2"),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(session.expand(input), expected, "Failed for: {input}");
+        }
+    }
+
+    #[test]
+    fn calculate() {
+        let mut session = Session::new();
+
+        let test_data: Vec<(&str, f64)> = vec![
+            ("2 + 2", 4.0),
+            ("* 10", 40.0),
+            ("x = ans / 8", 5.0),
+            ("_1 + _2 + x", 49.0),
+            ("- 9", 40.0),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(
+                session.calculate(input),
+                Ok(Value::Number(expected)),
+                "Failed for: {input}"
+            );
+        }
+
+        assert_eq!(session.environment().get("ans"), Some(40.0));
+        assert_eq!(session.environment().get("_3"), Some(5.0));
+
+        let history: Vec<(usize, &str)> = session
+            .history()
+            .iter()
+            .map(|e| (e.number, e.input.as_str()))
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                (1, "2 + 2"),
+                (2, "ans * 10"),
+                (3, "x = ans / 8"),
+                (4, "_1 + _2 + x"),
+                (5, "ans - 9"),
+            ]
+        );
+
+        // Failed inputs aren't recorded.
+        assert!(session.calculate("1 / 0").is_err());
+        assert_eq!(session.history().len(), 5);
+
+        session.clear();
+        assert_eq!(session.last(), None);
+        assert!(!session.environment().exists("ans"));
+    }
+}
@@ -0,0 +1,213 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use std::fmt;
+
+// The range of bytes in input, that a token or an error covers.
+// The start is inclusive and the end is exclusive, so an empty span
+// marks the position between two characters.
+//
+//   "2 • 3"
+//  ╭───┬───┬───┬───┬───┬───┬───╮
+//  │ 2 │   │     •     │   │ 3 │
+//  ╰───┴───┴───┴───┴───┴───┴───╯
+//    0   1   2   3   4   5   6
+//
+// The `•` takes three bytes, so its span is 2..5, and the span of `3` is 6..7.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+// The human readable position in input, both line and column start from 1.
+// Columns are counted in characters, not bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    // An empty span at the given offset, e.g. the place of a missing token.
+    pub fn at(offset: usize) -> Self {
+        Self::new(offset, offset)
+    }
+
+    // Converts the range of character indexes in [input] to a span of bytes.
+    // The [end] is exclusive, indexes out of input are clamped to its end.
+    pub fn from_chars(input: &str, start: usize, end: usize) -> Self {
+        let offset = |index: usize| {
+            input
+                .char_indices()
+                .nth(index)
+                .map_or(input.len(), |(i, _)| i)
+        };
+
+        Self::new(offset(start), offset(end))
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    // The smallest span, that covers both spans.
+    pub fn join(self, other: Span) -> Self {
+        Self::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    // Moves the span by the given amount of bytes.
+    // Used when the input was a part of some bigger text, like a line of script.
+    pub fn shifted(self, by: usize) -> Self {
+        Self::new(self.start + by, self.end + by)
+    }
+
+    // The part of [input] that the span covers, or an empty string,
+    // if the span doesn't fit into input.
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        input.get(self.start..self.end).unwrap_or_default()
+    }
+
+    // Converts the span to the range of character indexes in [input].
+    pub fn chars(&self, input: &str) -> (usize, usize) {
+        (char_index(input, self.start), char_index(input, self.end))
+    }
+
+    // The position of the span's first character in [input].
+    pub fn start_position(&self, input: &str) -> Position {
+        Position::of(input, self.start)
+    }
+
+    // The position right after the span's last character in [input].
+    pub fn end_position(&self, input: &str) -> Position {
+        Position::of(input, self.end)
+    }
+}
+
+impl Position {
+    // Finds the line and column of the byte [offset] in [input].
+    pub fn of(input: &str, offset: usize) -> Self {
+        let before = &input[..floor_boundary(input, offset)];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+// The amount of characters before the byte [offset] in [input].
+fn char_index(input: &str, offset: usize) -> usize {
+    input[..floor_boundary(input, offset)].chars().count()
+}
+
+// Moves the [offset] back to the closest character boundary of [input].
+fn floor_boundary(input: &str, offset: usize) -> usize {
+    let mut offset = offset.min(input.len());
+    while !input.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_chars() {
+        let test_data: Vec<(&str, (usize, usize), Span)> = vec![
+            ("2 + 3", (4, 5), Span::new(4, 5)),
+            ("2 • 3", (2, 3), Span::new(2, 5)),
+            ("2 • 3", (4, 5), Span::new(6, 7)),
+            ("é + 1", (0, 1), Span::new(0, 2)),
+            ("é + 1", (3, 10), Span::new(4, 6)),
+        ];
+
+        for (input, (start, end), expected) in test_data {
+            let span = Span::from_chars(input, start, end);
+            assert_eq!(span, expected, "Failed for: {input}");
+            assert_eq!(span.chars(input), (start.min(5), end.min(5)));
+        }
+    }
+
+    #[test]
+    fn join() {
+        assert_eq!(Span::new(4, 5).join(Span::new(0, 1)), Span::new(0, 5));
+        assert_eq!(Span::at(3).join(Span::new(1, 2)), Span::new(1, 3));
+        assert_eq!(Span::new(2, 5).shifted(3), Span::new(5, 8));
+        assert!(Span::at(2).is_empty());
+    }
+
+    #[test]
+    fn text() {
+        let input = "2 • 3 ± 0.1";
+        assert_eq!(Span::new(2, 5).text(input), "•");
+        assert_eq!(Span::new(8, 10).text(input), "±");
+        assert_eq!(Span::new(3, 4).text(input), "");
+        assert_eq!(Span::new(10, 40).text(input), "");
+    }
+
+    #[test]
+    fn positions() {
+        let input = "a = 2 • 3\nb = a / 0";
+        let test_data: Vec<(Span, Position, Position)> = vec![
+            (
+                Span::new(0, 1),
+                Position { line: 1, column: 1 },
+                Position { line: 1, column: 2 },
+            ),
+            (
+                Span::new(6, 9),
+                Position { line: 1, column: 7 },
+                Position { line: 1, column: 8 },
+            ),
+            (
+                Span::new(20, 21),
+                Position { line: 2, column: 9 },
+                Position {
+                    line: 2,
+                    column: 10,
+                },
+            ),
+            (
+                Span::new(7, 8),
+                Position { line: 1, column: 7 },
+                Position { line: 1, column: 7 },
+            ),
+        ];
+
+        for (span, start, end) in test_data {
+            assert_eq!(span.start_position(input), start, "Failed for: {span}");
+            assert_eq!(span.end_position(input), end, "Failed for: {span}");
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{environment::Environment, token::BUILTINS};
+
+// Names of constants, with the symbols they're written by.
+pub(crate) const CONSTANTS: [(&str, &str); 3] = [("pi", "π"), ("tau", "τ"), ("infinity", "∞")];
+
+// Finds what the unknown name [word] was probably meant to be: the closest one of
+// variables of [env], built-in functions and constants.
+//
+//  ╭────────╮     ╭────────╮    ╭──────╮     ╭─────╮    ╭────╮     ╭───╮
+//  │ raduis │ ──▶ │ radius │    │ sine │ ──▶ │ sin │    │ pi │ ──▶ │ π │
+//  ╰────────╯     ╰────────╯    ╰──────╯     ╰─────╯    ╰────╯     ╰───╯
+//
+// Short names need a closer match, so a single letter never suggests another one.
+pub fn did_you_mean(word: &str, env: &Environment) -> Option<String> {
+    let limit = match word.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    };
+
+    // Names are sorted, so the equally close ones are suggested in the same order.
+    let mut names = env.names();
+    names.sort();
+
+    let variables = names.into_iter().map(|name| (name.as_str(), name.as_str()));
+    let functions = BUILTINS.iter().map(|b| (b.name, b.name));
+    let constants = CONSTANTS.iter().copied();
+
+    let lowercase = word.to_lowercase();
+    let (_, suggestion) = variables
+        .chain(functions)
+        .chain(constants)
+        .filter(|(_, suggestion)| *suggestion != word)
+        .map(|(name, suggestion)| (distance(&lowercase, &name.to_lowercase()), suggestion))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)?;
+
+    Some(suggestion.to_string())
+}
+
+// The edit distance of [a] and [b]: how many characters must be inserted, removed,
+// replaced or swapped with the next one, to turn [a] into [b].
+//
+//  ╭────────╮  swap "u" and "i"  ╭────────╮
+//  │ raduis │ ─────────────────▶ │ radius │ = 1
+//  ╰────────╯                    ╰────────╯
+pub fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // [d[i][j]] is the distance of the first [i] chars of [a] and the first [j] chars of [b].
+    let mut d: Vec<Vec<usize>> = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+
+            let is_swap = i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1];
+            if is_swap {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        let test_data: Vec<(&str, &str, usize)> = vec![
+            ("", "", 0),
+            ("sqrt", "sqrt", 0),
+            ("sqr", "sqrt", 1),
+            ("sine", "sin", 1),
+            ("raduis", "radius", 1),
+            ("cos", "sin", 3),
+            ("", "abc", 3),
+            ("√x", "√y", 1),
+        ];
+
+        for (a, b, expected) in test_data {
+            assert_eq!(super::distance(a, b), expected, "Failed for: {a} and {b}");
+            assert_eq!(super::distance(b, a), expected, "Failed for: {b} and {a}");
+        }
+    }
+
+    #[test]
+    fn did_you_mean() {
+        let mut env = Environment::new();
+        env.set("radius", 2.0);
+        env.set("Total", 10.0);
+        env.set("x", 1.0);
+
+        let test_data: Vec<(&str, Option<&str>)> = vec![
+            ("raduis", Some("radius")),
+            ("sqr", Some("sqrt")),
+            ("sine", Some("sin")),
+            ("total", Some("Total")),
+            ("Radius", Some("radius")),
+            ("pi", Some("π")),
+            ("infinty", Some("∞")),
+            ("y", None),
+            ("radius", None),
+            ("something", None),
+        ];
+
+        for (word, expected) in test_data {
+            let suggestion = super::did_you_mean(word, &env);
+            assert_eq!(suggestion.as_deref(), expected, "Failed for: {word}");
+        }
+    }
+}
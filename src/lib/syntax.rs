@@ -0,0 +1,577 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    errors::Error,
+    lexer::Lexer,
+    span::Span,
+    token::{Sub, Token, TokenType},
+};
+use std::fmt;
+
+// The kind of trivia: the text between tokens, that doesn't change the meaning of input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Comment, // From `#` till the end of line.
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub span: Span,
+}
+
+// A token of the syntax tree, with its exact text in the source.
+//
+// Implicit tokens aren't written in the source, but lexer reads them anyway:
+// the `*` of `2x`, and the `^` of `x²`. They have an empty text, and an empty span
+// at the point they're read at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxToken {
+    pub typ: TokenType,
+    pub text: String,
+    pub span: Span,
+    pub implicit: bool,
+}
+
+// The kind of node: the whole input, or a bracketed group of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Root,
+    Paren, // (...)
+    Abs,   // [...] or |...|
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+    Trivia(Trivia),
+}
+
+// A node of the syntax tree. Brackets of a group are the first and the last
+// tokens of its children, so nothing of the source is lost.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+// The lossless (concrete) syntax tree of input.
+//
+// Unlike the tokens of [Lexer::lex], it keeps every character of the source:
+// white spaces and comments as trivia, brackets as tokens, and the operations that
+// lexer inserts are marked as implicit. So it can be edited and written back exactly.
+//
+//  ╭─────────────────╮     ROOT
+//  │ 2x + (y)  # sum │     ├─ NUMBER "2"
+//  ╰────────┬────────╯     ├─ PRODUCT (implicit)
+//           │              ├─ IDENTIFIER "x"
+//           ╰────────────▶ ├─ WHITESPACE " "
+//                          ├─ PLUS "+"
+//                          ├─ WHITESPACE " "
+//                          ├─ PAREN
+//                          │  ├─ LPAREN "("
+//                          │  ├─ IDENTIFIER "y"
+//                          │  ╰─ RPAREN ")"
+//                          ├─ WHITESPACE "  "
+//                          ╰─ COMMENT "# sum"
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxTree {
+    source: String,
+    root: SyntaxNode,
+}
+
+impl SyntaxTree {
+    // Builds the syntax tree of [input]. Never fails: text that lexer can't read
+    // comes as [ILLEGAL] tokens, and unmatched brackets are kept as plain tokens.
+    pub fn parse(input: &str) -> Self {
+        let comments = comments(input);
+        let tokens = read_tokens(input, &comments);
+
+        Self {
+            source: input.to_string(),
+            root: nest(tokens),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    // Returns every token of the tree, implicit ones included, in the order of source.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.root.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    // Returns the names of variables, in the order they first appear.
+    pub fn identifiers(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for t in self.tokens() {
+            if t.typ == TokenType::IDENTIFIER && !names.contains(&t.text) {
+                names.push(t.text.clone());
+            }
+        }
+
+        names
+    }
+
+    // Renames the variable [from] to [to] everywhere in the source, and keeps
+    // everything else, including white spaces and comments, as it's.
+    //
+    //  ╭───────────────────────────╮     ╭───────────────────────────────────╮
+    //  │ r * r * pi  # area of r   │ ──▶ │ radius * radius * pi  # area of r │
+    //  ╰───────────────────────────╯     ╰───────────────────────────────────╯
+    pub fn rename(&self, from: &str, to: &str) -> Result<SyntaxTree, Error> {
+        if !Token::from(to.to_string(), Span::default()).is_identifier() {
+            return Err(Error::invalid_name(to));
+        }
+
+        let mut source = String::new();
+        let mut last: usize = 0;
+        for t in self.tokens() {
+            if t.typ == TokenType::IDENTIFIER && t.text == from {
+                source.push_str(&self.source[last..t.span.start]);
+                source.push_str(to);
+                last = t.span.end;
+            }
+        }
+        source.push_str(&self.source[last..]);
+
+        Ok(SyntaxTree::parse(&source))
+    }
+}
+
+// Writes the source back, exactly as it was parsed.
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)
+    }
+}
+
+impl SyntaxNode {
+    // The range of source, that node covers.
+    pub fn span(&self) -> Span {
+        let spans = self.children.iter().map(SyntaxElement::span);
+        spans.reduce(Span::join).unwrap_or_default()
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(t) => tokens.push(t),
+                SyntaxElement::Trivia(_) => {}
+            }
+        }
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in self.children.iter() {
+            match child {
+                SyntaxElement::Node(node) => write!(f, "{node}")?,
+                SyntaxElement::Token(t) => write!(f, "{}", t.text)?,
+                SyntaxElement::Trivia(t) => write!(f, "{}", t.text)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SyntaxElement {
+    pub fn span(&self) -> Span {
+        match self {
+            SyntaxElement::Node(node) => node.span(),
+            SyntaxElement::Token(t) => t.span,
+            SyntaxElement::Trivia(t) => t.span,
+        }
+    }
+}
+
+impl SyntaxToken {
+    // Checks if the token ends a value, so a value right after it is multiplied by it.
+    fn ends_value(&self) -> bool {
+        matches!(
+            self.typ,
+            TokenType::NUMBER
+                | TokenType::IDENTIFIER
+                | TokenType::RPAREN
+                | TokenType::RABS
+                | TokenType::FACTORIAL
+        )
+    }
+
+    // Checks if the token starts a value: a number, a variable, a group or a function call.
+    fn starts_value(&self) -> bool {
+        let token = Token::new(self.typ.clone(), self.text.clone(), Sub::empty(), self.span);
+        token.is_number()
+            || token.is_identifier()
+            || token.is_lparen()
+            || token.is_labs()
+            || token.is_function()
+    }
+}
+
+// Finds the spans of comments: from `#` till the end of each line.
+fn comments(input: &str) -> Vec<Span> {
+    let mut comments = Vec::new();
+    let mut offset: usize = 0;
+    for line in input.split_inclusive('\n') {
+        let code = line.trim_end_matches(['\n', '\r']);
+        if let Some(at) = code.find('#') {
+            comments.push(Span::new(offset + at, offset + code.len()));
+        }
+        offset += line.len();
+    }
+
+    comments
+}
+
+// Reads the flat list of tokens and trivia, with implicit multiplications in it.
+fn read_tokens(input: &str, comments: &[Span]) -> Vec<SyntaxElement> {
+    // Comments are blanked out for lexer, byte by byte, so spans stay the same.
+    let mut masked = input.to_string();
+    for comment in comments.iter().rev() {
+        masked.replace_range(comment.start..comment.end, &" ".repeat(comment.len()));
+    }
+
+    let mut elements: Vec<SyntaxElement> = Vec::new();
+    let mut previous: Option<SyntaxToken> = None;
+    let mut last: usize = 0;
+    for t in Lexer::tokenize(&masked) {
+        let token = SyntaxToken {
+            text: t.span.text(input).to_string(),
+            implicit: t.span.is_empty(),
+            typ: t.typ,
+            span: t.span,
+        };
+
+        // Implicit products are put right after their left side, before the trivia.
+        if let Some(previous) = previous.take() {
+            let is_implicit_product = previous.ends_value() && token.starts_value();
+            elements.push(SyntaxElement::Token(previous.clone()));
+            if is_implicit_product {
+                elements.push(SyntaxElement::Token(SyntaxToken {
+                    typ: TokenType::PRODUCT,
+                    text: String::new(),
+                    span: Span::at(previous.span.end),
+                    implicit: true,
+                }));
+            }
+        }
+
+        elements.extend(gap(input, comments, last, token.span.start));
+        last = last.max(token.span.end);
+        previous = Some(token);
+    }
+
+    elements.extend(previous.map(SyntaxElement::Token));
+    elements.extend(gap(input, comments, last, input.len()));
+    elements
+}
+
+// Splits the text between two tokens to white spaces and comments.
+// Anything else, that lexer has skipped, is kept as an illegal token.
+fn gap(input: &str, comments: &[Span], start: usize, end: usize) -> Vec<SyntaxElement> {
+    let mut elements = Vec::new();
+    let mut at = start;
+    while at < end {
+        if let Some(comment) = comments.iter().find(|c| c.start == at) {
+            elements.push(trivia(input, TriviaKind::Comment, *comment));
+            at = comment.end;
+            continue;
+        }
+
+        // The text till the next comment.
+        let till = comments
+            .iter()
+            .map(|c| c.start)
+            .filter(|c| *c > at && *c < end)
+            .min()
+            .unwrap_or(end);
+        let text = &input[at..till];
+
+        let whitespace = text.len() - text.trim_start().len();
+        if whitespace > 0 {
+            elements.push(trivia(
+                input,
+                TriviaKind::Whitespace,
+                Span::new(at, at + whitespace),
+            ));
+            at += whitespace;
+            continue;
+        }
+
+        let length = text.find(char::is_whitespace).unwrap_or(text.len());
+        elements.push(SyntaxElement::Token(SyntaxToken {
+            typ: TokenType::ILLEGAL,
+            text: text[..length].to_string(),
+            span: Span::new(at, at + length),
+            implicit: false,
+        }));
+        at += length;
+    }
+
+    elements
+}
+
+fn trivia(input: &str, kind: TriviaKind, span: Span) -> SyntaxElement {
+    SyntaxElement::Trivia(Trivia {
+        kind,
+        text: span.text(input).to_string(),
+        span,
+    })
+}
+
+// Nests the flat list of elements into bracketed groups.
+fn nest(elements: Vec<SyntaxElement>) -> SyntaxNode {
+    let mut stack: Vec<SyntaxNode> = vec![SyntaxNode {
+        kind: NodeKind::Root,
+        children: Vec::new(),
+    }];
+
+    for element in elements {
+        let typ = match &element {
+            SyntaxElement::Token(t) => Some(t.typ.clone()),
+            _ => None,
+        };
+
+        match typ {
+            Some(TokenType::LPAREN) | Some(TokenType::LABS) => {
+                let kind = match typ {
+                    Some(TokenType::LPAREN) => NodeKind::Paren,
+                    _ => NodeKind::Abs,
+                };
+                stack.push(SyntaxNode {
+                    kind,
+                    children: vec![element],
+                });
+            }
+            Some(TokenType::RPAREN) | Some(TokenType::RABS) => {
+                let closes = match stack.last().map(|n| n.kind) {
+                    Some(NodeKind::Paren) => typ == Some(TokenType::RPAREN),
+                    Some(NodeKind::Abs) => typ == Some(TokenType::RABS),
+                    _ => false,
+                };
+
+                let top = stack.len() - 1;
+                stack[top].children.push(element);
+                if closes {
+                    close(&mut stack);
+                }
+            }
+            _ => {
+                let top = stack.len() - 1;
+                stack[top].children.push(element);
+            }
+        }
+    }
+
+    // Unclosed groups are kept as they're.
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+
+    stack.pop().unwrap_or(SyntaxNode {
+        kind: NodeKind::Root,
+        children: Vec::new(),
+    })
+}
+
+// Moves the top node of [stack] to children of its parent.
+fn close(stack: &mut Vec<SyntaxNode>) {
+    if let Some(node) = stack.pop() {
+        let top = stack.len() - 1;
+        stack[top].children.push(SyntaxElement::Node(node));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lossless() {
+        let test_data: Vec<&str> = vec![
+            "",
+            "   ",
+            "2 + 2",
+            "  (4 * 5 - 5)   * 2 + 24 / 2  ",
+            "2x + 3(y - 1)",
+            "sqrt(x) * 2   # the double\n+ 1",
+            "# only a comment",
+            "r = 7;\tpi * r ^ 2\r\n",
+            "√16 × 2 − x² + |−3| · 2πr",
+            "2 5 + 1,5",
+            "integrate(x ^ 2, x, 0, 1)",
+            "(2 ] $ é",
+            "((1 + 2)",
+            "[1, 2] ± 0.5 + 5!",
+        ];
+
+        for input in test_data {
+            let tree = SyntaxTree::parse(input);
+            assert_eq!(tree.to_string(), input);
+            assert_eq!(tree.source(), input);
+
+            // Numbers may have spaces in them, like `2 5`.
+            let tokens: String = tree.tokens().iter().map(|t| t.text.as_str()).collect();
+            let tokens: String = tokens.split_whitespace().collect();
+            let code: String = input
+                .lines()
+                .map(|l| l.split('#').next().unwrap_or_default())
+                .collect();
+            assert_eq!(
+                tokens,
+                code.split_whitespace().collect::<String>(),
+                "Failed for: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn tokens() {
+        type Tokens = Vec<(TokenType, &'static str, bool)>;
+
+        let test_data: Vec<(&str, Tokens)> = vec![
+            (
+                "2x",
+                vec![
+                    (TokenType::NUMBER, "2", false),
+                    (TokenType::PRODUCT, "", true),
+                    (TokenType::IDENTIFIER, "x", false),
+                ],
+            ),
+            (
+                "x² (1)",
+                vec![
+                    (TokenType::IDENTIFIER, "x", false),
+                    (TokenType::POWER, "", true),
+                    (TokenType::NUMBER, "²", false),
+                    (TokenType::PRODUCT, "", true),
+                    (TokenType::LPAREN, "(", false),
+                    (TokenType::NUMBER, "1", false),
+                    (TokenType::RPAREN, ")", false),
+                ],
+            ),
+            (
+                "sqrt(4) 5! * 2",
+                vec![
+                    (TokenType::SQRT, "sqrt", false),
+                    (TokenType::LPAREN, "(", false),
+                    (TokenType::NUMBER, "4", false),
+                    (TokenType::RPAREN, ")", false),
+                    (TokenType::PRODUCT, "", true),
+                    (TokenType::NUMBER, "5", false),
+                    (TokenType::FACTORIAL, "!", false),
+                    (TokenType::PRODUCT, "*", false),
+                    (TokenType::NUMBER, "2", false),
+                ],
+            ),
+        ];
+
+        for (input, expected) in test_data {
+            let tree = SyntaxTree::parse(input);
+            let tokens: Vec<(TokenType, &str, bool)> = tree
+                .tokens()
+                .into_iter()
+                .map(|t| (t.typ.clone(), t.text.as_str(), t.implicit))
+                .collect();
+            assert_eq!(tokens, expected, "Failed for: {input}");
+        }
+    }
+
+    #[test]
+    fn nodes() {
+        let tree = SyntaxTree::parse("1 + (2 * [x - 3])  # note");
+        let children = &tree.root().children;
+
+        let kinds: Vec<Option<TriviaKind>> = children
+            .iter()
+            .map(|c| match c {
+                SyntaxElement::Trivia(t) => Some(t.kind),
+                _ => None,
+            })
+            .collect();
+        let space = Some(TriviaKind::Whitespace);
+        let comment = Some(TriviaKind::Comment);
+        assert_eq!(kinds, vec![None, space, None, space, None, space, comment]);
+
+        let paren = match &children[4] {
+            SyntaxElement::Node(node) => node,
+            other => panic!("expected a node, got {other:?}"),
+        };
+        assert_eq!(paren.kind, NodeKind::Paren);
+        assert_eq!(paren.span(), Span::new(4, 17));
+        assert_eq!(paren.to_string(), "(2 * [x - 3])");
+
+        let abs = paren.children.iter().find_map(|c| match c {
+            SyntaxElement::Node(node) => Some(node),
+            _ => None,
+        });
+        assert_eq!(
+            abs.map(|n| (n.kind, n.to_string())),
+            Some((NodeKind::Abs, String::from("[x - 3]")))
+        );
+
+        // Unmatched brackets are kept as plain tokens.
+        let tree = SyntaxTree::parse("(1 + 2]");
+        assert_eq!(tree.to_string(), "(1 + 2]");
+        assert!(
+            matches!(&tree.root().children[0], SyntaxElement::Node(n) if n.kind == NodeKind::Paren)
+        );
+    }
+
+    #[test]
+    fn rename() {
+        let test_data: Vec<(&str, &str, &str, &str)> = vec![
+            (
+                "r * r * pi  # area of r",
+                "r",
+                "radius",
+                "radius * radius * pi  # area of r",
+            ),
+            ("2r+r²", "r", "radius", "2radius+radius²"),
+            (
+                "rate = r\n  * (1 + r)",
+                "r",
+                "x_1",
+                "rate = x_1\n  * (1 + x_1)",
+            ),
+            ("x + y", "z", "w", "x + y"),
+        ];
+
+        for (input, from, to, expected) in test_data {
+            let tree = SyntaxTree::parse(input).rename(from, to).unwrap();
+            assert_eq!(tree.to_string(), expected);
+        }
+
+        for name in ["sqrt", "2x", "a b", ""] {
+            assert_eq!(
+                SyntaxTree::parse("x + 1").rename("x", name),
+                Err(Error::invalid_name(name))
+            );
+        }
+    }
+
+    #[test]
+    fn identifiers() {
+        let tree = SyntaxTree::parse("y = 2x + x * z # w");
+        assert_eq!(tree.identifiers(), vec!["y", "x", "z"]);
+    }
+}
@@ -0,0 +1,389 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator, environment::Environment, errors::Error, lexer::Lexer, plot::Plot,
+    token::Sub,
+};
+
+// A sampled point of expression: the value of variable, and the result at it.
+pub type Sample = (f64, Result<f64, Error>);
+
+// The default maximum amount of rows of [Table], see [Table::with_max_rows].
+pub const MAX_ROWS: usize = 100_000;
+
+// A row of [Table]: the value of variable, and the result of each expression at it.
+pub type Row = (f64, Vec<Result<f64, Error>>);
+
+// The iterator of results of an expression, at evenly spaced values of its variable.
+// The expression is lexed once, and calculated in a child scope of the environment,
+// where the variable is bound to each value.
+pub struct Samples<'a> {
+    sub: Sub,
+    expression: String,
+    variable: String,
+    scope: Environment<'a>,
+    from: f64,
+    step: f64,
+    count: usize,
+    index: usize,
+}
+
+impl<'a> Samples<'a> {
+    // Creates the iterator of [count] samples, starting at [from].
+    pub fn new(
+        expression: &str,
+        variable: &str,
+        from: f64,
+        step: f64,
+        count: usize,
+        env: &'a Environment,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            sub: Lexer::lex(expression)?,
+            expression: expression.to_string(),
+            variable: variable.to_string(),
+            scope: env.child(),
+            from,
+            step,
+            count,
+            index: 0,
+        })
+    }
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        // Values are computed from the index, so steps don't accumulate rounding errors.
+        let x = self.from + self.step * self.index as f64;
+        self.index += 1;
+
+        self.scope.set(&self.variable, x);
+        let y = Calculator::calculate_with_env(self.sub.clone(), &self.expression, &mut self.scope);
+        Some((x, y))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.count - self.index;
+        (left, Some(left))
+    }
+}
+
+// The table of values of one or more expressions, side by side, over a range of variable.
+//
+//  table x ^ 2 + 1, sqrt(x - 1) for x = 0..2 step 0.5
+//
+//  x   │ x ^ 2 + 1 │ sqrt(x - 1)
+//  ────┼───────────┼────────────────────────────────────────────────────────
+//    0 │         1 │ error: cannot take square root of negative number: -1
+//  0.5 │      1.25 │ error: cannot take square root of negative number: -0.5
+//    1 │         2 │                                                       0
+//  1.5 │      3.25 │                                       0.707106781186548
+//    2 │         5 │                                                       1
+//
+// Each cell holds the result, or the error of calculation at its row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    expressions: Vec<String>,
+    variable: String,
+    from: f64,
+    to: f64,
+    step: f64,
+    max_rows: usize,
+}
+
+impl Table {
+    // Creates a table of [expression] over `x`, from [from] to [to], with the step of 1.
+    pub fn new(expression: &str, from: f64, to: f64) -> Self {
+        Self {
+            expressions: vec![expression.trim().to_string()],
+            variable: String::from("x"),
+            from,
+            to,
+            step: 1.0,
+            max_rows: MAX_ROWS,
+        }
+    }
+
+    // Parses the table command, without the leading `table`:
+    // `x ^ 2 + 1, 2 * x for x = 0..10 step 0.5`. The step is optional, and bounds can be
+    // expressions, that are calculated in [env].
+    pub fn parse(command: &str, env: &Environment) -> Result<Self, Error> {
+        let usage = "table EXPRESSION[, EXPRESSION...] for VARIABLE = A..B [step S]";
+        let lowercase = command.to_ascii_lowercase();
+
+        let for_at = lowercase
+            .rfind(" for ")
+            .ok_or_else(|| Error::invalid_command(usage))?;
+        let (variable, range) = command[for_at + 5..]
+            .split_once('=')
+            .ok_or_else(|| Error::invalid_command(usage))?;
+
+        let variable = variable.trim();
+        let valid_name = variable
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && variable.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(Error::invalid_command(usage));
+        }
+
+        let (range, step) = match range.to_ascii_lowercase().find(" step ") {
+            Some(i) => (&range[..i], Some(&range[i + 6..])),
+            None => (range, None),
+        };
+        let (from, to) = range
+            .split_once("..")
+            .ok_or_else(|| Error::invalid_command(usage))?;
+
+        let expressions = Table::split(&command[..for_at]);
+        if expressions.iter().any(|e| e.is_empty()) {
+            return Err(Error::invalid_command(usage));
+        }
+
+        let mut table = Table::new(
+            expressions[0],
+            Plot::bound(from, env, usage)?,
+            Plot::bound(to, env, usage)?,
+        )
+        .with_variable(variable);
+        for expression in expressions.iter().skip(1) {
+            table = table.with_expression(expression);
+        }
+        if let Some(step) = step {
+            table = table.with_step(Plot::bound(step, env, usage)?);
+        }
+
+        Ok(table)
+    }
+
+    // Adds another expression, as the next column.
+    pub fn with_expression(mut self, expression: &str) -> Self {
+        self.expressions.push(expression.trim().to_string());
+        self
+    }
+
+    // Sets the variable of expressions, `x` by default.
+    pub fn with_variable(mut self, variable: &str) -> Self {
+        self.variable = variable.to_string();
+        self
+    }
+
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    // Sets the maximum amount of rows, ranges with more rows fail instead of being calculated.
+    pub fn with_max_rows(mut self, max: usize) -> Self {
+        self.max_rows = max;
+        self
+    }
+
+    pub fn expressions(&self) -> &[String] {
+        &self.expressions
+    }
+
+    pub fn variable(&self) -> &str {
+        &self.variable
+    }
+
+    pub fn range(&self) -> (f64, f64) {
+        (self.from, self.to)
+    }
+
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    // The iterator of results of the expression at [index], one for each row.
+    pub fn column<'a>(&self, index: usize, env: &'a Environment) -> Result<Samples<'a>, Error> {
+        let count = self.count()?;
+        let expression = self
+            .expressions
+            .get(index)
+            .map(String::as_str)
+            .unwrap_or_default();
+        Samples::new(expression, &self.variable, self.from, self.step, count, env)
+    }
+
+    // Calculates all rows of table. Fails only if the range is invalid, or an expression
+    // can't be lexed, failed calculations are kept in their cells.
+    pub fn rows(&self, env: &Environment) -> Result<Vec<Row>, Error> {
+        let mut rows: Vec<Row> = Vec::new();
+        for index in 0..self.expressions.len() {
+            for (i, (x, y)) in self.column(index, env)?.enumerate() {
+                match rows.get_mut(i) {
+                    Some((_, cells)) => cells.push(y),
+                    None => rows.push((x, vec![y])),
+                }
+            }
+        }
+
+        Ok(rows)
+    }
+
+    // The amount of rows, the last one is included if the step reaches it.
+    // It's checked against the maximum, before anything is allocated or calculated.
+    fn count(&self) -> Result<usize, Error> {
+        if !(self.from.is_finite() && self.to.is_finite() && self.from <= self.to) {
+            return Err(Error::invalid_range(self.from, self.to));
+        }
+
+        if !(self.step.is_finite() && self.step > 0.0) {
+            return Err(Error::invalid_step(self.step));
+        }
+
+        let count = ((self.to - self.from) / self.step + 1e-9).floor() + 1.0;
+        if count > self.max_rows as f64 {
+            return Err(Error::too_many_rows(count, self.max_rows));
+        }
+
+        Ok(count as usize)
+    }
+
+    // Splits expressions by commas, that aren't inside of parentheses or brackets.
+    fn split(text: &str) -> Vec<&str> {
+        let mut expressions = Vec::new();
+        let (mut depth, mut start) = (0, 0);
+
+        for (i, c) in text.char_indices() {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    expressions.push(text[start..i].trim());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+
+        expressions.push(text[start..].trim());
+        expressions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let mut env = Environment::new();
+        env.set("n", 4.0);
+
+        let usage = "table EXPRESSION[, EXPRESSION...] for VARIABLE = A..B [step S]";
+        type Parsed<'a> = Result<(Vec<&'a str>, &'a str, f64, f64, f64), Error>;
+
+        let test_data: Vec<(&str, Parsed)> = vec![
+            (
+                "x ^ 2 + 1 for x = 0..10 step 0.5",
+                Ok((vec!["x ^ 2 + 1"], "x", 0.0, 10.0, 0.5)),
+            ),
+            (
+                "t * 2, [t, 1] for t = 0 - n..n",
+                Ok((vec!["t * 2", "[t, 1]"], "t", -4.0, 4.0, 1.0)),
+            ),
+            (
+                "integrate(x * k, x, 0, 1), k FOR k = 1..2 STEP 1 / 4",
+                Ok((vec!["integrate(x * k, x, 0, 1)", "k"], "k", 1.0, 2.0, 0.25)),
+            ),
+            ("x for x = 0", Err(Error::invalid_command(usage))),
+            ("x for 2 = 0..1", Err(Error::invalid_command(usage))),
+            ("x, for x = 0..1", Err(Error::invalid_command(usage))),
+            ("x = 0..1", Err(Error::invalid_command(usage))),
+        ];
+
+        for (command, expected) in test_data {
+            let table = Table::parse(command, &env);
+            let table = table.as_ref().map(|t| {
+                let expressions = t.expressions().iter().map(String::as_str).collect();
+                (
+                    expressions,
+                    t.variable(),
+                    t.range().0,
+                    t.range().1,
+                    t.step(),
+                )
+            });
+            assert_eq!(table, expected.as_ref().cloned(), "Failed for: {command}");
+        }
+    }
+
+    #[test]
+    fn column() {
+        let env = Environment::new();
+
+        let table = Table::new("x ^ 2", 0.0, 1.0).with_step(0.1);
+        let samples: Vec<Sample> = table.column(0, &env).unwrap().collect();
+        assert_eq!(samples.len(), 11);
+        assert_eq!(samples[3].0, 0.30000000000000004);
+        assert_eq!(samples[10], (1.0, Ok(1.0)));
+
+        assert_eq!(
+            Table::new("x", 1.0, 0.0).column(0, &env).err(),
+            Some(Error::invalid_range(1.0, 0.0))
+        );
+        assert_eq!(
+            Table::new("x", 0.0, 1.0)
+                .with_step(0.0)
+                .column(0, &env)
+                .err(),
+            Some(Error::invalid_step(0.0))
+        );
+        assert_eq!(
+            Table::new("x", 0.0, 1_000_000.0)
+                .with_step(0.0001)
+                .column(0, &env)
+                .err(),
+            Some(Error::too_many_rows(10_000_000_001.0, MAX_ROWS))
+        );
+        assert_eq!(
+            Table::new("x", 0.0, 9.0)
+                .with_max_rows(10)
+                .column(0, &env)
+                .unwrap()
+                .count(),
+            10
+        );
+        assert_eq!(
+            Table::new("x", 0.0, 10.0)
+                .with_max_rows(10)
+                .column(0, &env)
+                .err(),
+            Some(Error::too_many_rows(11.0, 10))
+        );
+    }
+
+    #[test]
+    fn rows() {
+        let mut env = Environment::new();
+        env.set("x", 100.0);
+
+        let rows = Table::new("x + 1", 0.0, 2.0)
+            .with_expression("1 / (x - 1)")
+            .rows(&env)
+            .unwrap();
+
+        let xs: Vec<f64> = rows.iter().map(|(x, _)| *x).collect();
+        assert_eq!(xs, vec![0.0, 1.0, 2.0]);
+        assert_eq!(rows[0].1, vec![Ok(1.0), Ok(-1.0)]);
+        assert_eq!(rows[1].1[0], Ok(2.0));
+        assert!(rows[1].1[1].is_err());
+        assert_eq!(rows[2].1, vec![Ok(3.0), Ok(1.0)]);
+
+        // The variable is bound in a scope of table, the environment is kept as it's.
+        assert_eq!(env.get("x"), Some(100.0));
+    }
+}
@@ -4,7 +4,7 @@
 // that can be found in the LICENSE file.
 //
 
-use crate::utils::ChUtils;
+use crate::{span::Span, utils::ChUtils};
 
 // The structure model for high level sub expression implementations.
 // That could hold the actual source: tokens, and transformation method.
@@ -53,6 +53,7 @@ pub enum TokenType {
     NUMBER,
     IDENTIFIER,
     ASSIGN,
+    COMMA,
 
     // Sub related tokens
     SUBEXP,
@@ -70,9 +71,11 @@ pub enum TokenType {
     PERCENTAGE,
     POWER,
     FACTORIAL,
+    PLUSMINUS,
 
     // Math functions
     SQRT,
+    CBRT,
     SIN,
     COS,
     TAN,
@@ -82,6 +85,46 @@ pub enum TokenType {
     FLOOR,
     CEIL,
     ROUND,
+
+    // Multi-argument functions
+    INTEGRATE,
+}
+
+// A built-in function, with its call signature and a short description.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub typ: TokenType,
+    pub signature: &'static str,
+    pub description: &'static str,
+}
+
+// Every built-in function, in the order of [TokenType].
+pub const BUILTINS: [Builtin; 12] = [
+    Builtin { name: "sqrt", typ: TokenType::SQRT, signature: "sqrt(x)", description: "Square root" },
+    Builtin { name: "cbrt", typ: TokenType::CBRT, signature: "cbrt(x)", description: "Cube root" },
+    Builtin { name: "sin", typ: TokenType::SIN, signature: "sin(x)", description: "Sine (radians)" },
+    Builtin { name: "cos", typ: TokenType::COS, signature: "cos(x)", description: "Cosine (radians)" },
+    Builtin { name: "tan", typ: TokenType::TAN, signature: "tan(x)", description: "Tangent (radians)" },
+    Builtin { name: "log", typ: TokenType::LOG, signature: "log(x)", description: "Base-10 logarithm" },
+    Builtin { name: "ln", typ: TokenType::LN, signature: "ln(x)", description: "Natural logarithm" },
+    Builtin { name: "exp", typ: TokenType::EXP, signature: "exp(x)", description: "Exponential (e^x)" },
+    Builtin { name: "floor", typ: TokenType::FLOOR, signature: "floor(x)", description: "Round down" },
+    Builtin { name: "ceil", typ: TokenType::CEIL, signature: "ceil(x)", description: "Round up" },
+    Builtin { name: "round", typ: TokenType::ROUND, signature: "round(x)", description: "Round to nearest" },
+    Builtin {
+        name: "integrate",
+        typ: TokenType::INTEGRATE,
+        signature: "integrate(expr, x, a, b[, tolerance])",
+        description: "Definite integral of expr over x from a to b",
+    },
+];
+
+impl Builtin {
+    // Finds the built-in function by its name, case insensitive like [Token::from].
+    pub fn find(name: &str) -> Option<&'static Builtin> {
+        BUILTINS.iter().find(|b| b.name.eq_ignore_ascii_case(name))
+    }
 }
 
 // The main structure of input's each parsed character.
@@ -90,10 +133,10 @@ pub struct Token {
     pub typ: TokenType,
     pub literal: String,
     pub sub: Sub,
-    // the index range of concrete token.
-    // [-1] represents the unknown index.
-    // left side is the starting point and right side is ending point.
-    pub index: (i32, i32),
+    // The bytes of input, that the token is read from.
+    // Sub-expressions cover all of their tokens, and tokens that don't come
+    // from input, like the product of `4(2 + 10)`, take an empty span at their place.
+    pub span: Span,
 }
 
 impl TokenType {
@@ -112,43 +155,53 @@ impl TokenType {
 
 impl Token {
     // Define a new Token value by providing all fields.
-    pub fn new(typ: TokenType, literal: String, sub: Sub, index: (i32, i32)) -> Self {
+    pub fn new(typ: TokenType, literal: String, sub: Sub, span: Span) -> Self {
         Self {
             typ,
             literal,
             sub,
-            index,
+            span,
         }
     }
 
     // Create a new sub token model with just sub tokens.
+    // The span of sub token is the smallest span, that covers all of its tokens.
     pub fn new_sub(tokens: Vec<Token>, method: SubMethod) -> Self {
+        let span = tokens
+            .iter()
+            .map(|t| t.span)
+            .reduce(Span::join)
+            .unwrap_or_default();
+
         Self {
             typ: TokenType::SUBEXP,
             literal: String::new(),
             sub: Sub { tokens, method },
-            index: Token::unknown_index(),
+            span,
         }
     }
 
     // Creates a pointer token, that newer will be used
     // at normal token result.
-    pub fn new_pointer(i: usize, method: SubMethod) -> Self {
+    // The [span] covers the brackets of pointed sub-expression, with both of them included.
+    pub fn new_pointer(i: usize, method: SubMethod, span: Span) -> Self {
         Self {
             typ: TokenType::POINTER,
             literal: format!("{i}"),
             sub: Sub::new(Vec::new(), method),
-            index: Token::unknown_index(),
+            span,
         }
     }
 
     // Create a new token model from a literal.
     // The type is decided automatically by checking it.
-    pub fn from(mut literal: String, index: (i32, i32)) -> Self {
-        let typ = if literal.is_number() {
+    pub fn from(mut literal: String, span: Span) -> Self {
+        // Unicode symbols are typed by their native spelling, but literal is kept as typed.
+        let normalized = literal.normalized();
+        let typ = if normalized.is_number() || literal.constant().is_some() {
             TokenType::NUMBER
         } else {
-            match literal.trim().to_lowercase().as_str() {
+            match normalized.trim().to_lowercase().as_str() {
                 "+" => TokenType::PLUS,
                 "-" => TokenType::MINUS,
                 "*" | "•" => TokenType::PRODUCT,
@@ -158,9 +211,11 @@ impl Token {
                 "%" => TokenType::PERCENTAGE,
                 "^" => TokenType::POWER,
                 "!" => TokenType::FACTORIAL,
-                "[" => TokenType::LABS,
+                "±" => TokenType::PLUSMINUS,
+                "[" | "|" => TokenType::LABS,
                 "]" => TokenType::RABS,
                 "sqrt" => TokenType::SQRT,
+                "cbrt" => TokenType::CBRT,
                 "sin" => TokenType::SIN,
                 "cos" => TokenType::COS,
                 "tan" => TokenType::TAN,
@@ -170,11 +225,14 @@ impl Token {
                 "floor" => TokenType::FLOOR,
                 "ceil" => TokenType::CEIL,
                 "round" => TokenType::ROUND,
+                "integrate" => TokenType::INTEGRATE,
                 "=" => TokenType::ASSIGN,
+                "," => TokenType::COMMA,
                 _ => {
-                    // Check if it's a valid identifier (starts with letter, contains only alphanumeric)
+                    // Check if it's a valid identifier (starts with letter or underscore, contains only alphanumeric)
                     let trimmed = literal.trim();
-                    if !trimmed.is_empty() && trimmed.chars().next().unwrap().is_alphabetic()
+                    let first = trimmed.chars().next().unwrap_or_default();
+                    if !trimmed.is_empty() && (first.is_alphabetic() || first == '_')
                         && trimmed.chars().all(|c| c.is_alphanumeric() || c == '_') {
                         TokenType::IDENTIFIER
                     } else {
@@ -191,7 +249,7 @@ impl Token {
             typ,
             literal,
             sub: Sub::empty(),
-            index,
+            span,
         }
     }
 
@@ -201,7 +259,7 @@ impl Token {
             typ: TokenType::ILLEGAL,
             literal: String::new(),
             sub: Sub::empty(),
-            index: (0, 0),
+            span: Span::default(),
         }
     }
 
@@ -217,11 +275,6 @@ impl Token {
         }
     }
 
-    // Returns the default unknown index representation.
-    pub fn unknown_index() -> (i32, i32) {
-        (-1, -1)
-    }
-
     // Takes the pointer's index as [usize].
     // If current token is not an pointer token, returned option will be [None].
     pub fn take_pointer_index(&self) -> Option<usize> {
@@ -272,6 +325,10 @@ impl Token {
         matches!(self.typ, TokenType::FACTORIAL)
     }
 
+    pub fn is_plusminus(&self) -> bool {
+        matches!(self.typ, TokenType::PLUSMINUS)
+    }
+
     pub fn is_identifier(&self) -> bool {
         matches!(self.typ, TokenType::IDENTIFIER)
     }
@@ -280,10 +337,15 @@ impl Token {
         matches!(self.typ, TokenType::ASSIGN)
     }
 
+    pub fn is_comma(&self) -> bool {
+        matches!(self.typ, TokenType::COMMA)
+    }
+
     pub fn is_function(&self) -> bool {
         matches!(
             self.typ,
             TokenType::SQRT
+                | TokenType::CBRT
                 | TokenType::SIN
                 | TokenType::COS
                 | TokenType::TAN
@@ -293,9 +355,16 @@ impl Token {
                 | TokenType::FLOOR
                 | TokenType::CEIL
                 | TokenType::ROUND
+                | TokenType::INTEGRATE
         )
     }
 
+    // Checks if the token is a function that takes a comma separated argument list,
+    // like `integrate(x ^ 2, x, 0, 1)`, instead of a single argument.
+    pub fn takes_arguments(&self) -> bool {
+        matches!(self.typ, TokenType::INTEGRATE)
+    }
+
     // Checks the "parentheses" family tokens' matching to each other.
     // So, if pointed(self) token is left-parentheses
     // given token(t) should be right-parentheses, if not returns false.
@@ -315,6 +384,18 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
+    #[test]
+    fn builtins() {
+        for builtin in BUILTINS.iter() {
+            let token = Token::from(builtin.name.to_string(), Span::new(0, 1));
+            assert_eq!(token.typ, builtin.typ, "Failed for: {}", builtin.name);
+            assert!(builtin.signature.starts_with(&format!("{}(", builtin.name)));
+        }
+
+        assert_eq!(Builtin::find("SQRT").map(|b| b.typ.clone()), Some(TokenType::SQRT));
+        assert_eq!(Builtin::find("sqr"), None);
+    }
+
     #[test]
     fn new_sub_struct() {
         let test_data: Vec<Sub> = vec![
@@ -362,32 +443,32 @@ mod tests {
                 typ: TokenType::PLUS,
                 literal: String::from("+"),
                 sub: Sub::empty(),
-                index: (0, 0),
+                span: Span::new(0, 1),
             },
             Token {
                 typ: TokenType::MINUS,
                 literal: String::from("-"),
                 sub: Sub::empty(),
-                index: (1, 1),
+                span: Span::new(1, 2),
             },
             Token {
                 typ: TokenType::DIVIDE,
                 literal: String::from("/"),
                 sub: Sub::empty(),
-                index: (2, 2),
+                span: Span::new(2, 3),
             },
             Token {
                 typ: TokenType::SUBEXP,
                 literal: String::from(""),
                 sub: Sub::new(
                     Vec::from([
-                        Token::from(String::from("2"), (0, 0)),
-                        Token::from(String::from("+"), (1, 1)),
-                        Token::from(String::from("5"), (2, 2)),
+                        Token::from(String::from("2"), Span::new(0, 1)),
+                        Token::from(String::from("+"), Span::new(1, 2)),
+                        Token::from(String::from("5"), Span::new(2, 3)),
                     ]),
                     SubMethod::PAREN,
                 ),
-                index: (0, 2),
+                span: Span::new(0, 3),
             },
         ];
 
@@ -396,13 +477,13 @@ mod tests {
                 t.clone().typ,
                 t.clone().literal,
                 t.clone().sub,
-                t.clone().index,
+                t.clone().span,
             );
 
             assert_eq!(res.typ, t.clone().typ);
             assert_eq!(res.literal, t.clone().literal);
             assert_eq!(res.sub, t.clone().sub);
-            assert_eq!(res.index, t.clone().index);
+            assert_eq!(res.span, t.clone().span);
         }
     }
 
@@ -416,13 +497,13 @@ mod tests {
                     literal: String::new(),
                     sub: Sub::new(
                         Vec::from([
-                            Token::from(String::from("4"), (0, 0)),
-                            Token::from(String::from("+"), (0, 0)),
-                            Token::from(String::from("2"), (0, 0)),
+                            Token::from(String::from("4"), Span::new(0, 1)),
+                            Token::from(String::from("+"), Span::new(0, 1)),
+                            Token::from(String::from("2"), Span::new(0, 1)),
                         ]),
                         SubMethod::PAREN,
                     ),
-                    index: Token::unknown_index(),
+                    span: Span::new(0, 1),
                 },
             ),
             (
@@ -432,25 +513,25 @@ mod tests {
                     literal: String::new(),
                     sub: Sub::new(
                         Vec::from([
-                            Token::from(String::from("2"), (0, 0)),
-                            Token::from(String::from("+"), (0, 0)),
-                            Token::from(String::from("+"), (0, 0)),
+                            Token::from(String::from("2"), Span::new(0, 1)),
+                            Token::from(String::from("+"), Span::new(0, 1)),
+                            Token::from(String::from("+"), Span::new(0, 1)),
                         ]),
                         SubMethod::PAREN,
                     ),
-                    index: Token::unknown_index(),
+                    span: Span::new(0, 1),
                 },
             ),
         ]);
 
         for (t, expected) in test_data {
-            let tokens = t.into_iter().map(|tt| Token::from(tt, (0, 0))).collect();
+            let tokens = t.into_iter().map(|tt| Token::from(tt, Span::new(0, 1))).collect();
             let res = Token::new_sub(tokens, SubMethod::PAREN);
 
             assert_eq!(res.typ, expected.clone().typ);
             assert_eq!(res.literal, expected.clone().literal);
             assert_eq!(res.sub, expected.clone().sub);
-            assert_eq!(res.index, expected.clone().index);
+            assert_eq!(res.span, expected.clone().span);
         }
     }
 
@@ -463,7 +544,7 @@ mod tests {
                     TokenType::POINTER,
                     String::from("0"),
                     Sub::new(Vec::new(), SubMethod::PAREN),
-                    (-1, -1),
+                    Span::new(2, 3),
                 ),
             ),
             (
@@ -472,59 +553,59 @@ mod tests {
                     TokenType::POINTER,
                     String::from("99"),
                     Sub::new(Vec::new(), SubMethod::ABS),
-                    (-1, -1),
+                    Span::new(2, 3),
                 ),
             ),
         ]);
 
         for (i, expected) in test_data {
-            let token: Token = Token::new_pointer(i, expected.clone().sub.method);
+            let token: Token = Token::new_pointer(i, expected.clone().sub.method, Span::new(2, 3));
             assert_eq!(token, expected);
         }
     }
 
     #[test]
     fn from() {
-        let test_data: HashMap<(String, (i32, i32)), Token> = HashMap::from([
+        let test_data: HashMap<(String, Span), Token> = HashMap::from([
             (
-                (String::from("42"), (0, 1)),
-                Token::new(TokenType::NUMBER, String::from("42"), Sub::empty(), (0, 1)),
+                (String::from("42"), Span::new(0, 2)),
+                Token::new(TokenType::NUMBER, String::from("42"), Sub::empty(), Span::new(0, 2)),
             ),
             (
-                (String::from("}"), (0, 0)),
-                Token::new(TokenType::ILLEGAL, String::from("}"), Sub::empty(), (0, 0)),
+                (String::from("}"), Span::new(0, 1)),
+                Token::new(TokenType::ILLEGAL, String::from("}"), Sub::empty(), Span::new(0, 1)),
             ),
             (
-                (String::from("+"), (0, 0)),
-                Token::new(TokenType::PLUS, String::from("+"), Sub::empty(), (0, 0)),
+                (String::from("+"), Span::new(0, 1)),
+                Token::new(TokenType::PLUS, String::from("+"), Sub::empty(), Span::new(0, 1)),
             ),
             (
-                (String::from("-"), (0, 0)),
-                Token::new(TokenType::MINUS, String::from("-"), Sub::empty(), (0, 0)),
+                (String::from("-"), Span::new(0, 1)),
+                Token::new(TokenType::MINUS, String::from("-"), Sub::empty(), Span::new(0, 1)),
             ),
             (
-                (String::from("*"), (0, 0)),
-                Token::new(TokenType::PRODUCT, String::from("*"), Sub::empty(), (0, 0)),
+                (String::from("*"), Span::new(0, 1)),
+                Token::new(TokenType::PRODUCT, String::from("*"), Sub::empty(), Span::new(0, 1)),
             ),
             (
-                (String::from("•"), (0, 0)),
-                Token::new(TokenType::PRODUCT, String::from("•"), Sub::empty(), (0, 0)),
+                (String::from("•"), Span::new(0, 1)),
+                Token::new(TokenType::PRODUCT, String::from("•"), Sub::empty(), Span::new(0, 1)),
             ),
             (
-                (String::from("/"), (0, 0)),
-                Token::new(TokenType::DIVIDE, String::from("/"), Sub::empty(), (0, 0)),
+                (String::from("/"), Span::new(0, 1)),
+                Token::new(TokenType::DIVIDE, String::from("/"), Sub::empty(), Span::new(0, 1)),
             ),
             (
-                (String::from(":"), (0, 0)),
-                Token::new(TokenType::DIVIDE, String::from(":"), Sub::empty(), (0, 0)),
+                (String::from(":"), Span::new(0, 1)),
+                Token::new(TokenType::DIVIDE, String::from(":"), Sub::empty(), Span::new(0, 1)),
             ),
             (
-                (String::from("%"), (0, 0)),
+                (String::from("%"), Span::new(0, 1)),
                 Token::new(
                     TokenType::PERCENTAGE,
                     String::from("%"),
                     Sub::empty(),
-                    (0, 0),
+                    Span::new(0, 1),
                 ),
             ),
         ]);
@@ -536,17 +617,43 @@ mod tests {
     }
 
     #[test]
-    fn unknown_index() {
-        assert_eq!(Token::unknown_index(), (-1, -1));
+    fn sub_span() {
+        let test_data: Vec<(Vec<Token>, Span)> = vec![
+            (Vec::new(), Span::default()),
+            (vec![Token::from(String::from("4"), Span::new(3, 4))], Span::new(3, 4)),
+            (
+                vec![
+                    Token::from(String::from("2"), Span::new(0, 1)),
+                    Token::from(String::from("•"), Span::new(2, 5)),
+                    Token::from(String::from("3"), Span::new(6, 7)),
+                ],
+                Span::new(0, 7),
+            ),
+            (
+                vec![
+                    Token::from(String::from("4"), Span::new(0, 1)),
+                    Token::from(String::from("*"), Span::at(1)),
+                    Token::new_sub(
+                        vec![Token::from(String::from("2"), Span::new(2, 3))],
+                        SubMethod::PAREN,
+                    ),
+                ],
+                Span::new(0, 3),
+            ),
+        ];
+
+        for (tokens, expected) in test_data {
+            assert_eq!(Token::new_sub(tokens, SubMethod::PAREN).span, expected);
+        }
     }
 
     #[test]
     fn take_pointer_index() {
         let test_data: HashMap<Option<usize>, Token> = HashMap::from([
-            (None, Token::from(String::from("25"), (0, 1))),
-            (None, Token::from(String::from("-"), (0, 0))),
-            (Some(0), Token::new_pointer(0, SubMethod::PAREN)),
-            (Some(9), Token::new_pointer(9, SubMethod::PAREN)),
+            (None, Token::from(String::from("25"), Span::new(0, 2))),
+            (None, Token::from(String::from("-"), Span::new(0, 1))),
+            (Some(0), Token::new_pointer(0, SubMethod::PAREN, Span::default())),
+            (Some(9), Token::new_pointer(9, SubMethod::PAREN, Span::default())),
         ]);
 
         for (expected, token) in test_data {
@@ -557,10 +664,10 @@ mod tests {
     #[test]
     fn is_illegal() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (true, Token::from(String::from("}"), (0, 0))),
-            (true, Token::from(String::from("|"), (0, 0))),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (true, Token::from(String::from("}"), Span::new(0, 1))),
+            (true, Token::from(String::from("$"), Span::new(0, 1))),
         ]);
 
         for (expected, token) in test_data {
@@ -571,10 +678,10 @@ mod tests {
     #[test]
     fn is_lparen() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (false, Token::from(String::from(")"), (0, 0))),
-            (true, Token::from(String::from("("), (0, 0))),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (false, Token::from(String::from(")"), Span::new(0, 1))),
+            (true, Token::from(String::from("("), Span::new(0, 1))),
         ]);
 
         for (expected, token) in test_data {
@@ -585,10 +692,10 @@ mod tests {
     #[test]
     fn is_rparen() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (false, Token::from(String::from("("), (0, 0))),
-            (true, Token::from(String::from(")"), (0, 0))),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (false, Token::from(String::from("("), Span::new(0, 1))),
+            (true, Token::from(String::from(")"), Span::new(0, 1))),
         ]);
 
         for (expected, token) in test_data {
@@ -599,10 +706,10 @@ mod tests {
     #[test]
     fn is_pointer() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (false, Token::from(String::from("("), (0, 0))),
-            (true, Token::new_pointer(0, SubMethod::PAREN)),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (false, Token::from(String::from("("), Span::new(0, 1))),
+            (true, Token::new_pointer(0, SubMethod::PAREN, Span::default())),
         ]);
 
         for (expected, token) in test_data {
@@ -613,9 +720,9 @@ mod tests {
     #[test]
     fn is_sub_exp() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (false, Token::from(String::from("("), (0, 0))),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (false, Token::from(String::from("("), Span::new(0, 1))),
             (true, Token::new_sub(vec![], SubMethod::PAREN)),
         ]);
 
@@ -627,10 +734,10 @@ mod tests {
     #[test]
     fn is_power() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (false, Token::from(String::from("("), (0, 0))),
-            (true, Token::from(String::from("^"), (0, 0))),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (false, Token::from(String::from("("), Span::new(0, 1))),
+            (true, Token::from(String::from("^"), Span::new(0, 1))),
         ]);
 
         for (expected, token) in test_data {
@@ -641,10 +748,10 @@ mod tests {
     #[test]
     fn is_labs() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (false, Token::from(String::from("]"), (0, 0))),
-            (true, Token::from(String::from("["), (0, 0))),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (false, Token::from(String::from("]"), Span::new(0, 1))),
+            (true, Token::from(String::from("["), Span::new(0, 1))),
         ]);
 
         for (expected, token) in test_data {
@@ -655,10 +762,10 @@ mod tests {
     #[test]
     fn is_rabs() {
         let test_data: HashMap<bool, Token> = HashMap::from([
-            (false, Token::from(String::from("-25"), (0, 1))),
-            (false, Token::from(String::from("-"), (0, 0))),
-            (false, Token::from(String::from("["), (0, 0))),
-            (true, Token::from(String::from("]"), (0, 0))),
+            (false, Token::from(String::from("-25"), Span::new(0, 2))),
+            (false, Token::from(String::from("-"), Span::new(0, 1))),
+            (false, Token::from(String::from("["), Span::new(0, 1))),
+            (true, Token::from(String::from("]"), Span::new(0, 1))),
         ]);
 
         for (expected, token) in test_data {
@@ -672,22 +779,22 @@ mod tests {
             (
                 true,
                 (
-                    Token::from(String::from("("), (0, 0)),
-                    Token::from(String::from(")"), (0, 0)),
+                    Token::from(String::from("("), Span::new(0, 1)),
+                    Token::from(String::from(")"), Span::new(0, 1)),
                 ),
             ),
             (
                 true,
                 (
-                    Token::from(String::from("["), (0, 0)),
-                    Token::from(String::from("]"), (0, 0)),
+                    Token::from(String::from("["), Span::new(0, 1)),
+                    Token::from(String::from("]"), Span::new(0, 1)),
                 ),
             ),
             (
                 false,
                 (
-                    Token::from(String::from("0"), (0, 0)),
-                    Token::from(String::from("1"), (0, 0)),
+                    Token::from(String::from("0"), Span::new(0, 1)),
+                    Token::from(String::from("1"), Span::new(0, 1)),
                 ),
             ),
         ]);
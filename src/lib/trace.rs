@@ -0,0 +1,250 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{span::Span, token::Token, value::Value};
+use std::fmt;
+
+// A single reduction step of calculation.
+//
+// [span] is the range of bytes in input, that was reduced,
+// [source] is the text of that range, and [value] is what it was reduced to.
+// [expression] is the whole input, after applying this and every previous step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub span: Span,
+    pub source: String,
+    pub value: Value,
+    pub expression: String,
+}
+
+// The evaluation trace: an ordered list of reduction steps, that calculator
+// made to reach the final answer.
+//
+//  ╭─────────────╮     ╭───────╮     ╭────╮
+//  │ (2 + 5) * 3 │ ──▶ │ 7 * 3 │ ──▶ │ 21 │
+//  ╰─────────────╯     ╰───────╯     ╰────╯
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    input: String,
+    steps: Vec<Step>,
+    // Already reduced spans, with the text they were replaced by.
+    reduced: Vec<(Span, String)>,
+}
+
+impl Trace {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.to_string(),
+            steps: Vec::new(),
+            reduced: Vec::new(),
+        }
+    }
+
+    pub fn input(&self) -> String {
+        self.input.clone()
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    // Returns the input, and the expression after each step.
+    pub fn reductions(&self) -> Vec<String> {
+        let mut reductions = vec![self.input.trim().to_string()];
+        reductions.extend(self.steps.iter().map(|s| s.expression.clone()));
+        reductions
+    }
+
+    // Records the reduction of [tokens] to [value].
+    // Steps, that don't change the expression, like `(5)` to `5`, are skipped.
+    pub(crate) fn record(&mut self, tokens: &[Token], value: Value) {
+        let span = match self.span(tokens) {
+            Some(span) => span,
+            None => return,
+        };
+
+        self.reduced
+            .retain(|(reduced, _)| reduced.end <= span.start || reduced.start >= span.end);
+        self.reduced.push((span, value.to_string()));
+        self.reduced.sort_by_key(|(span, _)| span.start);
+
+        let expression = self.render();
+        let previous = match self.steps.last() {
+            Some(step) => step.expression.clone(),
+            None => self.input.trim().to_string(),
+        };
+        if expression == previous {
+            return;
+        }
+
+        self.steps.push(Step {
+            span,
+            source: span.text(&self.input).to_string(),
+            value,
+            expression,
+        });
+    }
+
+    // Renders the input, with every reduced span replaced by its value.
+    fn render(&self) -> String {
+        let mut result = String::new();
+        let mut i: usize = 0;
+
+        for (span, text) in self.reduced.iter() {
+            result.push_str(&self.input[i..span.start]);
+
+            // Keep the parentheses of function arguments: `sqrt(25)` not `sqrt25`.
+            let is_argument = result.chars().last().is_some_and(char::is_alphanumeric);
+            if is_argument {
+                result.push_str(&format!("({text})"));
+            } else {
+                result.push_str(text);
+            }
+
+            i = span.end;
+        }
+        result.push_str(&self.input[i.min(self.input.len())..]);
+
+        result.trim().to_string()
+    }
+
+    // Calculates the span of tokens in input, extended by the brackets around them.
+    // Tokens that don't come from input, like the product of `4(2 + 10)`, are skipped.
+    fn span(&self, tokens: &[Token]) -> Option<Span> {
+        let spans = tokens.iter().filter_map(|t| {
+            if t.is_sub_exp() {
+                return self.span(&t.sub.tokens);
+            }
+
+            Some(t.span).filter(|s| !s.is_empty())
+        });
+
+        let span = spans.reduce(Span::join)?;
+        if span.end > self.input.len() {
+            return None;
+        }
+
+        Some(self.extend(span))
+    }
+
+    // Extends the span by the matching brackets, that surround it: `2 + 5` to `(2 + 5)`.
+    // Brackets and white-spaces are single bytes, so the input is scanned by bytes.
+    fn extend(&self, mut span: Span) -> Span {
+        let bytes = self.input.as_bytes();
+        loop {
+            let before = bytes[..span.start]
+                .iter()
+                .rposition(|c| !c.is_ascii_whitespace());
+            let after = bytes[span.end..]
+                .iter()
+                .position(|c| !c.is_ascii_whitespace())
+                .map(|p| span.end + p);
+
+            let (open, close) = match (before, after) {
+                (Some(open), Some(close)) => (open, close),
+                _ => return span,
+            };
+
+            let pair = (bytes[open], bytes[close]);
+            if !matches!(pair, (b'(', b')') | (b'[', b']')) || !self.matches(open, close) {
+                return span;
+            }
+
+            span = Span::new(open, close + 1);
+        }
+    }
+
+    // Checks if the bracket at [open] is closed by the bracket at [close].
+    fn matches(&self, open: usize, close: usize) -> bool {
+        let mut depth: usize = 0;
+        for (i, c) in self.input.bytes().enumerate().take(close + 1).skip(open) {
+            match c {
+                b'(' | b'[' => depth += 1,
+                b')' | b']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i == close;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        false
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reductions().join(" → "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+
+    #[test]
+    fn record() {
+        let input = "(2 + 5) * 3";
+        let sub = Lexer::lex(input).unwrap();
+        let mut trace = Trace::new(input);
+
+        trace.record(&sub.tokens[..1], Value::Number(7.0));
+        trace.record(&sub.tokens, Value::Number(21.0));
+
+        assert_eq!(
+            trace.steps(),
+            &[
+                Step {
+                    span: Span::new(0, 7),
+                    source: String::from("(2 + 5)"),
+                    value: Value::Number(7.0),
+                    expression: String::from("7 * 3"),
+                },
+                Step {
+                    span: Span::new(0, 11),
+                    source: String::from("(2 + 5) * 3"),
+                    value: Value::Number(21.0),
+                    expression: String::from("21"),
+                },
+            ]
+        );
+        assert_eq!(trace.to_string(), "(2 + 5) * 3 → 7 * 3 → 21");
+    }
+
+    #[test]
+    fn skips_unchanged() {
+        let input = "(5)";
+        let sub = Lexer::lex(input).unwrap();
+        let mut trace = Trace::new(input);
+
+        trace.record(&sub.tokens, Value::Number(5.0));
+        assert_eq!(trace.reductions(), vec!["(5)", "5"]);
+
+        trace.record(&sub.tokens, Value::Number(5.0));
+        assert_eq!(trace.steps().len(), 1);
+    }
+
+    #[test]
+    fn extend() {
+        let cases: Vec<(&str, Span, Span)> = vec![
+            ("(2 + 5)", Span::new(1, 6), Span::new(0, 7)),
+            ("( (2) )", Span::new(3, 4), Span::new(0, 7)),
+            ("[2] * 3", Span::new(1, 2), Span::new(0, 3)),
+            ("(1) * (2)", Span::new(1, 8), Span::new(1, 8)),
+            ("(1 ]", Span::new(1, 2), Span::new(1, 2)),
+            ("(2 • 3)", Span::new(1, 8), Span::new(0, 9)),
+        ];
+
+        for (input, span, expected) in cases {
+            let trace = Trace::new(input);
+            assert_eq!(trace.extend(span), expected, "Failed for: {input}");
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    calculator::Calculator,
+    errors::Error,
+    span::Span,
+    token::TokenType,
+    value::{Domain, Value},
+};
+use std::{
+    collections::BTreeMap,
+    f64::consts::LN_10,
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// The counter of independent uncertainty sources.
+// Each `±` creates a new source, that is identified by the next number of it.
+static SOURCES: AtomicUsize = AtomicUsize::new(0);
+
+// A value with a standard uncertainty: `12.3 ± 0.2`.
+//
+// Uncertainties are propagated by the first-order (linear) error propagation.
+// Instead of storing the sigma itself, the value keeps its sensitivity to every
+// independent source of uncertainty, which it was calculated from:
+//
+//  ╭────────────────────╮     ╭────────────────────────────────────╮
+//  │ x = 12.3 ± 0.2     │ ──▶ │ value: 12.3, terms: { #1: 0.2 }    │
+//  │ 2 * x              │ ──▶ │ value: 24.6, terms: { #1: 0.4 }    │
+//  │ x - x              │ ──▶ │ value: 0,    terms: { #1: 0 }      │
+//  ╰────────────────────╯     ╰────────────────────────────────────╯
+//
+// So, reuses of the same variable are fully correlated, and `x - x` is `0 ± 0`.
+// The sigma is the root of sum of squared terms.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uncertain {
+    value: f64,
+    terms: BTreeMap<usize, f64>,
+}
+
+impl Uncertain {
+    // Creates a new value with an independent standard uncertainty [sigma].
+    // Sigma must be a non-negative finite number.
+    pub fn new(value: f64, sigma: f64) -> Result<Self, Error> {
+        if !(sigma >= 0.0 && sigma.is_finite()) {
+            return Err(Error::invalid_uncertainty(sigma));
+        }
+
+        let source = SOURCES.fetch_add(1, Ordering::Relaxed);
+        Ok(Self {
+            value,
+            terms: BTreeMap::from([(source, sigma)]),
+        })
+    }
+
+    // Creates an exact value, that has no uncertainty.
+    pub fn exact(value: f64) -> Self {
+        Self {
+            value,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    // The standard uncertainty of the value.
+    pub fn sigma(&self) -> f64 {
+        self.terms.values().fold(0.0, |sum, t| sum + t * t).sqrt()
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.terms.values().all(|t| *t == 0.0)
+    }
+
+    // The covariance of two values, caused by their common sources of uncertainty.
+    pub fn covariance(&self, other: &Uncertain) -> f64 {
+        self.terms
+            .iter()
+            .filter_map(|(source, t)| other.terms.get(source).map(|o| t * o))
+            .fold(0.0, |sum, c| sum + c)
+    }
+
+    pub fn add(&self, other: &Uncertain) -> Uncertain {
+        Uncertain::linear(self.value + other.value, &[(self, 1.0), (other, 1.0)])
+    }
+
+    pub fn sub(&self, other: &Uncertain) -> Uncertain {
+        Uncertain::linear(self.value - other.value, &[(self, 1.0), (other, -1.0)])
+    }
+
+    pub fn mul(&self, other: &Uncertain) -> Uncertain {
+        Uncertain::linear(
+            self.value * other.value,
+            &[(self, other.value), (other, self.value)],
+        )
+    }
+
+    // Divides the value by [other].
+    // Returns none, if the value of [other] is zero.
+    pub fn div(&self, other: &Uncertain) -> Option<Uncertain> {
+        if other.value == 0.0 {
+            return None;
+        }
+
+        let value = self.value / other.value;
+        Some(Uncertain::linear(
+            value,
+            &[(self, 1.0 / other.value), (other, -value / other.value)],
+        ))
+    }
+
+    pub fn abs(&self) -> Uncertain {
+        let sign = if self.value < 0.0 { -1.0 } else { 1.0 };
+        Uncertain::linear(self.value.abs(), &[(self, sign)])
+    }
+
+    // Raises the value to the power of [exp].
+    pub fn pow(&self, exp: &Uncertain) -> Result<Uncertain, Error> {
+        let value = f64::powf(self.value, exp.value);
+        let base_derivative = exp.value * f64::powf(self.value, exp.value - 1.0);
+        let exp_derivative = value * self.value.ln();
+
+        Uncertain::propagate(
+            "^",
+            value,
+            &[(self, base_derivative), (exp, exp_derivative)],
+        )
+    }
+
+    // Creates the value with first-order derivatives of the result, by each operand.
+    // Operands without uncertainty are ignored, so their derivatives may be non-finite.
+    fn propagate(
+        operation: &str,
+        value: f64,
+        parts: &[(&Uncertain, f64)],
+    ) -> Result<Uncertain, Error> {
+        for (operand, derivative) in parts {
+            if !derivative.is_finite() && !operand.is_exact() {
+                return Err(Error::uncertainty_not_propagated(operation, operand.value));
+            }
+        }
+
+        let parts: Vec<(&Uncertain, f64)> = parts
+            .iter()
+            .filter(|(operand, _)| !operand.is_exact())
+            .copied()
+            .collect();
+
+        Ok(Uncertain::linear(value, &parts))
+    }
+
+    // Creates the value from the linear combination of operand terms.
+    fn linear(value: f64, parts: &[(&Uncertain, f64)]) -> Uncertain {
+        let mut terms: BTreeMap<usize, f64> = BTreeMap::new();
+        for (operand, derivative) in parts {
+            for (source, t) in operand.terms.iter() {
+                *terms.entry(*source).or_insert(0.0) += derivative * t;
+            }
+        }
+
+        Uncertain { value, terms }
+    }
+}
+
+impl fmt::Display for Uncertain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ± {}", self.value, self.sigma())
+    }
+}
+
+impl Domain for Uncertain {
+    fn from_number(n: f64) -> Self {
+        Uncertain::exact(n)
+    }
+
+    fn from_value(name: &str, value: &Value) -> Result<Self, Error> {
+        match value {
+            Value::Number(n) => Ok(Uncertain::exact(*n)),
+            Value::Uncertain(u) => Ok(u.clone()),
+            _ => Err(Error::incompatible_variable(
+                name,
+                value.kind(),
+                "uncertainty",
+            )),
+        }
+    }
+
+    fn into_value(self) -> Value {
+        Value::Uncertain(self)
+    }
+
+    fn to_number(&self) -> Option<f64> {
+        if self.is_exact() {
+            return Some(self.value);
+        }
+
+        None
+    }
+
+    fn operation(
+        x: Self,
+        y: Self,
+        operation: TokenType,
+        input: &str,
+        span: Span,
+    ) -> Result<Self, Error> {
+        let result = match operation {
+            TokenType::PLUS => x.add(&y),
+            TokenType::MINUS => x.sub(&y),
+            TokenType::PRODUCT => x.mul(&y),
+            TokenType::DIVIDE => x
+                .div(&y)
+                .ok_or_else(|| Error::division_by_zero(input.to_string(), span))?,
+            TokenType::PERCENTAGE => x.mul(&y).mul(&Uncertain::exact(0.01)),
+            TokenType::POWER => x.pow(&y)?,
+            TokenType::PLUSMINUS => {
+                let sigma = y.to_number().ok_or_else(|| {
+                    Error::invalid_arguments(
+                        "±",
+                        &format!("the uncertainty must be an exact number: {y}"),
+                    )
+                })?;
+
+                x.add(&Uncertain::new(0.0, sigma)?)
+            }
+            _ => return Err(Error::invalid_operation(&operation)),
+        };
+
+        Ok(result)
+    }
+
+    fn function(func: TokenType, arg: Self, input: &str, span: Span) -> Result<Self, Error> {
+        // The value itself is calculated as usual, with the same domain errors.
+        let value = Calculator::execute_function(func.clone(), arg.value, input, span)?;
+
+        let x = arg.value;
+        let (name, derivative) = match func {
+            TokenType::SQRT => ("sqrt", 0.5 / value),
+            TokenType::CBRT => ("cbrt", 1.0 / (3.0 * value * value)),
+            TokenType::SIN => ("sin", x.cos()),
+            TokenType::COS => ("cos", -x.sin()),
+            TokenType::TAN => ("tan", 1.0 / (x.cos() * x.cos())),
+            TokenType::LOG => ("log", 1.0 / (x * LN_10)),
+            TokenType::LN => ("ln", 1.0 / x),
+            TokenType::EXP => ("exp", value),
+            // Step functions are flat almost everywhere.
+            TokenType::FLOOR => ("floor", 0.0),
+            TokenType::CEIL => ("ceil", 0.0),
+            TokenType::ROUND => ("round", 0.0),
+            _ => return Err(Error::missing_some_tokens(input.to_string(), span.end)),
+        };
+
+        Uncertain::propagate(name, value, &[(&arg, derivative)])
+    }
+
+    fn factorial(self) -> Result<Self, Error> {
+        match self.to_number() {
+            Some(n) => Ok(Uncertain::exact(Calculator::compute_factorial(n)?)),
+            None => Err(Error::out_of_domain(format!(
+                "error: factorial is only defined for exact integers, not for {self}"
+            ))),
+        }
+    }
+
+    fn abs(self) -> Self {
+        Uncertain::abs(&self)
+    }
+
+    fn interval(_lo: Self, _hi: Self) -> Result<Self, Error> {
+        Err(Error::unsupported_in_mode(
+            "interval literal",
+            "uncertainty",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(result: &Uncertain, value: f64, sigma: f64) {
+        assert!(
+            (result.value() - value).abs() < 1e-12 && (result.sigma() - sigma).abs() < 1e-12,
+            "expected: {value} ± {sigma}, got: {result}"
+        );
+    }
+
+    #[test]
+    fn new() {
+        let x = Uncertain::new(12.3, 0.2).unwrap();
+        assert_eq!(x.value(), 12.3);
+        assert_eq!(x.sigma(), 0.2);
+        assert!(!x.is_exact());
+        assert!(Uncertain::exact(5.0).is_exact());
+
+        assert!(Uncertain::new(1.0, -0.1).is_err());
+        assert!(Uncertain::new(1.0, f64::NAN).is_err());
+        assert!(Uncertain::new(1.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let x = Uncertain::new(3.0, 0.3).unwrap();
+        let y = Uncertain::new(4.0, 0.4).unwrap();
+
+        assert_close(&x.add(&y), 7.0, 0.5);
+        assert_close(&x.sub(&y), -1.0, 0.5);
+        assert_close(&x.mul(&y), 12.0, (1.2_f64 * 1.2 + 1.2 * 1.2).sqrt());
+        assert_close(&x.div(&y).unwrap(), 0.75, (0.075_f64.powi(2) * 2.0).sqrt());
+        assert_close(&x.mul(&Uncertain::exact(2.0)), 6.0, 0.6);
+        assert_eq!(x.div(&Uncertain::exact(0.0)), None);
+    }
+
+    #[test]
+    fn correlation() {
+        let x = Uncertain::new(3.0, 0.3).unwrap();
+
+        assert_close(&x.sub(&x), 0.0, 0.0);
+        assert!(x.sub(&x).is_exact());
+        assert_close(&x.add(&x), 6.0, 0.6);
+        assert_close(&x.div(&x).unwrap(), 1.0, 0.0);
+        assert_close(&x.mul(&x), 9.0, 1.8);
+
+        let y = Uncertain::new(3.0, 0.3).unwrap();
+        assert_close(&x.sub(&y), 0.0, (0.18_f64).sqrt());
+        assert_eq!(x.covariance(&y), 0.0);
+        assert!((x.covariance(&x) - 0.09).abs() < 1e-15);
+    }
+
+    #[test]
+    fn pow() {
+        let x = Uncertain::new(3.0, 0.1).unwrap();
+
+        assert_close(&x.pow(&Uncertain::exact(2.0)).unwrap(), 9.0, 0.6);
+        assert_close(
+            &Uncertain::exact(4.0).pow(&Uncertain::exact(0.5)).unwrap(),
+            2.0,
+            0.0,
+        );
+
+        let exp = Uncertain::exact(2.0).pow(&x).unwrap();
+        assert_close(&exp, 8.0, 8.0 * 2.0_f64.ln() * 0.1);
+
+        let zero = Uncertain::new(0.0, 0.1).unwrap();
+        assert!(zero.pow(&Uncertain::exact(0.5)).is_err());
+        assert!(Uncertain::exact(-2.0).pow(&x).is_err());
+    }
+
+    #[test]
+    fn functions() {
+        let x = Uncertain::new(4.0, 0.2).unwrap();
+
+        let sqrt = Uncertain::function(TokenType::SQRT, x.clone(), "", Span::default()).unwrap();
+        assert_close(&sqrt, 2.0, 0.05);
+
+        let ln = Uncertain::function(TokenType::LN, x.clone(), "", Span::default()).unwrap();
+        assert_close(&ln, 4.0_f64.ln(), 0.05);
+
+        let sin = Uncertain::function(TokenType::SIN, x.clone(), "", Span::default()).unwrap();
+        assert_close(&sin, 4.0_f64.sin(), (4.0_f64.cos() * 0.2).abs());
+
+        let floor = Uncertain::function(TokenType::FLOOR, x.clone(), "", Span::default()).unwrap();
+        assert_close(&floor, 4.0, 0.0);
+
+        let negative = Uncertain::new(-1.0, 0.1).unwrap();
+        assert!(
+            Uncertain::function(TokenType::SQRT, negative.clone(), "", Span::default()).is_err()
+        );
+        assert_close(&negative.abs(), 1.0, 0.1);
+
+        let zero = Uncertain::new(0.0, 0.1).unwrap();
+        assert!(Uncertain::function(TokenType::SQRT, zero, "", Span::default()).is_err());
+    }
+
+    #[test]
+    fn plus_minus() {
+        let result = Uncertain::operation(
+            Uncertain::exact(12.3),
+            Uncertain::exact(0.2),
+            TokenType::PLUSMINUS,
+            "",
+            Span::default(),
+        );
+        assert_close(&result.unwrap(), 12.3, 0.2);
+
+        let negative = Uncertain::operation(
+            Uncertain::exact(12.3),
+            Uncertain::exact(-0.2),
+            TokenType::PLUSMINUS,
+            "",
+            Span::default(),
+        );
+        assert!(negative.is_err());
+
+        let uncertain_sigma = Uncertain::operation(
+            Uncertain::exact(12.3),
+            Uncertain::new(0.2, 0.1).unwrap(),
+            TokenType::PLUSMINUS,
+            "",
+            Span::default(),
+        );
+        assert!(uncertain_sigma.is_err());
+    }
+
+    #[test]
+    fn invalid_operation() {
+        let result = Uncertain::operation(
+            Uncertain::exact(2.0),
+            Uncertain::exact(3.0),
+            TokenType::FACTORIAL,
+            "",
+            Span::default(),
+        );
+        assert_eq!(result, Err(Error::invalid_operation(&TokenType::FACTORIAL)));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Uncertain::new(12.3, 0.2).unwrap().to_string(), "12.3 ± 0.2");
+        assert_eq!(Uncertain::exact(5.0).to_string(), "5 ± 0");
+    }
+}
@@ -5,6 +5,44 @@
 //
 
 use crate::token::{Token, TokenType};
+use std::f64::consts::{PI, TAU};
+
+// Unicode math symbols, that are pasted from word processors and rendered formulas,
+// with the spelling that lexer reads natively.
+const SYMBOLS: [(char, &str); 20] = [
+    ('×', "*"),
+    ('⋅', "*"),
+    ('·', "*"),
+    ('÷', "/"),
+    ('−', "-"),
+    ('√', "sqrt"),
+    ('∛', "cbrt"),
+    ('⁰', "0"),
+    ('¹', "1"),
+    ('²', "2"),
+    ('³', "3"),
+    ('⁴', "4"),
+    ('⁵', "5"),
+    ('⁶', "6"),
+    ('⁷', "7"),
+    ('⁸', "8"),
+    ('⁹', "9"),
+    ('⁺', "+"),
+    ('⁻', "-"),
+    ('∗', "*"),
+];
+
+// Symbols of mathematical constants, that are read as numbers.
+const CONSTANTS: [(&str, f64); 3] = [("π", PI), ("τ", TAU), ("∞", f64::INFINITY)];
+
+// Invisible characters, that are pasted along with text from word processors and web pages,
+// but aren't whitespace for [char::is_whitespace]: zero width space, word joiner and BOM.
+const INVISIBLES: [char; 3] = ['\u{200b}', '\u{2060}', '\u{feff}'];
+
+// Checks if the character only separates tokens, like a space or an invisible character.
+pub fn is_space(c: char) -> bool {
+    c.is_whitespace() || INVISIBLES.contains(&c)
+}
 
 //
 // A interface for custom char-type-checking utility methods.
@@ -22,22 +60,22 @@ pub trait ChUtils {
     // Checks if the given [&self] object is plus sign or minus sign.
     //
     // Plus signs   --> <+>
-    // Minus signs  --> <->
+    // Minus signs  --> <-> and <−>
     fn is_plus_or_minus(&self) -> bool;
 
     // Checks if the given [&self] object is division sign or multiplication sign.
     //
-    // Division signs        --> <:> and </>
-    // Multiplication signs  --> <*> and <•>
+    // Division signs        --> <:>, </> and <÷>
+    // Multiplication signs  --> <*>, <•>, <×>, <⋅>, <·> and <∗>
     fn is_div_or_prod(&self) -> bool;
 
     // A function that combines [is_plus_or_minus] and [is_div_or_prod].
     // So, it checks if [&self] object is operation sign or not.
     //
     // Plus signs            --> <+>
-    // Minus signs           --> <->
-    // Division signs        --> <:> and </>
-    // Multiplication signs  --> <*> and <•>
+    // Minus signs           --> <-> and <−>
+    // Division signs        --> <:>, </> and <÷>
+    // Multiplication signs  --> <*>, <•>, <×>, <⋅>, <·> and <∗>
     fn is_operation_sign(&self) -> bool;
 
     // Checks if the given [&self] object is left parentheses or right parentheses sign.
@@ -48,12 +86,33 @@ pub trait ChUtils {
 
     // Checks if the given [&self] object is left abs or right abs sign.
     //
-    // Left  ABS --> [
-    // Right ABS --> ]
+    // Left  ABS --> [ and |
+    // Right ABS --> ] and |
+    //
+    // The bar is both, lexer decides which one it's by the tokens before it.
     fn is_abs(&self) -> (bool, bool);
 
     // Checks if the given [%self] object is percentage sign or not.
     fn is_percentage(&self) -> bool;
+
+    // Checks if the given [&self] object is made of superscript digits and signs,
+    // like <²> in x² or <⁻¹> in x⁻¹.
+    fn is_superscript(&self) -> bool;
+
+    // Returns the value of the given [&self] object, if it's a symbol of constant.
+    //
+    // Pi       --> <π>
+    // Tau      --> <τ>
+    // Infinity --> <∞>
+    fn constant(&self) -> Option<f64>;
+
+    // Converts the unicode math symbols of [&self] object to the spelling, that lexer
+    // reads natively. Everything else is kept as it's.
+    //
+    //  ╭───╮     ╭───╮    ╭───╮     ╭──────╮    ╭────╮     ╭────╮
+    //  │ × │ ──▶ │ * │    │ √ │ ──▶ │ sqrt │    │ ⁻¹ │ ──▶ │ -1 │
+    //  ╰───╯     ╰───╯    ╰───╯     ╰──────╯    ╰────╯     ╰────╯
+    fn normalized(&self) -> String;
 }
 
 impl ChUtils for String {
@@ -101,12 +160,12 @@ impl ChUtils for String {
     }
 
     fn is_plus_or_minus(&self) -> bool {
-        self.trim().eq("+") || self.trim().eq("-")
+        matches!(self.trim(), "+" | "-" | "−")
     }
 
     fn is_div_or_prod(&self) -> bool {
-        let is_div: bool = self.trim().eq(":") || self.trim().eq("/");
-        let is_prod: bool = self.trim().eq("*") || self.trim().eq("•");
+        let is_div: bool = matches!(self.trim(), ":" | "/" | "÷");
+        let is_prod: bool = matches!(self.trim(), "*" | "•" | "×" | "⋅" | "·" | "∗");
 
         is_div || is_prod
     }
@@ -120,12 +179,31 @@ impl ChUtils for String {
     }
 
     fn is_abs(&self) -> (bool, bool) {
-        (self.trim().eq("["), self.trim().eq("]"))
+        let bar = self.trim().eq("|");
+        (self.trim().eq("[") || bar, self.trim().eq("]") || bar)
     }
 
     fn is_percentage(&self) -> bool {
         self.trim().eq("%")
     }
+
+    fn is_superscript(&self) -> bool {
+        let trimmed = self.trim();
+        !trimmed.is_empty() && trimmed.chars().all(|c| matches!(c, '⁰' | '¹' | '²' | '³' | '⁴'..='⁹' | '⁺' | '⁻'))
+    }
+
+    fn constant(&self) -> Option<f64> {
+        CONSTANTS.iter().find(|(symbol, _)| *symbol == self.trim()).map(|(_, value)| *value)
+    }
+
+    fn normalized(&self) -> String {
+        self.chars()
+            .map(|c| match SYMBOLS.iter().find(|(symbol, _)| *symbol == c) {
+                Some((_, spelling)) => spelling.to_string(),
+                None => c.to_string(),
+            })
+            .collect()
+    }
 }
 
 impl ChUtils for Token {
@@ -168,12 +246,28 @@ impl ChUtils for Token {
     fn is_percentage(&self) -> bool {
         matches!(self.typ, TokenType::PERCENTAGE)
     }
+
+    fn is_superscript(&self) -> bool {
+        false
+    }
+
+    fn constant(&self) -> Option<f64> {
+        match self.typ {
+            TokenType::NUMBER => self.literal.constant(),
+            _ => None,
+        }
+    }
+
+    fn normalized(&self) -> String {
+        self.literal.normalized()
+    }
 }
 
 // Includes tests for only String implementation of [ChUtils].
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::span::Span;
     use std::collections::HashMap;
 
     #[test]
@@ -189,7 +283,7 @@ mod tests {
 
         for (target, expected) in test_data {
             assert_eq!(target.is_number(), expected);
-            assert_eq!(Token::from(target, (0, 0)).is_number(), expected);
+            assert_eq!(Token::from(target, Span::default()).is_number(), expected);
         }
     }
 
@@ -214,6 +308,7 @@ mod tests {
         let test_data: HashMap<String, bool> = HashMap::from([
             (String::from("-"), true),
             (String::from("+"), true),
+            (String::from("−"), true),
             (String::from("/"), false),
             (String::from(".5"), false),
             (String::from("/"), false),
@@ -222,7 +317,7 @@ mod tests {
 
         for (target, expected) in test_data {
             assert_eq!(target.is_plus_or_minus(), expected);
-            assert_eq!(Token::from(target, (0, 0)).is_plus_or_minus(), expected);
+            assert_eq!(Token::from(target, Span::default()).is_plus_or_minus(), expected);
         }
     }
 
@@ -233,6 +328,9 @@ mod tests {
             (String::from("*"), true),
             (String::from(":"), true),
             (String::from("•"), true),
+            (String::from("×"), true),
+            (String::from("÷"), true),
+            (String::from("⋅"), true),
             (String::from("-"), false),
             (String::from("+"), false),
             (String::from(".5"), false),
@@ -240,7 +338,7 @@ mod tests {
 
         for (target, expected) in test_data {
             assert_eq!(target.is_div_or_prod(), expected);
-            assert_eq!(Token::from(target, (0, 0)).is_div_or_prod(), expected);
+            assert_eq!(Token::from(target, Span::default()).is_div_or_prod(), expected);
         }
     }
 
@@ -260,7 +358,7 @@ mod tests {
 
         for (target, expected) in test_data {
             assert_eq!(target.is_operation_sign(), expected);
-            assert_eq!(Token::from(target, (0, 0)).is_operation_sign(), expected);
+            assert_eq!(Token::from(target, Span::default()).is_operation_sign(), expected);
         }
     }
 
@@ -280,7 +378,7 @@ mod tests {
 
         for (target, expected) in test_data {
             assert_eq!(target.is_parentheses(), expected);
-            assert_eq!(Token::from(target, (0, 0)).is_parentheses(), expected);
+            assert_eq!(Token::from(target, Span::default()).is_parentheses(), expected);
         }
     }
 
@@ -300,8 +398,11 @@ mod tests {
 
         for (target, expected) in test_data {
             assert_eq!(target.is_abs(), expected);
-            assert_eq!(Token::from(target, (0, 0)).is_abs(), expected);
+            assert_eq!(Token::from(target, Span::default()).is_abs(), expected);
         }
+
+        // A bar is both, and lexer decides which one it's.
+        assert_eq!(String::from("|").is_abs(), (true, true));
     }
 
     #[test]
@@ -315,7 +416,57 @@ mod tests {
 
         for (expected, data) in test_data {
             assert_eq!(expected, data.is_percentage());
-            assert_eq!(expected, Token::from(data, (0, 0)).is_percentage());
+            assert_eq!(expected, Token::from(data, Span::default()).is_percentage());
+        }
+    }
+
+    #[test]
+    fn is_superscript() {
+        let test_data: Vec<(&str, bool)> = vec![
+            ("²", true),
+            ("⁻¹", true),
+            ("¹⁰", true),
+            ("2", false),
+            ("x²", false),
+            ("", false),
+        ];
+
+        for (target, expected) in test_data {
+            assert_eq!(target.to_string().is_superscript(), expected, "Failed for: {target}");
+        }
+    }
+
+    #[test]
+    fn constant() {
+        let test_data: Vec<(&str, Option<f64>)> = vec![
+            ("π", Some(PI)),
+            ("τ", Some(TAU)),
+            ("∞", Some(f64::INFINITY)),
+            ("pi", None),
+            ("3", None),
+        ];
+
+        for (target, expected) in test_data {
+            assert_eq!(target.to_string().constant(), expected);
+            assert_eq!(Token::from(target.to_string(), Span::default()).constant(), expected);
+        }
+    }
+
+    #[test]
+    fn normalized() {
+        let test_data: Vec<(&str, &str)> = vec![
+            ("2 × 3 ÷ 4", "2 * 3 / 4"),
+            ("5 − 1", "5 - 1"),
+            ("2 ⋅ x · y", "2 * x * y"),
+            ("√", "sqrt"),
+            ("∛", "cbrt"),
+            ("⁻¹²", "-12"),
+            ("π", "π"),
+            ("[x]", "[x]"),
+        ];
+
+        for (target, expected) in test_data {
+            assert_eq!(target.to_string().normalized(), expected);
         }
     }
 }
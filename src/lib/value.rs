@@ -0,0 +1,155 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use crate::{
+    errors::Error, interval::Interval, span::Span, token::TokenType, uncertain::Uncertain,
+};
+use std::fmt;
+
+// The value that a variable of [Environment] can hold,
+// and that [Calculator] can produce as a result.
+//
+// - [Number] is a plain floating point number.
+// - [Interval] is a closed range of numbers, like `[9.8, 9.82]`.
+// - [Uncertain] is a number with standard uncertainty, like `12.3 ± 0.2`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Interval(Interval),
+    Uncertain(Uncertain),
+}
+
+impl Value {
+    // Returns the plain number, if the value is a number.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    // Returns the interval, if the value is an interval.
+    pub fn as_interval(&self) -> Option<Interval> {
+        match self {
+            Value::Interval(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    // Returns the uncertain value, if the value is an uncertain value.
+    pub fn as_uncertain(&self) -> Option<&Uncertain> {
+        match self {
+            Value::Uncertain(u) => Some(u),
+            _ => None,
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Number(_))
+    }
+
+    pub fn is_interval(&self) -> bool {
+        matches!(self, Value::Interval(_))
+    }
+
+    pub fn is_uncertain(&self) -> bool {
+        matches!(self, Value::Uncertain(_))
+    }
+
+    // The human readable name of value's kind, used in error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::Interval(_) => "an interval",
+            Value::Uncertain(_) => "an uncertain value",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{n}"),
+            Value::Interval(i) => write!(f, "{i}"),
+            Value::Uncertain(u) => write!(f, "{u}"),
+        }
+    }
+}
+
+// The value domain that [Calculator] evaluates token trees in.
+//
+// The X/O/Y algorithm of calculator is the same for every domain, only the way
+// how numbers are combined differs. So, plain numbers ([f64]), intervals ([Interval])
+// and uncertain values ([Uncertain]) implement the operations by their own rules.
+pub(crate) trait Domain: Clone + Sized {
+    // Converts a plain number to the domain.
+    fn from_number(n: f64) -> Self;
+
+    // Converts the value of variable [name] to the domain.
+    fn from_value(name: &str, value: &Value) -> Result<Self, Error>;
+
+    // Converts the domain value to a variable value.
+    fn into_value(self) -> Value;
+
+    // Returns the plain number representation, if the value is an exact number.
+    fn to_number(&self) -> Option<f64>;
+
+    // Executes the given [operation] for [X] and [Y].
+    fn operation(
+        x: Self,
+        y: Self,
+        operation: TokenType,
+        input: &str,
+        span: Span,
+    ) -> Result<Self, Error>;
+
+    // Executes a math function on the given argument.
+    fn function(func: TokenType, arg: Self, input: &str, span: Span) -> Result<Self, Error>;
+
+    // Computes the factorial of the value.
+    fn factorial(self) -> Result<Self, Error>;
+
+    // Computes the absolute value of the value.
+    fn abs(self) -> Self;
+
+    // Creates a value from an interval literal: `[lo, hi]`.
+    fn interval(lo: Self, hi: Self) -> Result<Self, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accessors() {
+        let number = Value::Number(4.2);
+        let interval = Value::Interval(Interval::new(1.0, 2.0).unwrap());
+        let uncertain = Value::Uncertain(Uncertain::new(1.0, 0.1).unwrap());
+
+        assert_eq!(number.as_number(), Some(4.2));
+        assert_eq!(number.as_interval(), None);
+        assert!(number.is_number());
+        assert_eq!(interval.as_number(), None);
+        assert_eq!(interval.as_interval(), Interval::new(1.0, 2.0).ok());
+        assert!(interval.is_interval());
+        assert_eq!(uncertain.as_number(), None);
+        assert_eq!(uncertain.as_uncertain().map(Uncertain::value), Some(1.0));
+        assert!(uncertain.is_uncertain());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Value::Number(42.0).to_string(), "42");
+        assert_eq!(
+            Value::Interval(Interval::new(1.5, 2.0).unwrap()).to_string(),
+            "[1.5, 2]"
+        );
+        assert_eq!(
+            Value::Uncertain(Uncertain::new(12.3, 0.2).unwrap()).to_string(),
+            "12.3 ± 0.2"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+// mate-lsp - the language server of mate scripts, speaking LSP over stdio.
+//
+// Scripts are analyzed by [Analysis] on each change:
+//  - diagnostics, from errors of lexer and calculator,
+//  - hover, with the current value of variable or the signature of function,
+//  - completion of built-in functions and defined variables,
+//  - inlay hints, with the result of each statement.
+//
+// Positions are counted in characters, which match UTF-16 code units of LSP
+// for every character that mate understands.
+
+use mate_rs::analysis::{Analysis, CompletionKind};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    process,
+};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// The codes of JSON-RPC errors, used by LSP.
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+#[derive(Default)]
+struct LanguageServer {
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl LanguageServer {
+    // Handles a single message, and returns the messages to send back:
+    // the response of request, and notifications like published diagnostics.
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = message.get("id").cloned();
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": [] },
+                    "inlayHintProvider": true,
+                },
+                "serverInfo": { "name": "mate-lsp", "version": VERSION },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "exit" => process::exit(if self.shutdown { 0 } else { 1 }),
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_string(), text.to_string());
+                return vec![self.diagnostics(uri)];
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                return vec![self.diagnostics(uri)];
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                return vec![publish(uri, Vec::new())];
+            }
+            "textDocument/hover" => self.analysis(params).map(|(analysis, line, character)| {
+                match analysis.hover(line, character) {
+                    Some(hover) => json!({
+                        "contents": { "kind": "markdown", "value": format!("```\n{hover}\n```") },
+                    }),
+                    None => Value::Null,
+                }
+            }),
+            "textDocument/completion" => {
+                self.analysis(params).map(|(analysis, line, character)| {
+                    let prefix = analysis.prefix_at(line, character);
+                    let items: Vec<Value> = analysis
+                        .completions(&prefix)
+                        .into_iter()
+                        .map(|c| {
+                            let kind = match c.kind {
+                                CompletionKind::Function => 3,
+                                CompletionKind::Variable => 6,
+                                CompletionKind::Constant => 21,
+                            };
+                            json!({
+                                "label": c.label,
+                                "kind": kind,
+                                "detail": c.detail,
+                                "insertText": c.insert,
+                            })
+                        })
+                        .collect();
+                    Value::Array(items)
+                })
+            }
+            "textDocument/inlayHint" => self.analysis(params).map(|(analysis, _, _)| {
+                let from = params["range"]["start"]["line"].as_u64().unwrap_or(0) as usize;
+                let to = params["range"]["end"]["line"].as_u64().unwrap_or(u64::MAX) as usize;
+                let hints: Vec<Value> = analysis
+                    .hints()
+                    .iter()
+                    .filter(|h| h.line >= from && h.line <= to)
+                    .map(|h| {
+                        json!({
+                            "position": { "line": h.line, "character": h.column },
+                            "label": h.label,
+                            "paddingLeft": true,
+                        })
+                    })
+                    .collect();
+                Value::Array(hints)
+            }),
+            _ => Err((METHOD_NOT_FOUND, format!("method not found: {method}"))),
+        };
+
+        // Notifications are never answered, even on failure.
+        let id = match id {
+            Some(id) => id,
+            None => return Vec::new(),
+        };
+
+        match result {
+            Ok(result) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+            Err((code, message)) => vec![json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            })],
+        }
+    }
+
+    // Analyzes the document of request, and takes the position of it.
+    fn analysis(&self, params: &Value) -> Result<(Analysis, usize, usize), (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let text = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("unknown document: {uri}")))?;
+
+        let line = params["position"]["line"].as_u64().unwrap_or(0) as usize;
+        let character = params["position"]["character"].as_u64().unwrap_or(0) as usize;
+        Ok((Analysis::new(text), line, character))
+    }
+
+    fn diagnostics(&self, uri: &str) -> Value {
+        let text = self
+            .documents
+            .get(uri)
+            .map(String::as_str)
+            .unwrap_or_default();
+        let diagnostics = Analysis::new(text)
+            .diagnostics()
+            .iter()
+            .map(|d| {
+                json!({
+                    "range": {
+                        "start": { "line": d.line, "character": d.start },
+                        "end": { "line": d.line, "character": d.end },
+                    },
+                    "severity": 1,
+                    "source": "mate",
+                    "message": d.message,
+                })
+            })
+            .collect();
+
+        publish(uri, diagnostics)
+    }
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+// Reads a single message: headers, an empty line, and the content of `Content-Length` bytes.
+// Returns none at the end of input.
+fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Value>> {
+    let mut length: Option<usize> = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+
+    let mut content = vec![0; length.unwrap_or(0)];
+    reader.read_exact(&mut content)?;
+    Ok(Some(
+        serde_json::from_slice(&content).unwrap_or(Value::Null),
+    ))
+}
+
+fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush()
+}
+
+fn main() {
+    let mut server = LanguageServer::default();
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+
+    loop {
+        let message = match read_message(&mut stdin) {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("mate-lsp: {e}");
+                process::exit(1);
+            }
+        };
+
+        for reply in server.handle(&message) {
+            if let Err(e) = write_message(&mut stdout, &reply) {
+                eprintln!("mate-lsp: {e}");
+                process::exit(1);
+            }
+        }
+    }
+}
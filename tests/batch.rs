@@ -0,0 +1,66 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use serde_json::Value;
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+// Runs mate in batch mode with the given [output] format, and returns its exit code and lines.
+fn batch(output: &str, input: &str) -> (i32, Vec<String>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mate"))
+        .args(["--output", output])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let result = child.wait_with_output().unwrap();
+    let lines = String::from_utf8(result.stdout)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+
+    (result.status.code().unwrap(), lines)
+}
+
+#[test]
+fn json() {
+    let (code, lines) = batch("json", "0.1 + 0.2\n[1, 2] + 1\nx + 1\n");
+    assert_eq!(code, 1);
+
+    let records: Vec<Value> = lines
+        .iter()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(records[0]["value"]["number"], 0.1 + 0.2);
+    assert_eq!(records[0]["value"]["kind"], "number");
+    assert_eq!(records[1]["value"]["value"], "[2, 3]");
+    assert_eq!(records[1]["value"]["number"], Value::Null);
+    assert_eq!(records[2]["error"]["kind"], "UndefinedVariable");
+}
+
+#[test]
+fn csv() {
+    let (code, lines) = batch("csv", "2 * 2\n1 / 0\n");
+    assert_eq!(code, 1);
+    assert_eq!(
+        lines,
+        vec![
+            "input,value,number,error_kind,error_point,error_message",
+            "2 * 2,4,4,,,",
+            "1 / 0,,,DivisionByZero,4,error: division by zero",
+        ]
+    );
+}
@@ -0,0 +1,61 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use serde_json::{json, Value};
+use std::process::Command;
+
+// Runs mate with the given arguments, and returns its exit code and lines of output.
+fn mate(args: &[&str]) -> (i32, Vec<String>) {
+    let result = Command::new(env!("CARGO_BIN_EXE_mate"))
+        .args(args)
+        .env("COLUMNS", "40")
+        .output()
+        .unwrap();
+    let lines = String::from_utf8(result.stdout)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+
+    (result.status.code().unwrap(), lines)
+}
+
+#[test]
+fn options_after_plot_and_table() {
+    let (code, lines) = mate(&["--plot", "sin(x) from 0 to 3", "--ascii"]);
+    assert_eq!(code, 0);
+    assert!(lines.iter().any(|l| l.contains('*')));
+    let braille = |c: char| ('\u{2800}'..='\u{28ff}').contains(&c);
+    assert!(lines.iter().all(|l| !l.chars().any(braille)));
+
+    let (code, lines) = mate(&["--table", "x ^ 2 for x = 0..2", "--output", "csv"]);
+    assert_eq!(code, 0);
+    assert_eq!(lines, vec!["x,x ^ 2", "0,0", "1,1", "2,4"]);
+}
+
+#[test]
+fn table_json() {
+    let spec = "x ^ 2 / 2, 1 / (x - 1) for x = 0..1";
+    let (code, lines) = mate(&["--table", spec, "--output", "json", "-p", "2"]);
+    assert_eq!(code, 0);
+
+    let rows: Vec<Value> = lines
+        .iter()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(rows[0]["x"], 0.0);
+    assert_eq!(
+        rows[0]["results"][1],
+        json!({ "value": -1.0, "text": "-1.00", "error": null })
+    );
+    assert_eq!(
+        (&rows[1]["x"], &rows[1]["text"]),
+        (&json!(1.0), &json!("1.00"))
+    );
+    assert_eq!(rows[1]["results"][0]["value"], 0.5);
+    assert_eq!(rows[1]["results"][1]["value"], Value::Null);
+    assert_eq!(rows[1]["results"][1]["error"]["kind"], "DivisionByZero");
+}
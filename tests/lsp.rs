@@ -0,0 +1,127 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{ChildStdout, Command, Stdio},
+};
+
+fn send(stdin: &mut impl Write, message: Value) {
+    let content = message.to_string();
+    write!(stdin, "Content-Length: {}\r\n\r\n{content}", content.len()).unwrap();
+    stdin.flush().unwrap();
+}
+
+fn receive(stdout: &mut BufReader<ChildStdout>) -> Value {
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        stdout.read_line(&mut header).unwrap();
+        match header.trim().split_once(':') {
+            Some((_, value)) => length = value.trim().parse().unwrap(),
+            None => break,
+        }
+    }
+
+    let mut content = vec![0; length];
+    stdout.read_exact(&mut content).unwrap();
+    serde_json::from_slice(&content).unwrap()
+}
+
+#[test]
+fn lsp() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mate-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let uri = "file:///circle.mate";
+    let document = json!({ "uri": uri });
+
+    send(
+        &mut stdin,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+    );
+    let response = receive(&mut stdout);
+    assert_eq!(response["result"]["capabilities"]["hoverProvider"], true);
+
+    let text = "r = 7\nr / 0\nsqrt(r) * 2";
+    send(
+        &mut stdin,
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "languageId": "mate", "version": 1, "text": text } },
+        }),
+    );
+    let notification = receive(&mut stdout);
+    assert_eq!(notification["method"], "textDocument/publishDiagnostics");
+    let diagnostics = notification["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0]["range"]["start"],
+        json!({ "line": 1, "character": 4 })
+    );
+    assert_eq!(diagnostics[0]["message"], "error: division by zero");
+
+    let position = json!({ "line": 2, "character": 1 });
+    send(
+        &mut stdin,
+        json!({
+            "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover",
+            "params": { "textDocument": document, "position": position },
+        }),
+    );
+    let response = receive(&mut stdout);
+    assert!(response["result"]["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .contains("sqrt(x)"));
+
+    let position = json!({ "line": 2, "character": 6 });
+    send(
+        &mut stdin,
+        json!({
+            "jsonrpc": "2.0", "id": 3, "method": "textDocument/completion",
+            "params": { "textDocument": document, "position": position },
+        }),
+    );
+    let response = receive(&mut stdout);
+    let labels: Vec<&str> = response["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(labels, vec!["round", "r"]);
+
+    let range =
+        json!({ "start": { "line": 0, "character": 0 }, "end": { "line": 3, "character": 0 } });
+    send(
+        &mut stdin,
+        json!({
+            "jsonrpc": "2.0", "id": 4, "method": "textDocument/inlayHint",
+            "params": { "textDocument": document, "range": range },
+        }),
+    );
+    let response = receive(&mut stdout);
+    let hints = response["result"].as_array().unwrap();
+    assert_eq!(hints.len(), 2);
+    assert_eq!(hints[0]["label"], "= 7");
+    assert_eq!(hints[1]["position"], json!({ "line": 2, "character": 11 }));
+
+    send(
+        &mut stdin,
+        json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+    );
+    assert_eq!(receive(&mut stdout)["result"], Value::Null);
+    send(&mut stdin, json!({ "jsonrpc": "2.0", "method": "exit" }));
+    assert!(child.wait().unwrap().success());
+}
@@ -0,0 +1,85 @@
+//
+// Copyright 2022-present theiskaa. All rights reserved.
+// Use of this source code is governed by MIT license
+// that can be found in the LICENSE file.
+//
+
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Command, Stdio},
+};
+
+#[test]
+fn serve() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mate"))
+        .arg("--serve")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    let mut call = |id: i64, method: &str, params: Value| -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        writeln!(stdin, "{request}").unwrap();
+
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    };
+
+    let response = call(1, "setVariable", json!({ "name": "r", "value": 2 }));
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["value"], "2");
+
+    let response = call(2, "evaluate", json!({ "expression": "r ^ 2 * 3" }));
+    assert_eq!(response["id"], 2);
+    assert_eq!(response["result"]["number"], 12.0);
+
+    let response = call(3, "listVariables", Value::Null);
+    assert_eq!(
+        response["result"],
+        json!({ "r": { "value": "2", "kind": "number", "number": 2.0 } })
+    );
+
+    let response = call(4, "evaluate", json!({ "expression": "r / 0" }));
+    assert_eq!(response["error"]["message"], "error: division by zero");
+    assert_eq!(response["error"]["data"]["kind"], "DivisionByZero");
+
+    let response = call(5, "reset", Value::Null);
+    assert_eq!(response["result"], Value::Null);
+
+    let response = call(6, "evaluate", json!({ "expression": "r" }));
+    assert_eq!(
+        response["error"]["message"],
+        "error: undefined variable 'r'"
+    );
+    assert_eq!(response["error"]["data"]["kind"], "UndefinedVariable");
+
+    let test_data: Vec<(&str, &str)> = vec![
+        ("2 $ 3", "IllegalToken"),
+        ("(2 + 3", "MismatchedBrackets"),
+        ("2 +", "MissingTokens"),
+        ("sqrt(-1)", "OutOfDomain"),
+        ("integrate(x, 0, 1)", "InvalidArguments"),
+        ("[1, 2] / [-1, 1]", "DivisionByZero"),
+    ];
+
+    for (i, (expression, kind)) in test_data.into_iter().enumerate() {
+        let response = call(
+            7 + i as i64,
+            "evaluate",
+            json!({ "expression": expression }),
+        );
+        assert_eq!(
+            response["error"]["data"]["kind"], kind,
+            "Failed for: {expression}"
+        );
+    }
+
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+}